@@ -0,0 +1,41 @@
+//! Bulk vs one-by-one insertion of a generated area into the environment manager.
+//!
+//! `cargo bench --bench environment`
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use kbve_bugwars::game::{ChunkCoord, EnvironmentGenerator, EnvironmentManager};
+
+fn add_objects_vs_add_object(c: &mut Criterion) {
+    let generator = EnvironmentGenerator::new(42, 50.0);
+    let objects = generator.generate_area(&ChunkCoord { x: 0, z: 0 }, 10);
+
+    let mut group = c.benchmark_group("environment_insert");
+    group.sample_size(10);
+    group.bench_function("add_object", |b| {
+        b.iter_batched(
+            || (EnvironmentManager::new(50.0, 3, 10.0), objects.clone()),
+            |(manager, batch)| {
+                for object in batch {
+                    manager.add_object(object).unwrap();
+                }
+                manager
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("add_objects", |b| {
+        b.iter_batched(
+            || (EnvironmentManager::new(50.0, 3, 10.0), objects.clone()),
+            |(manager, batch)| {
+                manager.add_objects(batch);
+                manager
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, add_objects_vs_add_object);
+criterion_main!(benches);
@@ -1,6 +1,14 @@
 // src/auth/jwt_cache.rs
 // JWT cache using DashMap for concurrent access across HTTP/TCP/gRPC
+//
+// Multi-tenant: besides the default Supabase project, named tenants can be registered.
+// A token is verified against the tenant named by the caller (e.g. `?tenant=` on the
+// WebSocket upgrade), else the tenant whose URL matches the token's `iss` claim,
+// else the default project.
 use dashmap::DashMap;
+use futures_util::{stream, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 use tokio::time;
@@ -9,6 +17,7 @@ use tracing::{debug, info, warn};
 const MAX_CACHE_SIZE: usize = 10_000; // Maximum number of cached tokens
 const CLEANUP_INTERVAL: Duration = Duration::from_secs(60); // Cleanup every 60 seconds
 const TOKEN_GRACE_PERIOD: i64 = 300; // 5 minutes grace period before expiry
+const BATCH_VERIFY_CONCURRENCY: usize = 8; // Max concurrent Supabase calls per verify_batch
 
 /// Global service role key - set once at startup, used only for admin operations
 /// This bypasses RLS and has full database access - use with extreme caution
@@ -19,6 +28,8 @@ pub struct TokenInfo {
     pub user_id: String,
     pub email: Option<String>,
     pub role: String,
+    /// Supabase `app_metadata` (server-set, e.g. per-player perks)
+    pub app_metadata: Option<serde_json::Value>,
     pub expires_at: i64, // Unix timestamp
     pub verified_at: Instant,
 }
@@ -28,19 +39,39 @@ impl TokenInfo {
         let now = chrono::Utc::now().timestamp();
         now >= self.expires_at
     }
+}
 
-    pub fn is_near_expiry(&self) -> bool {
-        let now = chrono::Utc::now().timestamp();
-        (self.expires_at - now) <= TOKEN_GRACE_PERIOD
+/// Supabase project a token is verified against
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SupabaseUpstream {
+    pub url: String,
+    pub anon_key: String,
+}
+
+impl SupabaseUpstream {
+    pub fn new(url: impl Into<String>, anon_key: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            anon_key: anon_key.into(),
+        }
+    }
+
+    /// Whether a token issuer is exactly this project's `<url>/auth/v1`
+    fn issued(&self, issuer: &str) -> bool {
+        issuer == format!("{}/auth/v1", self.url.trim_end_matches('/'))
     }
 }
 
 #[derive(Clone)]
 pub struct JwtCache {
     tokens: Arc<DashMap<String, TokenInfo>>,
-    supabase_url: String,
-    supabase_anon_key: String,
+    /// Project used when no tenant is named or matched
+    default_upstream: SupabaseUpstream,
+    /// Named tenant projects
+    tenants: Arc<HashMap<String, SupabaseUpstream>>,
     http_client: reqwest::Client,
+    /// Seconds before expiry at which sessions are warned to refresh
+    grace_period_secs: i64,
 }
 
 impl JwtCache {
@@ -48,13 +79,49 @@ impl JwtCache {
         info!("Initializing JWT cache with Supabase URL: {}", supabase_url);
         Self {
             tokens: Arc::new(DashMap::new()),
-            supabase_url,
-            supabase_anon_key,
+            default_upstream: SupabaseUpstream::new(supabase_url, supabase_anon_key),
+            tenants: Arc::new(HashMap::new()),
             http_client: reqwest::Client::builder()
                 .timeout(Duration::from_secs(5))
                 .build()
                 .expect("Failed to create HTTP client"),
+            grace_period_secs: TOKEN_GRACE_PERIOD,
+        }
+    }
+
+    /// Register a named tenant project
+    pub fn with_tenant(mut self, tenant: impl Into<String>, upstream: SupabaseUpstream) -> Self {
+        let tenant = tenant.into();
+        info!(tenant = %tenant, url = %upstream.url, "Registered Supabase tenant");
+        Arc::make_mut(&mut self.tenants).insert(tenant, upstream);
+        self
+    }
+
+    /// Pick the project to verify a token against
+    /// An explicit tenant must be registered; otherwise the `iss` claim selects a tenant
+    fn resolve_upstream(&self, token: &str, tenant: Option<&str>) -> Result<&SupabaseUpstream, AuthCacheError> {
+        if let Some(tenant) = tenant {
+            return self.tenants
+                .get(tenant)
+                .ok_or_else(|| AuthCacheError::InvalidToken(format!("Unknown tenant: {}", tenant)));
         }
+        let issuer = unverified_claims(token)
+            .ok()
+            .and_then(|claims| claims["iss"].as_str().map(|s| s.to_string()));
+        Ok(issuer
+            .and_then(|iss| self.tenants.values().find(|upstream| upstream.issued(&iss)))
+            .unwrap_or(&self.default_upstream))
+    }
+
+    /// Override the near-expiry grace period (seconds)
+    pub fn with_grace_period(mut self, grace_period_secs: i64) -> Self {
+        self.grace_period_secs = grace_period_secs;
+        self
+    }
+
+    /// Near-expiry grace period in seconds
+    pub fn grace_period_secs(&self) -> i64 {
+        self.grace_period_secs
     }
 
     /// Get a token from the cache if it exists and is not expired
@@ -81,6 +148,11 @@ impl JwtCache {
 
     /// Verify a token against Supabase API and cache the result
     pub async fn verify_and_cache(&self, token: &str) -> Result<TokenInfo, AuthCacheError> {
+        self.verify_and_cache_for(token, None).await
+    }
+
+    /// Verify a token against a named tenant's project (None = resolve from the token)
+    pub async fn verify_and_cache_for(&self, token: &str, tenant: Option<&str>) -> Result<TokenInfo, AuthCacheError> {
         // First check cache (fast path)
         if let Some(info) = self.get(token) {
             debug!(
@@ -97,8 +169,9 @@ impl JwtCache {
             cache_size = self.tokens.len(),
             "JWT cache miss, verifying with Supabase API (slow path)"
         );
+        let upstream = self.resolve_upstream(token, tenant)?;
         let api_start = std::time::Instant::now();
-        let token_info = self.verify_with_supabase(token).await?;
+        let token_info = self.verify_with_supabase(upstream, token).await?;
         let api_duration = api_start.elapsed();
 
         // Cache the verified token
@@ -113,9 +186,28 @@ impl JwtCache {
         Ok(token_info)
     }
 
+    /// Verify many tokens at once; results are in the same order as `tokens`
+    /// Cached tokens are answered immediately, only misses go to Supabase (bounded concurrency)
+    pub async fn verify_batch(&self, tokens: &[String]) -> Vec<Result<TokenInfo, AuthCacheError>> {
+        let mut results: Vec<Option<Result<TokenInfo, AuthCacheError>>> =
+            tokens.iter().map(|token| self.get(token).map(Ok)).collect();
+        let misses: Vec<usize> = (0..tokens.len()).filter(|i| results[*i].is_none()).collect();
+        debug!(tokens = tokens.len(), misses = misses.len(), "Verifying token batch");
+
+        let verified: Vec<_> = stream::iter(misses)
+            .map(|i| async move { (i, self.verify_and_cache(&tokens[i]).await) })
+            .buffer_unordered(BATCH_VERIFY_CONCURRENCY)
+            .collect()
+            .await;
+        for (i, result) in verified {
+            results[i] = Some(result);
+        }
+        results.into_iter().map(|result| result.expect("every token is a hit or was verified")).collect()
+    }
+
     /// Verify token by calling Supabase /auth/v1/user endpoint
-    async fn verify_with_supabase(&self, token: &str) -> Result<TokenInfo, AuthCacheError> {
-        let url = format!("{}/auth/v1/user", self.supabase_url);
+    async fn verify_with_supabase(&self, upstream: &SupabaseUpstream, token: &str) -> Result<TokenInfo, AuthCacheError> {
+        let url = format!("{}/auth/v1/user", upstream.url);
 
         debug!(
             url = %url,
@@ -126,7 +218,7 @@ impl JwtCache {
         let request_start = std::time::Instant::now();
         let response = self.http_client
             .get(&url)
-            .header("apikey", &self.supabase_anon_key)  // Supabase requires the anon key
+            .header("apikey", &upstream.anon_key)       // Supabase requires the anon key
             .bearer_auth(token)                          // And the user's JWT token
             .send()
             .await
@@ -171,21 +263,12 @@ impl JwtCache {
 
         let email = user_data["email"].as_str().map(|s| s.to_string());
         let role = user_data["role"].as_str().unwrap_or("authenticated").to_string();
+        let app_metadata = user_data.get("app_metadata").filter(|v| !v.is_null()).cloned();
 
         // Parse JWT to get expiry time (we still need this for cache management)
-        use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
-        let mut validation = Validation::new(Algorithm::HS256);
-        validation.validate_exp = false; // Don't validate expiry here, Supabase already did
-        validation.validate_aud = false; // Don't validate audience, Supabase already did
-        validation.insecure_disable_signature_validation(); // We trust Supabase's response
-
-        let token_data = decode::<serde_json::Value>(
-            token,
-            &DecodingKey::from_secret(&[]), // Empty secret since we disabled validation
-            &validation,
-        ).map_err(|e| AuthCacheError::InvalidToken(e.to_string()))?;
-
-        let expires_at = token_data.claims["exp"]
+        let claims = unverified_claims(token)?;
+
+        let expires_at = claims["exp"]
             .as_i64()
             .ok_or_else(|| AuthCacheError::InvalidToken("Missing exp claim".to_string()))?;
 
@@ -203,13 +286,14 @@ impl JwtCache {
             user_id,
             email,
             role,
+            app_metadata,
             expires_at,
             verified_at: Instant::now(),
         })
     }
 
     /// Insert a token into the cache
-    fn insert(&self, token: String, info: TokenInfo) {
+    pub(crate) fn insert(&self, token: String, info: TokenInfo) {
         let cache_size = self.tokens.len();
 
         // Check size limit before inserting
@@ -363,6 +447,24 @@ impl JwtCache {
     }
 }
 
+/// Decode a token's claims without checking its signature
+/// Only for routing and cache bookkeeping; Supabase does the actual verification
+fn unverified_claims(token: &str) -> Result<serde_json::Value, AuthCacheError> {
+    use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.validate_exp = false; // Don't validate expiry here, Supabase already did
+    validation.validate_aud = false; // Don't validate audience, Supabase already did
+    validation.insecure_disable_signature_validation(); // We trust Supabase's response
+
+    decode::<serde_json::Value>(
+        token,
+        &DecodingKey::from_secret(&[]), // Empty secret since we disabled validation
+        &validation,
+    )
+    .map(|data| data.claims)
+    .map_err(|e| AuthCacheError::InvalidToken(e.to_string()))
+}
+
 /// Initialize the service role key - MUST be called exactly once at startup
 /// This key bypasses RLS and has full database access
 pub fn init_service_role_key(key: String) -> Result<(), String> {
@@ -378,6 +480,18 @@ pub fn has_service_role() -> bool {
     SERVICE_ROLE_KEY.get().is_some()
 }
 
+/// Check a presented credential against the service role key (constant-time compare)
+/// Always false when no service role key is configured
+pub fn is_service_role_key(candidate: &str) -> bool {
+    match SERVICE_ROLE_KEY.get() {
+        Some(key) => {
+            let (a, b) = (key.as_bytes(), candidate.as_bytes());
+            a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+        }
+        None => false,
+    }
+}
+
 /// Execute an admin operation with the service role key
 /// This bypasses RLS - use ONLY for legitimate admin operations
 /// Returns None if service role key is not configured
@@ -418,3 +532,122 @@ pub enum AuthCacheError {
     #[error("Invalid response from Supabase: {0}")]
     InvalidResponse(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::HeaderMap, routing::get, Json, Router};
+
+    /// Mock Supabase `/auth/v1/user` that only accepts its own anon key
+    async fn mock_upstream(anon_key: &'static str, user_id: &'static str) -> SupabaseUpstream {
+        let app = Router::new().route(
+            "/auth/v1/user",
+            get(move |headers: HeaderMap| async move {
+                if headers.get("apikey").and_then(|v| v.to_str().ok()) != Some(anon_key) {
+                    return Err(http::StatusCode::UNAUTHORIZED);
+                }
+                Ok(Json(serde_json::json!({ "id": user_id, "role": "authenticated" })))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        SupabaseUpstream::new(url, anon_key)
+    }
+
+    fn token_issued_by(issuer: &str) -> String {
+        let claims = serde_json::json!({
+            "sub": "ignored",
+            "exp": chrono::Utc::now().timestamp() + 3600,
+            "iss": issuer,
+        });
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(b"test-secret"),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify_batch_only_calls_supabase_for_misses() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Mock that rejects anything that isn't a JWT and counts its calls
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let app = Router::new().route(
+            "/auth/v1/user",
+            get(|headers: HeaderMap| async move {
+                CALLS.fetch_add(1, Ordering::SeqCst);
+                let bearer = headers.get("authorization").and_then(|v| v.to_str().ok()).unwrap_or("");
+                if bearer.matches('.').count() != 2 {
+                    return Err(http::StatusCode::UNAUTHORIZED);
+                }
+                Ok(Json(serde_json::json!({ "id": "user-fresh-0001", "role": "authenticated" })))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let cache = JwtCache::new(url, "anon".to_string());
+
+        let cached = "cached-token".to_string();
+        cache.insert(cached.clone(), TokenInfo {
+            user_id: "user-cached-0001".to_string(),
+            email: None,
+            role: "authenticated".to_string(),
+            app_metadata: None,
+            expires_at: chrono::Utc::now().timestamp() + 3600,
+            verified_at: Instant::now(),
+        });
+        let fresh = token_issued_by("https://project.supabase.co/auth/v1");
+        let invalid = "not-a-jwt".to_string();
+
+        let results = cache.verify_batch(&[cached, fresh.clone(), invalid]).await;
+        assert_eq!(results[0].as_ref().unwrap().user_id, "user-cached-0001");
+        assert_eq!(results[1].as_ref().unwrap().user_id, "user-fresh-0001");
+        assert!(matches!(results[2], Err(AuthCacheError::InvalidToken(_))));
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2, "the cached token must not hit Supabase");
+
+        // The freshly verified token is now cached too
+        assert_eq!(cache.get(&fresh).unwrap().user_id, "user-fresh-0001");
+    }
+
+    #[tokio::test]
+    async fn test_tokens_routed_to_their_tenant_upstream() {
+        let acme = mock_upstream("acme-anon", "user-acme-0001").await;
+        let globex = mock_upstream("globex-anon", "user-globex-01").await;
+        let cache = JwtCache::new("http://127.0.0.1:9".to_string(), "default-anon".to_string())
+            .with_tenant("acme", acme.clone())
+            .with_tenant("globex", globex.clone());
+
+        // Resolved from the iss claim
+        let acme_token = token_issued_by(&format!("{}/auth/v1", acme.url));
+        assert_eq!(cache.verify_and_cache(&acme_token).await.unwrap().user_id, "user-acme-0001");
+
+        // Named explicitly
+        let globex_token = token_issued_by("https://unrelated.example/auth/v1");
+        let info = cache.verify_and_cache_for(&globex_token, Some("globex")).await.unwrap();
+        assert_eq!(info.user_id, "user-globex-01");
+
+        assert!(matches!(
+            cache.verify_and_cache_for(&token_issued_by("x"), Some("initech")).await,
+            Err(AuthCacheError::InvalidToken(_))
+        ));
+    }
+
+    #[test]
+    fn test_look_alike_issuer_falls_back_to_default_upstream() {
+        let acme = SupabaseUpstream::new("https://acme.supabase.co/", "acme-anon");
+        let cache = JwtCache::new("https://default.supabase.co".to_string(), "default-anon".to_string())
+            .with_tenant("acme", acme.clone());
+
+        let genuine = token_issued_by("https://acme.supabase.co/auth/v1");
+        assert_eq!(cache.resolve_upstream(&genuine, None).unwrap(), &acme);
+
+        for issuer in ["https://acme.supabase.co.evil.tld/auth/v1", "https://acme.supabase.co/auth/v1/../evil"] {
+            let token = token_issued_by(issuer);
+            assert_eq!(cache.resolve_upstream(&token, None).unwrap().url, "https://default.supabase.co", "{issuer}");
+        }
+    }
+}
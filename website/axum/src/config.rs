@@ -0,0 +1,534 @@
+// src/config.rs
+// Startup configuration gathered from environment variables
+//
+// Every setting is read and validated up front; all problems are reported together in a
+// single ConfigError instead of panicking on the first bad value. Secrets are redacted
+// in the startup summary.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::info;
+
+use crate::auth::jwt_cache::SupabaseUpstream;
+use crate::game::anticheat::AntiCheatMode;
+use crate::game::entity_state::DEFAULT_RESPAWN_COOLDOWN;
+use crate::game::crafting::RecipeRegistry;
+use crate::game::environment::{DEFAULT_HARVEST_ALL_LIMIT, DEFAULT_INTERACTION_RANGE, MAX_NEIGHBOR_RADIUS};
+use crate::game::harvest_audit::DEFAULT_HARVEST_AUDIT_CAPACITY;
+use crate::game::tick::DEFAULT_TICK_RATE_HZ;
+use crate::game::{EntityType, WorldConfig};
+use crate::transports::recorder::{RecordingConfig, DEFAULT_RECORDING_CAPACITY};
+use crate::transports::sessions::DuplicateSessionPolicy;
+
+const DEFAULT_SUPABASE_URL: &str = "http://localhost:8000";
+const DEFAULT_WORLD_SEED: u64 = 12345;
+/// Players are removed on disconnect unless DISCONNECT_LINGER_SECS opts into a window
+const DEFAULT_DISCONNECT_LINGER_SECS: u64 = 0;
+const DEFAULT_HEALTH_REGEN_PER_SEC: f32 = 1.0;
+const DEFAULT_HEALTH_REGEN_DELAY_SECS: u64 = 5;
+
+/// Every configuration problem found at startup
+#[derive(Debug, thiserror::Error)]
+#[error("invalid configuration:\n  - {}", .problems.join("\n  - "))]
+pub struct ConfigError {
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    // Supabase
+    pub supabase_url: String,
+    /// SUPABASE_URL was unset and the local development default is in use
+    pub supabase_url_defaulted: bool,
+    pub supabase_anon_key: String,
+    pub supabase_service_role_key: Option<String>,
+    pub supabase_tenants: HashMap<String, SupabaseUpstream>,
+    pub token_expiry_warning_secs: Option<i64>,
+
+    // Network
+    pub http_addr: SocketAddr,
+    pub ws_idle_timeout: Duration,
+    pub ws_ping_interval: Duration,
+    pub ws_pong_timeout: Duration,
+    pub duplicate_session_policy: DuplicateSessionPolicy,
+    pub tick_rate_hz: u32,
+    /// Broadcast rate overrides per entity type (others go out every tick)
+    pub entity_sync_hz: HashMap<EntityType, u32>,
+    /// Per-session message recording (None unless SESSION_RECORDING is on)
+    pub recording: Option<RecordingConfig>,
+
+    // World
+    pub world_seed: u64,
+    pub chunk_size: f32,
+    pub view_distance_chunks: i32,
+    pub max_harvest_range: f32,
+    /// Reach for examine and other non-harvest interactions
+    pub interaction_range: f32,
+    /// Most objects one "harvest all in range" request may take (0 = action disabled)
+    pub harvest_all_limit: usize,
+    /// Harvest attempts kept for /admin/audit/harvests (oldest dropped first)
+    pub harvest_audit_capacity: usize,
+    /// Also send object despawns grouped by chunk
+    pub grouped_despawns: bool,
+    /// Re-entering a chunk only sends what changed since the player left
+    pub chunk_diffing: bool,
+    /// Reject planting below this height (None = planting allowed anywhere)
+    pub water_level: Option<f32>,
+    pub respawn_check_interval: Duration,
+    pub respawn_jitter_secs: u32,
+    pub world_config_path: Option<PathBuf>,
+    pub world: WorldConfig,
+    /// Crafting recipes from RECIPES_PATH (empty without it); reloads re-read that file
+    pub recipes: RecipeRegistry,
+
+    // Entities
+    pub max_entities: Option<usize>,
+    /// Connected player cap; new players past it get a 503 (spectators and admins exempt)
+    pub max_players: Option<usize>,
+    pub inventory_ops_per_sec: Option<u32>,
+    pub max_move_speed: Option<f32>,
+    /// Player radius for collision checks against trees/rocks (None = disabled)
+    pub collision_player_radius: Option<f32>,
+    pub player_respawn_cooldown: Duration,
+    /// Shadow mode logs and counts speed/range violations without rejecting them
+    pub anticheat_mode: AntiCheatMode,
+    /// Multiplier on speed and harvest range limits (>= 1.0)
+    pub anticheat_tolerance: f32,
+    /// How long a disconnected player's entity waits for a reconnect before removal (0 = remove at once)
+    pub disconnect_linger: Duration,
+    /// Passive player health regen in HP/sec (0 = disabled)
+    pub health_regen_per_sec: f32,
+    /// No regen until this long after a player last took damage
+    pub health_regen_delay: Duration,
+
+    // Persistence
+    /// Directory for per-player progress files (None = progress is not persisted)
+    pub player_store_dir: Option<PathBuf>,
+    pub checkpoint_interval: Duration,
+}
+
+impl Config {
+    /// Read and validate configuration from the process environment
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Read and validate configuration from any key lookup (tests pass a map)
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let mut env = Reader { lookup: &lookup, problems: Vec::new() };
+
+        let supabase_url = env.raw("SUPABASE_URL");
+        let supabase_url_defaulted = supabase_url.is_none();
+        let supabase_anon_key = env.required("SUPABASE_ANON_KEY");
+        let supabase_tenants = env
+            .raw("SUPABASE_TENANTS")
+            .and_then(|raw| env.check("SUPABASE_TENANTS", serde_json::from_str(&raw)))
+            .unwrap_or_default();
+
+        let http_host = env.raw("HTTP_HOST").unwrap_or_else(|| "0.0.0.0".to_string());
+        let http_port: u16 = env.parsed("HTTP_PORT", 4321);
+        let http_addr = env.check("HTTP_HOST", format!("{http_host}:{http_port}").parse::<SocketAddr>());
+
+        let duplicate_session_policy = match env.raw("DUPLICATE_SESSION_POLICY") {
+            Some(value) => DuplicateSessionPolicy::parse(&value).unwrap_or_else(|| {
+                env.problem(format!("DUPLICATE_SESSION_POLICY: unknown policy '{value}' (expected reject or replace)"));
+                DuplicateSessionPolicy::default()
+            }),
+            None => DuplicateSessionPolicy::default(),
+        };
+
+        let anticheat_mode = match env.raw("ANTICHEAT_MODE") {
+            Some(value) => AntiCheatMode::parse(&value).unwrap_or_else(|| {
+                env.problem(format!("ANTICHEAT_MODE: unknown mode '{value}' (expected enforce or shadow)"));
+                AntiCheatMode::default()
+            }),
+            None => AntiCheatMode::default(),
+        };
+
+        // "npc=2,enemy=10"
+        let entity_sync_hz = env
+            .raw("ENTITY_SYNC_HZ")
+            .and_then(|raw| env.check("ENTITY_SYNC_HZ", parse_entity_sync_hz(&raw)))
+            .unwrap_or_default();
+
+        let recording_enabled = match env.raw("SESSION_RECORDING") {
+            Some(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "1" | "true" => true,
+                "0" | "false" => false,
+                _ => {
+                    env.problem(format!("SESSION_RECORDING: unknown value '{value}' (expected 1, 0, true or false)"));
+                    false
+                }
+            },
+            None => false,
+        };
+        // Read even when recording is off so a bad value is still reported
+        let recording_capacity: usize = env.parsed("SESSION_RECORDING_CAPACITY", DEFAULT_RECORDING_CAPACITY);
+        let recording = recording_enabled.then(|| RecordingConfig {
+            capacity: recording_capacity,
+            dir: env.raw("SESSION_RECORDING_DIR").map(PathBuf::from),
+        });
+
+        let world_config_path = env.raw("WORLD_CONFIG_PATH").map(PathBuf::from);
+        let world = match &world_config_path {
+            Some(path) => env
+                .check("WORLD_CONFIG_PATH", WorldConfig::load(path).map_err(|e| format!("{}: {}", path.display(), e)))
+                .unwrap_or_default(),
+            None => WorldConfig::default(),
+        };
+
+        let recipes_path = env.raw("RECIPES_PATH").map(PathBuf::from);
+        let recipes = match &recipes_path {
+            Some(path) => env
+                .check("RECIPES_PATH", RecipeRegistry::from_file(path).map_err(|e| format!("{}: {}", path.display(), e)))
+                .unwrap_or_default(),
+            None => RecipeRegistry::default(),
+        };
+
+        let config = Self {
+            supabase_url: supabase_url.unwrap_or_else(|| DEFAULT_SUPABASE_URL.to_string()),
+            supabase_url_defaulted,
+            supabase_anon_key: supabase_anon_key.unwrap_or_default(),
+            supabase_service_role_key: env.raw("SUPABASE_SERVICE_ROLE_KEY"),
+            supabase_tenants,
+            token_expiry_warning_secs: env.optional("TOKEN_EXPIRY_WARNING_SECS"),
+            http_addr: http_addr.unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], http_port))),
+            ws_idle_timeout: Duration::from_secs(env.parsed("WS_IDLE_TIMEOUT_SECS", 300)),
+            ws_ping_interval: Duration::from_secs(env.parsed("WS_PING_INTERVAL_SECS", 30)),
+            ws_pong_timeout: Duration::from_secs(env.parsed("WS_PONG_TIMEOUT_SECS", 10)),
+            duplicate_session_policy,
+            tick_rate_hz: env.parsed("TICK_RATE_HZ", DEFAULT_TICK_RATE_HZ),
+            entity_sync_hz,
+            recording,
+            world_seed: env.parsed("WORLD_SEED", DEFAULT_WORLD_SEED),
+            chunk_size: env.parsed("CHUNK_SIZE", 50.0),
+            view_distance_chunks: env.parsed("VIEW_DISTANCE_CHUNKS", 3),
+            max_harvest_range: env.parsed("MAX_HARVEST_RANGE", 10.0),
+            interaction_range: env.parsed("INTERACTION_RANGE", DEFAULT_INTERACTION_RANGE),
+            harvest_all_limit: env.parsed("HARVEST_ALL_LIMIT", DEFAULT_HARVEST_ALL_LIMIT),
+            harvest_audit_capacity: env.parsed("HARVEST_AUDIT_CAPACITY", DEFAULT_HARVEST_AUDIT_CAPACITY),
+            grouped_despawns: env.parsed("GROUPED_DESPAWNS", false),
+            chunk_diffing: env.parsed("CHUNK_DIFFING", false),
+            water_level: env.optional("WATER_LEVEL"),
+            respawn_check_interval: Duration::from_secs(env.parsed("RESPAWN_CHECK_INTERVAL_SECS", 10)),
+            respawn_jitter_secs: env.parsed("RESPAWN_JITTER_SECS", 15),
+            world_config_path,
+            world,
+            recipes,
+            max_entities: env.optional("MAX_ENTITIES"),
+            max_players: env.optional("MAX_PLAYERS"),
+            inventory_ops_per_sec: env.optional("INVENTORY_OPS_PER_SEC"),
+            max_move_speed: env.optional("MAX_MOVE_SPEED"),
+            collision_player_radius: env.optional("COLLISION_PLAYER_RADIUS"),
+            player_respawn_cooldown: Duration::from_secs(
+                env.parsed("PLAYER_RESPAWN_COOLDOWN_SECS", DEFAULT_RESPAWN_COOLDOWN.as_secs()),
+            ),
+            anticheat_mode,
+            anticheat_tolerance: env.parsed("ANTICHEAT_TOLERANCE", 1.0),
+            disconnect_linger: Duration::from_secs(env.parsed("DISCONNECT_LINGER_SECS", DEFAULT_DISCONNECT_LINGER_SECS)),
+            health_regen_per_sec: env.parsed("HEALTH_REGEN_PER_SEC", DEFAULT_HEALTH_REGEN_PER_SEC),
+            health_regen_delay: Duration::from_secs(env.parsed("HEALTH_REGEN_DELAY_SECS", DEFAULT_HEALTH_REGEN_DELAY_SECS)),
+            player_store_dir: env.raw("PLAYER_STORE_DIR").map(PathBuf::from),
+            checkpoint_interval: Duration::from_secs(env.parsed("CHECKPOINT_INTERVAL_SECS", 60)),
+        };
+        config.validate(&mut env);
+
+        if env.problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError { problems: env.problems })
+        }
+    }
+
+    /// Range checks that parsing alone can't express
+    fn validate(&self, env: &mut Reader<'_>) {
+        if !(self.chunk_size.is_finite() && self.chunk_size > 0.0) {
+            env.problem(format!("CHUNK_SIZE: must be > 0 (got {})", self.chunk_size));
+        }
+        if !(0..=MAX_NEIGHBOR_RADIUS).contains(&self.view_distance_chunks) {
+            env.problem(format!(
+                "VIEW_DISTANCE_CHUNKS: must be between 0 and {MAX_NEIGHBOR_RADIUS} (got {})",
+                self.view_distance_chunks
+            ));
+        }
+        if !(self.max_harvest_range.is_finite() && self.max_harvest_range > 0.0) {
+            env.problem(format!("MAX_HARVEST_RANGE: must be > 0 (got {})", self.max_harvest_range));
+        }
+        if !(self.interaction_range.is_finite() && self.interaction_range > 0.0) {
+            env.problem(format!("INTERACTION_RANGE: must be > 0 (got {})", self.interaction_range));
+        }
+        if self.tick_rate_hz == 0 {
+            env.problem("TICK_RATE_HZ: must be > 0".to_string());
+        }
+        if self.ws_idle_timeout.is_zero() {
+            env.problem("WS_IDLE_TIMEOUT_SECS: must be > 0".to_string());
+        }
+        if self.ws_ping_interval.is_zero() {
+            env.problem("WS_PING_INTERVAL_SECS: must be > 0".to_string());
+        }
+        if self.ws_pong_timeout.is_zero() {
+            env.problem("WS_PONG_TIMEOUT_SECS: must be > 0".to_string());
+        }
+        if self.respawn_check_interval.is_zero() {
+            env.problem("RESPAWN_CHECK_INTERVAL_SECS: must be > 0".to_string());
+        }
+        if let Some(recording) = self.recording.as_ref().filter(|r| r.capacity == 0) {
+            env.problem(format!("SESSION_RECORDING_CAPACITY: must be > 0 (got {})", recording.capacity));
+        }
+        if self.checkpoint_interval.is_zero() {
+            env.problem("CHECKPOINT_INTERVAL_SECS: must be > 0".to_string());
+        }
+        if let Some(speed) = self.max_move_speed.filter(|s| !(s.is_finite() && *s > 0.0)) {
+            env.problem(format!("MAX_MOVE_SPEED: must be > 0 (got {speed})"));
+        }
+        if !(self.anticheat_tolerance.is_finite() && self.anticheat_tolerance >= 1.0) {
+            env.problem(format!("ANTICHEAT_TOLERANCE: must be >= 1.0 (got {})", self.anticheat_tolerance));
+        }
+        if !(self.health_regen_per_sec.is_finite() && self.health_regen_per_sec >= 0.0) {
+            env.problem(format!("HEALTH_REGEN_PER_SEC: must be >= 0 (got {})", self.health_regen_per_sec));
+        }
+        if let Some(level) = self.water_level.filter(|l| !l.is_finite()) {
+            env.problem(format!("WATER_LEVEL: must be a finite height (got {level})"));
+        }
+        if let Some(radius) = self.collision_player_radius.filter(|r| !(r.is_finite() && *r > 0.0)) {
+            env.problem(format!("COLLISION_PLAYER_RADIUS: must be > 0 (got {radius})"));
+        }
+        if !self.supabase_url.starts_with("http://") && !self.supabase_url.starts_with("https://") {
+            env.problem(format!("SUPABASE_URL: must be an http(s) URL (got '{}')", self.supabase_url));
+        }
+    }
+
+    /// Ticks between broadcasts for each throttled entity type, at the configured tick rate
+    pub fn entity_sync_intervals(&self) -> impl Iterator<Item = (EntityType, u64)> + '_ {
+        self.entity_sync_hz.iter().map(|(entity_type, hz)| {
+            let ticks = (self.tick_rate_hz as f64 / (*hz).max(1) as f64).round().max(1.0) as u64;
+            (*entity_type, ticks)
+        })
+    }
+
+    /// Log the effective configuration with secrets redacted
+    pub fn log_summary(&self) {
+        info!(
+            supabase_url = %self.supabase_url,
+            supabase_url_defaulted = self.supabase_url_defaulted,
+            supabase_anon_key = %redact(&self.supabase_anon_key),
+            service_role_key = %self.supabase_service_role_key.as_deref().map(redact).unwrap_or_else(|| "unset".to_string()),
+            tenants = ?self.supabase_tenants.keys().collect::<Vec<_>>(),
+            "Config: auth"
+        );
+        info!(
+            http_addr = %self.http_addr,
+            ws_idle_timeout_secs = self.ws_idle_timeout.as_secs(),
+            ws_ping_interval_secs = self.ws_ping_interval.as_secs(),
+            ws_pong_timeout_secs = self.ws_pong_timeout.as_secs(),
+            duplicate_session_policy = ?self.duplicate_session_policy,
+            tick_rate_hz = self.tick_rate_hz,
+            entity_sync_hz = ?self.entity_sync_hz,
+            session_recording = self.recording.is_some(),
+            session_recording_capacity = ?self.recording.as_ref().map(|r| r.capacity),
+            session_recording_dir = ?self.recording.as_ref().and_then(|r| r.dir.as_ref()),
+            "Config: network"
+        );
+        info!(
+            world_seed = self.world_seed,
+            chunk_size = self.chunk_size,
+            view_distance_chunks = self.view_distance_chunks,
+            max_harvest_range = self.max_harvest_range,
+            interaction_range = self.interaction_range,
+            harvest_all_limit = self.harvest_all_limit,
+            harvest_audit_capacity = self.harvest_audit_capacity,
+            grouped_despawns = self.grouped_despawns,
+            chunk_diffing = self.chunk_diffing,
+            water_level = ?self.water_level,
+            respawn_check_secs = self.respawn_check_interval.as_secs(),
+            respawn_jitter_secs = self.respawn_jitter_secs,
+            world_config_path = ?self.world_config_path,
+            recipes = self.recipes.list().len(),
+            max_entities = ?self.max_entities,
+            max_players = ?self.max_players,
+            inventory_ops_per_sec = ?self.inventory_ops_per_sec,
+            max_move_speed = ?self.max_move_speed,
+            collision_player_radius = ?self.collision_player_radius,
+            anticheat_mode = ?self.anticheat_mode,
+            anticheat_tolerance = self.anticheat_tolerance,
+            player_respawn_cooldown_secs = self.player_respawn_cooldown.as_secs(),
+            disconnect_linger_secs = self.disconnect_linger.as_secs(),
+            health_regen_per_sec = self.health_regen_per_sec,
+            health_regen_delay_secs = self.health_regen_delay.as_secs(),
+            "Config: world"
+        );
+        info!(
+            player_store_dir = ?self.player_store_dir,
+            checkpoint_interval_secs = self.checkpoint_interval.as_secs(),
+            "Config: persistence"
+        );
+    }
+}
+
+fn parse_entity_sync_hz(raw: &str) -> Result<HashMap<EntityType, u32>, String> {
+    raw.split(',')
+        .map(|pair| {
+            let (name, hz) = pair.split_once('=').ok_or_else(|| format!("expected type=hz, got '{pair}'"))?;
+            let entity_type = serde_json::from_value(serde_json::Value::String(name.trim().to_ascii_lowercase()))
+                .map_err(|_| format!("unknown entity type '{}'", name.trim()))?;
+            let hz = hz.trim().parse::<u32>().ok().filter(|hz| *hz > 0).ok_or_else(|| format!("invalid rate '{}' for {}", hz.trim(), name.trim()))?;
+            Ok((entity_type, hz))
+        })
+        .collect()
+}
+
+/// Show only enough of a secret to tell keys apart
+fn redact(secret: &str) -> String {
+    let chars = secret.chars().count();
+    if chars <= 8 {
+        return "***".to_string();
+    }
+    format!("{}***({} chars)", secret.chars().take(4).collect::<String>(), chars)
+}
+
+/// Env reader that records problems instead of failing fast
+struct Reader<'a> {
+    lookup: &'a dyn Fn(&str) -> Option<String>,
+    problems: Vec<String>,
+}
+
+impl Reader<'_> {
+    fn problem(&mut self, problem: String) {
+        self.problems.push(problem);
+    }
+
+    /// Value if set and non-empty
+    fn raw(&self, key: &str) -> Option<String> {
+        (self.lookup)(key).filter(|v| !v.trim().is_empty())
+    }
+
+    fn required(&mut self, key: &str) -> Option<String> {
+        let value = self.raw(key);
+        if value.is_none() {
+            self.problem(format!("{key}: required but not set"));
+        }
+        value
+    }
+
+    fn check<T, E: Display>(&mut self, key: &str, result: Result<T, E>) -> Option<T> {
+        result.map_err(|e| self.problem(format!("{key}: {e}"))).ok()
+    }
+
+    fn optional<T: FromStr>(&mut self, key: &str) -> Option<T>
+    where
+        T::Err: Display,
+    {
+        let raw = self.raw(key)?;
+        let parsed = raw.trim().parse::<T>().map_err(|e| format!("'{raw}' is not valid ({e})"));
+        self.check(key, parsed)
+    }
+
+    fn parsed<T: FromStr>(&mut self, key: &str, default: T) -> T
+    where
+        T::Err: Display,
+    {
+        self.optional(key).unwrap_or(default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_from(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        Config::from_lookup(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let config = config_from(&[
+            ("SUPABASE_URL", "https://project.supabase.co"),
+            ("SUPABASE_ANON_KEY", "anon-key-0123456789"),
+            ("HTTP_PORT", "8080"),
+            ("WORLD_SEED", "42"),
+            ("CHUNK_SIZE", "64"),
+            ("MAX_MOVE_SPEED", "12.5"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.http_addr.port(), 8080);
+        assert_eq!(config.world_seed, 42);
+        assert_eq!(config.chunk_size, 64.0);
+        assert_eq!(config.max_move_speed, Some(12.5));
+        assert_eq!(config.tick_rate_hz, DEFAULT_TICK_RATE_HZ);
+        assert!(config.entity_sync_hz.is_empty());
+        assert!(!config.supabase_url_defaulted);
+        assert_eq!(redact(&config.supabase_anon_key), "anon***(19 chars)");
+    }
+
+    #[test]
+    fn test_session_recording_settings() {
+        let config = config_from(&[("SUPABASE_ANON_KEY", "anon-key-0123456789")]).unwrap();
+        assert!(config.recording.is_none());
+
+        let config = config_from(&[
+            ("SUPABASE_ANON_KEY", "anon-key-0123456789"),
+            ("SESSION_RECORDING", "TRUE"),
+            ("SESSION_RECORDING_CAPACITY", "64"),
+            ("SESSION_RECORDING_DIR", "/tmp/recordings"),
+        ])
+        .unwrap();
+        let recording = config.recording.unwrap();
+        assert_eq!(recording.capacity, 64);
+        assert_eq!(recording.dir, Some(PathBuf::from("/tmp/recordings")));
+
+        let err = config_from(&[
+            ("SUPABASE_ANON_KEY", "anon-key-0123456789"),
+            ("SESSION_RECORDING", "1"),
+            ("SESSION_RECORDING_CAPACITY", "lots"),
+        ])
+        .unwrap_err();
+        assert_eq!(err.problems.len(), 1, "{err}");
+        assert!(err.problems[0].starts_with("SESSION_RECORDING_CAPACITY"), "{err}");
+    }
+
+    #[test]
+    fn test_redact_counts_chars_not_bytes() {
+        assert_eq!(redact(""), "***");
+        assert_eq!(redact("ab"), "***");
+        assert_eq!(redact("ключ-ключ-ключ"), "ключ***(14 chars)");
+        assert_eq!(redact("🔑🔑🔑🔑🔑🔑🔑🔑"), "***");
+    }
+
+    #[test]
+    fn test_entity_sync_rates() {
+        let config = config_from(&[
+            ("SUPABASE_ANON_KEY", "anon-key-0123456789"),
+            ("TICK_RATE_HZ", "20"),
+            ("ENTITY_SYNC_HZ", "npc=2, Boss=20"),
+        ])
+        .unwrap();
+        let intervals: HashMap<_, _> = config.entity_sync_intervals().collect();
+        assert_eq!(intervals[&EntityType::Npc], 10);
+        assert_eq!(intervals[&EntityType::Boss], 1);
+
+        let err = config_from(&[("SUPABASE_ANON_KEY", "anon-key-0123456789"), ("ENTITY_SYNC_HZ", "dragon=2")]).unwrap_err();
+        assert!(err.to_string().contains("unknown entity type 'dragon'"), "{err}");
+    }
+
+    #[test]
+    fn test_missing_anon_key_reported_with_other_problems() {
+        let err = config_from(&[
+            ("WORLD_SEED", "not-a-seed"),
+            ("CHUNK_SIZE", "0"),
+            ("DUPLICATE_SESSION_POLICY", "sometimes"),
+        ])
+        .unwrap_err();
+
+        assert_eq!(err.problems.len(), 4, "{err}");
+        let message = err.to_string();
+        for key in ["SUPABASE_ANON_KEY", "WORLD_SEED", "CHUNK_SIZE", "DUPLICATE_SESSION_POLICY"] {
+            assert!(message.contains(key), "missing {key} in: {message}");
+        }
+    }
+}
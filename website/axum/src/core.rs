@@ -1,9 +1,26 @@
+use std::sync::Arc;
+use serde::Serialize;
 use tokio::sync::{mpsc, oneshot};
 
+use crate::auth::jwt_cache::JwtCache;
+use crate::game::{EntityStateManager, EnvironmentManager, EnvironmentStats};
+
 #[derive(Debug)]
 pub enum AppCmd {
     Hello { name: String, reply: oneshot::Sender<String> },
     Chat  { room: String, text: String },
+    /// Read-only query for live entity/cache/environment counts
+    Stats { reply: oneshot::Sender<StatsSnapshot> },
+}
+
+/// Point-in-time counts returned by `AppCmd::Stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsSnapshot {
+    pub entity_count: usize,
+    pub player_count: usize,
+    pub jwt_cache_size: usize,
+    pub environment: EnvironmentStats,
+    pub timestamp: i64,
 }
 
 #[derive(Clone)]
@@ -16,7 +33,12 @@ pub fn new_bus(cap: usize) -> (AppBus, mpsc::Receiver<AppCmd>) {
     (AppBus { tx }, rx)
 }
 
-pub async fn run_app(mut rx: mpsc::Receiver<AppCmd>) {
+pub async fn run_app(
+    mut rx: mpsc::Receiver<AppCmd>,
+    jwt_cache: JwtCache,
+    entity_state: EntityStateManager,
+    environment_manager: Arc<EnvironmentManager>,
+) {
     while let Some(cmd) = rx.recv().await {
         match cmd {
             AppCmd::Hello { name, reply } => {
@@ -25,6 +47,49 @@ pub async fn run_app(mut rx: mpsc::Receiver<AppCmd>) {
             AppCmd::Chat { room, text } => {
                 tracing::info!(%room, %text, "chat");
             }
+            AppCmd::Stats { reply } => {
+                let _ = reply.send(StatsSnapshot {
+                    entity_count: entity_state.entity_count(),
+                    player_count: entity_state.player_count(),
+                    jwt_cache_size: jwt_cache.size(),
+                    environment: environment_manager.get_stats(),
+                    timestamp: chrono::Utc::now().timestamp(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{ChunkCoord, EnvironmentGenerator};
+
+    #[tokio::test]
+    async fn test_stats_reply_matches_managers() {
+        let jwt_cache = JwtCache::new("http://localhost:8000".to_string(), "anon".to_string());
+        let entity_state = EntityStateManager::new(120);
+        let environment_manager = Arc::new(EnvironmentManager::new(50.0, 3, 10.0));
+
+        entity_state.add_player("player-0001".to_string(), "alice".to_string());
+        entity_state.add_npc("npc-00000001".to_string());
+        let generator = EnvironmentGenerator::new(12345, 50.0);
+        for object in generator.generate_chunk(&ChunkCoord { x: 0, z: 0 }) {
+            environment_manager.add_object(object).unwrap();
         }
+
+        let (bus, rx) = new_bus(8);
+        tokio::spawn(run_app(rx, jwt_cache, entity_state.clone(), environment_manager.clone()));
+
+        let (tx, reply) = oneshot::channel();
+        bus.tx.send(AppCmd::Stats { reply: tx }).await.unwrap();
+        let stats = reply.await.unwrap();
+
+        let env_stats = environment_manager.get_stats();
+        assert_eq!(stats.entity_count, 2);
+        assert_eq!(stats.player_count, 1);
+        assert_eq!(stats.jwt_cache_size, 0);
+        assert_eq!(stats.environment.total_objects, env_stats.total_objects);
+        assert_eq!(stats.environment.loaded_chunks, 1);
     }
-}
\ No newline at end of file
+}
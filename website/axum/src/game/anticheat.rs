@@ -0,0 +1,124 @@
+// src/game/anticheat.rs
+// Shared switchboard for movement/harvest validation
+//
+// Managers report violations here instead of deciding on their own whether to reject.
+// In enforce mode violations are rejected as before; in shadow mode they are only logged
+// and counted, so operators can watch what validation would do before turning it on.
+// The tolerance multiplier scales every limit (1.0 = exact, 1.5 = 50% slack for lag).
+// The handle is cheap to clone; clones share mode and counters (toggled via /admin/anticheat).
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AntiCheatMode {
+    /// Reject violating moves and harvests
+    #[default]
+    Enforce,
+    /// Log and count violations but let them through
+    Shadow,
+}
+
+impl AntiCheatMode {
+    /// Parse "enforce" / "shadow" (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "enforce" => Some(Self::Enforce),
+            "shadow" => Some(Self::Shadow),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// Client move faster than the speed limit
+    Speed,
+    /// Harvest from beyond the object's harvest range
+    HarvestRange,
+}
+
+/// Violations seen since startup (counted in both modes)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ViolationCounts {
+    pub speed: u64,
+    pub harvest_range: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AntiCheatStatus {
+    pub mode: AntiCheatMode,
+    pub tolerance: f32,
+    pub violations: ViolationCounts,
+}
+
+#[derive(Debug, Clone)]
+pub struct AntiCheat {
+    shadow: Arc<AtomicBool>,
+    tolerance: f32,
+    speed_violations: Arc<AtomicU64>,
+    harvest_range_violations: Arc<AtomicU64>,
+}
+
+impl Default for AntiCheat {
+    fn default() -> Self {
+        Self::new(AntiCheatMode::Enforce, 1.0)
+    }
+}
+
+impl AntiCheat {
+    /// `tolerance` below 1.0 would tighten limits, so it is clamped to 1.0
+    pub fn new(mode: AntiCheatMode, tolerance: f32) -> Self {
+        Self {
+            shadow: Arc::new(AtomicBool::new(mode == AntiCheatMode::Shadow)),
+            tolerance: if tolerance.is_finite() { tolerance.max(1.0) } else { 1.0 },
+            speed_violations: Arc::new(AtomicU64::new(0)),
+            harvest_range_violations: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn mode(&self) -> AntiCheatMode {
+        if self.shadow.load(Ordering::Relaxed) {
+            AntiCheatMode::Shadow
+        } else {
+            AntiCheatMode::Enforce
+        }
+    }
+
+    pub fn set_mode(&self, mode: AntiCheatMode) {
+        self.shadow.store(mode == AntiCheatMode::Shadow, Ordering::Relaxed);
+    }
+
+    /// Multiplier applied to speed and range limits
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Count a violation; returns true if it should be rejected (enforce mode)
+    pub fn report(&self, violation: Violation, entity_id: &str) -> bool {
+        let counter = match violation {
+            Violation::Speed => &self.speed_violations,
+            Violation::HarvestRange => &self.harvest_range_violations,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        let mode = self.mode();
+        if mode == AntiCheatMode::Shadow {
+            warn!(entity_id = %entity_id, violation = ?violation, "Anti-cheat violation allowed (shadow mode)");
+        }
+        mode == AntiCheatMode::Enforce
+    }
+
+    pub fn violations(&self) -> ViolationCounts {
+        ViolationCounts {
+            speed: self.speed_violations.load(Ordering::Relaxed),
+            harvest_range: self.harvest_range_violations.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn status(&self) -> AntiCheatStatus {
+        AntiCheatStatus { mode: self.mode(), tolerance: self.tolerance, violations: self.violations() }
+    }
+}
@@ -0,0 +1,112 @@
+// src/game/crafting.rs
+// Crafting recipe definitions and the shared, hot-reloadable recipe registry
+//
+// Recipes are data: a JSON file (RECIPES_PATH) holding a list of recipes. The registry
+// hands out cheap snapshots (`Arc<Vec<Recipe>>`) and can swap in a freshly loaded file
+// at runtime (/admin/recipes/reload); readers holding an old snapshot are unaffected.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// An item and count consumed or produced by a recipe
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipeItem {
+    pub item_id: String,
+    pub quantity: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recipe {
+    pub recipe_id: String,
+    pub inputs: Vec<RecipeItem>,
+    pub output: RecipeItem,
+    /// Seconds to craft (0 = instant)
+    #[serde(default)]
+    pub craft_time: f32,
+}
+
+/// Load and validate a recipe file (unique ids, non-empty inputs, positive quantities)
+pub fn load_recipes(path: impl AsRef<Path>) -> Result<Vec<Recipe>> {
+    let raw = std::fs::read_to_string(path.as_ref())?;
+    let recipes: Vec<Recipe> = serde_json::from_str(&raw)?;
+
+    let mut ids = HashSet::new();
+    for recipe in &recipes {
+        if !ids.insert(recipe.recipe_id.as_str()) {
+            bail!("duplicate recipe id '{}'", recipe.recipe_id);
+        }
+        if recipe.inputs.is_empty() {
+            bail!("recipe '{}' has no inputs", recipe.recipe_id);
+        }
+        if recipe.inputs.iter().chain([&recipe.output]).any(|item| item.quantity == 0) {
+            bail!("recipe '{}' has a zero quantity", recipe.recipe_id);
+        }
+    }
+    Ok(recipes)
+}
+
+/// Shared recipe set; clones see the same recipes and reloads
+#[derive(Debug, Clone, Default)]
+pub struct RecipeRegistry {
+    recipes: Arc<RwLock<Arc<Vec<Recipe>>>>,
+    /// File `reload` reads from (None = fixed recipe set)
+    source: Option<PathBuf>,
+}
+
+impl RecipeRegistry {
+    pub fn new(recipes: Vec<Recipe>) -> Self {
+        Self {
+            recipes: Arc::new(RwLock::new(Arc::new(recipes))),
+            source: None,
+        }
+    }
+
+    /// Registry backed by a recipe file, loaded now and again on every `reload`
+    pub fn from_file(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let registry = Self::new(load_recipes(&path)?);
+        Ok(Self { source: Some(path), ..registry })
+    }
+
+    /// Current recipes
+    pub fn list(&self) -> Arc<Vec<Recipe>> {
+        self.recipes.read().unwrap().clone()
+    }
+
+    /// Re-read the source file and swap it in; the current set is kept if loading fails
+    /// Returns the number of recipes now loaded
+    pub fn reload(&self) -> Result<usize> {
+        let Some(path) = &self.source else {
+            bail!("no recipe file configured (RECIPES_PATH)");
+        };
+        let recipes = load_recipes(path)?;
+        let count = recipes.len();
+        *self.recipes.write().unwrap() = Arc::new(recipes);
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_rejects_invalid_recipes() {
+        let path = std::env::temp_dir().join(format!("bugwars-recipes-{}.json", ulid::Ulid::new()));
+        let recipe = serde_json::json!({
+            "recipe_id": "torch",
+            "inputs": [{ "item_id": "wood", "quantity": 1 }],
+            "output": { "item_id": "torch", "quantity": 1 }
+        });
+
+        std::fs::write(&path, serde_json::json!([recipe.clone()]).to_string()).unwrap();
+        assert_eq!(load_recipes(&path).unwrap()[0].craft_time, 0.0);
+
+        std::fs::write(&path, serde_json::json!([recipe.clone(), recipe]).to_string()).unwrap();
+        assert!(load_recipes(&path).unwrap_err().to_string().contains("duplicate recipe id 'torch'"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
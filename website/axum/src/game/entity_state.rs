@@ -1,14 +1,25 @@
 // src/game/entity_state.rs
 // Manages game entity state (players, NPCs, etc.)
 
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+use super::entity_store::{EntityStore, InMemoryEntityStore};
+use super::player_store::{PlayerRecord, PlayerStore};
+use super::environment::{ChunkCoord, ChunkDespawn, HarvestErrorCode, HarvestObjectResponse, InteractAction};
+use super::environment_gen::Biome;
+use super::anticheat::{AntiCheat, Violation};
+use super::events::{GameEvent, GameEventBus};
+use super::spawn::SpawnManager;
+use super::tick::EntityDelta;
+
 /// 3D position in game world
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
@@ -26,6 +37,15 @@ impl Position {
         let dz = self.z - other.z;
         (dx * dx + dy * dy + dz * dz).sqrt()
     }
+
+    /// Linear interpolation towards `other` (t = 0 is self, t = 1 is other)
+    pub fn lerp(&self, other: &Position, t: f32) -> Position {
+        Position::new(
+            self.x + (other.x - self.x) * t,
+            self.y + (other.y - self.y) * t,
+            self.z + (other.z - self.z) * t,
+        )
+    }
 }
 
 impl Default for Position {
@@ -71,15 +91,30 @@ impl InventoryItem {
         }
     }
 
-    pub fn with_metadata(item_id: String, quantity: u32, metadata: String) -> Self {
-        Self {
+    /// Rejects metadata longer than MAX_ITEM_METADATA_LEN bytes
+    pub fn with_metadata(item_id: String, quantity: u32, metadata: String) -> Result<Self, String> {
+        if metadata.len() > MAX_ITEM_METADATA_LEN {
+            return Err(format!(
+                "Metadata for {} is {} bytes (max {})",
+                item_id,
+                metadata.len(),
+                MAX_ITEM_METADATA_LEN
+            ));
+        }
+        Ok(Self {
             item_id,
             quantity,
             metadata: Some(metadata),
-        }
+        })
     }
 }
 
+/// Largest metadata blob an inventory item may carry (bytes)
+pub const MAX_ITEM_METADATA_LEN: usize = 4096;
+
+/// Inventory slots a player gets unless the world config or an override says otherwise
+pub const DEFAULT_INVENTORY_SLOTS: u32 = 20;
+
 /// Player inventory (items keyed by item_id)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Inventory {
@@ -95,6 +130,13 @@ impl Inventory {
         }
     }
 
+    /// Whether `item_id` would fit: it stacks onto an existing item or a slot is free
+    pub fn can_add_item(&self, item_id: &str) -> bool {
+        self.items.iter().any(|i| i.item_id == item_id && i.metadata.is_none())
+            || self.max_slots == 0
+            || self.items.len() < self.max_slots as usize
+    }
+
     pub fn add_item(&mut self, item_id: String, quantity: u32) -> bool {
         // Try to stack with existing item
         if let Some(existing) = self.items.iter_mut().find(|i| i.item_id == item_id && i.metadata.is_none()) {
@@ -103,7 +145,7 @@ impl Inventory {
         }
 
         // Check slot limit
-        if self.max_slots > 0 && self.items.len() >= self.max_slots as usize {
+        if !self.can_add_item(&item_id) {
             return false; // Inventory full
         }
 
@@ -149,12 +191,12 @@ impl Inventory {
 
 impl Default for Inventory {
     fn default() -> Self {
-        Self::new(20) // Default 20 slots
+        Self::new(DEFAULT_INVENTORY_SLOTS)
     }
 }
 
 /// Entity type (Player, NPC, etc.)
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum EntityType {
     Player,
@@ -163,8 +205,15 @@ pub enum EntityType {
     Boss,
 }
 
+impl EntityType {
+    /// Whether AI should treat this entity as hostile to players
+    pub fn is_hostile(&self) -> bool {
+        matches!(self, EntityType::Enemy | EntityType::Boss)
+    }
+}
+
 /// Entity state tracked by the server
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntityState {
     pub entity_id: String,  // Unique entity ID (user_id for players, generated for NPCs)
     pub entity_type: EntityType,
@@ -174,9 +223,21 @@ pub struct EntityState {
     pub health: f32,
     pub is_alive: bool,
     pub inventory: Inventory,
+    /// Item id of the equipped tool (must also be in the inventory)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub equipped_tool: Option<String>,
+    /// Team/faction id; entities on the same team can't damage each other
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
     pub last_update: i64, // Unix timestamp
-    #[serde(skip)]
+    /// Game-specific data (level, mana, team); keys starting with `_` stay server-side
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub attributes: HashMap<String, serde_json::Value>,
+    #[serde(skip, default = "Instant::now")]
     pub last_seen: Instant, // Server-side tracking (not serialized)
+    /// Last time health went down (suppresses passive regen during combat)
+    #[serde(skip)]
+    pub last_damaged_at: Option<Instant>,
 }
 
 impl EntityState {
@@ -190,8 +251,12 @@ impl EntityState {
             health: 100.0,
             is_alive: true,
             inventory: Inventory::default(),
+            equipped_tool: None,
             last_update: chrono::Utc::now().timestamp(),
+            team: None,
+            attributes: HashMap::new(),
             last_seen: Instant::now(),
+            last_damaged_at: None,
         }
     }
 
@@ -205,8 +270,12 @@ impl EntityState {
             health: 100.0,
             is_alive: true,
             inventory: Inventory::default(),
+            equipped_tool: None,
             last_update: chrono::Utc::now().timestamp(),
+            team: None,
+            attributes: HashMap::new(),
             last_seen: Instant::now(),
+            last_damaged_at: None,
         }
     }
 
@@ -220,8 +289,12 @@ impl EntityState {
             health: 100.0,
             is_alive: true,
             inventory: Inventory::default(),
+            equipped_tool: None,
             last_update: chrono::Utc::now().timestamp(),
+            team: None,
+            attributes: HashMap::new(),
             last_seen: Instant::now(),
+            last_damaged_at: None,
         }
     }
 
@@ -235,8 +308,12 @@ impl EntityState {
             health,
             is_alive: true,
             inventory: Inventory::default(),
+            equipped_tool: None,
             last_update: chrono::Utc::now().timestamp(),
+            team: None,
+            attributes: HashMap::new(),
             last_seen: Instant::now(),
+            last_damaged_at: None,
         }
     }
 
@@ -249,8 +326,17 @@ impl EntityState {
         self.last_seen = Instant::now();
     }
 
+    /// Both entities are on the same (named) team
+    pub fn is_teammate_of(&self, other: &EntityState) -> bool {
+        self.team.is_some() && self.team == other.team
+    }
+
     pub fn update_health(&mut self, health: f32) {
-        self.health = health.clamp(0.0, 100.0);
+        let health = health.clamp(0.0, 100.0);
+        if health < self.health {
+            self.last_damaged_at = Some(Instant::now());
+        }
+        self.health = health;
         self.is_alive = self.health > 0.0;
         self.last_update = chrono::Utc::now().timestamp();
         self.last_seen = Instant::now();
@@ -259,6 +345,53 @@ impl EntityState {
     pub fn is_stale(&self, timeout: Duration) -> bool {
         self.last_seen.elapsed() > timeout
     }
+
+    /// Broadcast-safe view of this entity for other clients
+    pub fn public(&self) -> PublicEntityState {
+        PublicEntityState {
+            entity_id: self.entity_id.clone(),
+            entity_type: self.entity_type,
+            display_name: self.display_name.clone(),
+            position: self.position,
+            rotation: self.rotation,
+            health: self.health,
+            is_alive: self.is_alive,
+            equipped_tool: self.equipped_tool.clone(),
+            team: self.team.clone(),
+            attributes: self
+                .attributes
+                .iter()
+                .filter(|(key, _)| !is_private_attribute(key))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// Attributes whose key starts with `_` are never sent to other clients
+fn is_private_attribute(key: &str) -> bool {
+    key.starts_with('_')
+}
+
+/// What other players may see of an entity (GameState, PlayerJoined, Welcome)
+/// Deliberately a separate type so private fields (inventory, anything account-related)
+/// can't reach other clients by being added to `EntityState`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicEntityState {
+    pub entity_id: String,
+    pub entity_type: EntityType,
+    pub display_name: String,
+    pub position: Position,
+    pub rotation: Rotation,
+    pub health: f32,
+    pub is_alive: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub equipped_tool: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    /// Public custom attributes (private `_` keys filtered out)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub attributes: HashMap<String, serde_json::Value>,
 }
 
 /// Messages from Unity clients
@@ -268,6 +401,9 @@ pub enum GameMessage {
     /// Player joins the game
     Join {
         position: Option<Position>,
+        /// Requested view distance in chunks (clamped to the server max)
+        #[serde(default)]
+        view_distance: Option<i32>,
     },
     /// Player updates position/rotation
     UpdatePosition {
@@ -278,7 +414,7 @@ pub enum GameMessage {
     UpdateHealth {
         health: f32,
     },
-    /// Add item to inventory
+    /// Add item to inventory (always rejected; items are granted server-side)
     AddItem {
         item_id: String,
         quantity: u32,
@@ -290,21 +426,118 @@ pub enum GameMessage {
     },
     /// Get full inventory
     GetInventory,
+    /// Equip a tool from the inventory (None unequips)
+    EquipTool {
+        item_id: Option<String>,
+    },
+    /// Dead player asks to come back at the spawn point
+    Respawn,
     /// Player leaves the game
     Leave,
     /// Request current game state
     GetState,
     /// Heartbeat/keepalive
     Ping,
+    /// Client received the server frame stamped with `seq` (optional, feeds ack metrics)
+    Ack {
+        seq: u64,
+    },
     /// Request to harvest an environment object (tree, rock, bush)
     HarvestObject {
         object_id: String,
         player_position: Position,
     },
+    /// Harvest every eligible object within reach in one request (skips ineligible ones)
+    HarvestAllInRange {
+        player_position: Position,
+    },
+    /// Generic object interaction (examine, harvest, plant)
+    /// For `plant`, `object_id` is ignored and the sapling goes to `target_position`
+    /// (defaults to `player_position`)
+    InteractObject {
+        #[serde(default)]
+        object_id: String,
+        action: InteractAction,
+        player_position: Position,
+        #[serde(default)]
+        target_position: Option<Position>,
+    },
+}
+
+/// Longest inbound game message accepted (the WebSocket frame limit is far larger)
+pub const MAX_GAME_MESSAGE_LEN: usize = 16 * 1024;
+
+/// Deepest `{`/`[` nesting accepted in an inbound game message
+pub const MAX_GAME_MESSAGE_DEPTH: usize = 16;
+
+/// Why an inbound game message was refused
+#[derive(Debug, thiserror::Error)]
+pub enum GameMessageError {
+    #[error("message too large ({len} bytes, max {max})")]
+    TooLarge { len: usize, max: usize },
+    #[error("message nested too deeply (max depth {max})")]
+    TooDeep { max: usize },
+    #[error("not a game message: {0}")]
+    Invalid(#[from] serde_json::Error),
+}
+
+impl GameMessage {
+    /// Parse a client message, refusing oversized or deeply nested JSON before serde sees it
+    /// The depth check is a single byte scan that stops at the first violation.
+    pub fn parse(text: &str) -> Result<Self, GameMessageError> {
+        if text.len() > MAX_GAME_MESSAGE_LEN {
+            return Err(GameMessageError::TooLarge { len: text.len(), max: MAX_GAME_MESSAGE_LEN });
+        }
+        if json_depth_exceeds(text, MAX_GAME_MESSAGE_DEPTH) {
+            return Err(GameMessageError::TooDeep { max: MAX_GAME_MESSAGE_DEPTH });
+        }
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// Messages that never change game state (allowed for spectators)
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            GameMessage::Ping
+                | GameMessage::Ack { .. }
+                | GameMessage::GetState
+                | GameMessage::InteractObject { action: InteractAction::Examine, .. }
+        )
+    }
+}
+
+/// True if `{`/`[` nesting (outside string literals) goes deeper than `max`
+fn json_depth_exceeds(text: &str, max: usize) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for byte in text.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > max {
+                    return true;
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
 }
 
 /// Server response messages
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ServerMessage {
     /// Welcome message when player connects
@@ -312,6 +545,20 @@ pub enum ServerMessage {
         user_id: String,
         role: String,
     },
+    /// Full initial state sent once on upgrade (replaces Connected + GameState + the JSON object snapshot)
+    /// `nearby_objects` is empty when the client negotiated a binary snapshot, which follows separately
+    Welcome {
+        user_id: String,
+        role: String,
+        spectator: bool,
+        spawn_position: Position,
+        /// Biome of the spawn chunk (None until that chunk is generated)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        biome: Option<Biome>,
+        nearby_players: Vec<PublicEntityState>,
+        nearby_objects: Vec<serde_json::Value>,
+        server_tick: u64,
+    },
     /// Player successfully joined
     Joined {
         user_id: String,
@@ -319,12 +566,19 @@ pub enum ServerMessage {
     },
     /// Current game state (all players)
     GameState {
-        players: Vec<EntityState>,
+        players: Vec<PublicEntityState>,
+        timestamp: i64,
+    },
+    /// Batched entity changes for one server tick (sent at the tick rate)
+    GameStateDelta {
+        tick: u64,
+        entities: Vec<EntityDelta>,
+        removed: Vec<String>,
         timestamp: i64,
     },
     /// Another player joined
     PlayerJoined {
-        player: EntityState,
+        player: PublicEntityState,
     },
     /// Another player left
     PlayerLeft {
@@ -336,6 +590,13 @@ pub enum ServerMessage {
         position: Position,
         rotation: Rotation,
     },
+    /// Server rejected a move; the client should return to this authoritative position
+    /// (`smooth` asks the client to interpolate there instead of snapping)
+    PositionCorrected {
+        position: Position,
+        rotation: Rotation,
+        smooth: bool,
+    },
     /// Player health changed
     PlayerHealthChanged {
         user_id: String,
@@ -347,6 +608,12 @@ pub enum ServerMessage {
         user_id: String,
         inventory: Inventory,
     },
+    /// Only the inventory items that changed (quantities are the amounts added/removed)
+    /// Full syncs use InventoryUpdated (on join and GetInventory)
+    InventoryDelta {
+        added: Vec<InventoryItem>,
+        removed: Vec<(String, u32)>,
+    },
     /// Item added successfully
     ItemAdded {
         item_id: String,
@@ -359,6 +626,10 @@ pub enum ServerMessage {
         quantity: u32,
         success: bool,
     },
+    /// Equipped tool changed
+    ToolEquipped {
+        item_id: Option<String>,
+    },
     /// Pong response to ping
     Pong {
         timestamp: i64,
@@ -367,42 +638,736 @@ pub enum ServerMessage {
     EnvironmentObjects {
         objects: Vec<serde_json::Value>, // Using Value to avoid circular dependency
     },
+    /// Environment objects that left the player's visible chunks
+    ObjectsDespawned {
+        object_ids: Vec<String>,
+        /// Same ids grouped by chunk, when the server groups despawns
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        chunks: Vec<ChunkDespawn>,
+    },
+    /// Biome of the chunk a player just entered
+    ChunkInfo {
+        chunk_x: i32,
+        chunk_z: i32,
+        biome: Biome,
+    },
     /// Harvest result (success or failure)
     HarvestResult {
         object_id: String,
         success: bool,
         message: String,
         resources: Option<Vec<(String, u32)>>, // resource_type, quantity
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_code: Option<HarvestErrorCode>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        effect_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        sound_id: Option<String>,
+    },
+    /// Objects taken by a `harvest_all_in_range` request (successes only, nearest first)
+    HarvestAllResult {
+        results: Vec<HarvestObjectResponse>,
+    },
+    /// Object harvested by another player (despawn it; broadcast to players in its chunk)
+    ObjectHarvested {
+        object_id: String,
     },
     /// Object respawned (broadcast to nearby players)
     ObjectRespawned {
         object_id: String,
         object_data: serde_json::Value,
     },
+    /// Object details returned by an examine interaction
+    ObjectExamined {
+        object_data: serde_json::Value,
+        is_harvested: bool,
+        harvested_at: Option<i64>,
+    },
+    /// Sapling planted by the player (seed consumed)
+    ObjectPlanted {
+        object_data: serde_json::Value,
+    },
+    /// Session token expires soon - client should refresh and reconnect
+    TokenExpiringSoon {
+        expires_at: i64,
+    },
+    /// Entity changed type at runtime (e.g. a neutral NPC turned hostile)
+    EntityTypeChanged {
+        entity_id: String,
+        entity_type: EntityType,
+    },
+    /// World was regenerated with a new seed; clients should drop all environment objects and reload
+    WorldReset {
+        seed: u64,
+    },
+    /// Player was removed by an administrator (connection closes right after)
+    Kicked {
+        reason: String,
+    },
+    /// Everyone will be disconnected in `grace_seconds` (maintenance, deploys)
+    /// A `Disconnecting` notice and close frame follow when the grace period ends
+    ShuttingDown {
+        reason: String,
+        grace_seconds: u64,
+    },
+    /// Player cap reached; sent as the 503 body when refusing a WebSocket upgrade
+    ServerFull {
+        current: usize,
+        max: usize,
+    },
+    /// Server is about to close the connection
+    /// `retry_after_ms` is set when the close is transient (e.g. overload); clients should wait
+    /// at least that long before reconnecting. Absent means don't auto-reconnect.
+    Disconnecting {
+        reason: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_after_ms: Option<u64>,
+    },
     /// Error message
     Error {
         message: String,
     },
+    /// Several messages coalesced into one frame (see `tick::coalesce`)
+    /// Each inner message keeps its own `type` tag
+    Batch {
+        messages: Vec<ServerMessage>,
+    },
+}
+
+/// Default cap on tracked entities (players + NPCs + enemies + bosses)
+pub const DEFAULT_MAX_ENTITIES: usize = 10_000;
+
+/// Shortest interval used when checking move speed (so rapid updates aren't over-penalized)
+const MIN_MOVE_WINDOW: Duration = Duration::from_millis(250);
+
+/// Default chunk size used to detect chunk crossings (matches EnvironmentManager)
+pub const DEFAULT_CHUNK_SIZE: f32 = 50.0;
+
+/// What a position update changed
+#[derive(Debug, Clone, Copy)]
+pub struct PositionUpdateOutcome {
+    /// Position differs from the previous one
+    pub moved: bool,
+    /// Entity entered a different chunk (visible chunks need updating)
+    pub crossed_chunk: bool,
+    pub new_chunk: ChunkCoord,
+    pub position: Position,
+    pub rotation: Rotation,
+}
+
+/// Outcome of a speed-checked move
+#[derive(Debug, Clone)]
+pub enum MoveResult {
+    Moved(PositionUpdateOutcome),
+    /// Move exceeded the speed limit; carries the unchanged (last valid) state
+    Rejected(Box<EntityState>),
+}
+
+/// Default per-entity inventory operations (add/remove) allowed per second
+pub const DEFAULT_MAX_INVENTORY_OPS_PER_SEC: u32 = 20;
+
+/// Circular trigger area on the x/z plane (height is ignored)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProximityArea {
+    pub center: Position,
+    pub radius: f32,
+}
+
+impl ProximityArea {
+    pub fn contains(&self, position: &Position) -> bool {
+        let dx = position.x - self.center.x;
+        let dz = position.z - self.center.z;
+        dx * dx + dz * dz <= self.radius * self.radius
+    }
+}
+
+/// Rectangle of chunks (inclusive corners) with its own PvP rule
+/// `{"min": {"x": -2, "z": -2}, "max": {"x": 2, "z": 2}, "pvp": false}` makes the 5x5 chunks
+/// around the origin PvE-only
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PvpZone {
+    pub min: ChunkCoord,
+    pub max: ChunkCoord,
+    pub pvp: bool,
+}
+
+impl PvpZone {
+    pub fn contains(&self, chunk: &ChunkCoord) -> bool {
+        (self.min.x..=self.max.x).contains(&chunk.x) && (self.min.z..=self.max.z).contains(&chunk.z)
+    }
+}
+
+/// A watched area and the entities currently inside it
+struct ProximityWatch {
+    area: ProximityArea,
+    occupants: HashSet<String>,
+}
+
+/// Default wait between a player's death and their respawn
+pub const DEFAULT_RESPAWN_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// Default time updates for a removed entity are ignored (covers in-flight messages)
+pub const DEFAULT_TOMBSTONE_TTL: Duration = Duration::from_secs(10);
+
+/// Write one player record, logging failures
+fn save_record(store: &dyn PlayerStore, record: &PlayerRecord) -> bool {
+    match store.save(record) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!(entity_id = %record.user_id, error = %e, "Failed to save player progress");
+            false
+        }
+    }
 }
 
 /// Global entity state manager (tracks players, NPCs, enemies, bosses, etc.)
+/// Storage is delegated to an `EntityStore` (in-memory by default)
 #[derive(Clone)]
 pub struct EntityStateManager {
-    entities: Arc<DashMap<String, EntityState>>,
+    entities: Arc<dyn EntityStore>,
     stale_timeout: Duration,
+    max_entities: usize,
+    /// Cap on connected players, checked before the WebSocket upgrade (None = only `max_entities`)
+    max_players: Option<usize>,
+    /// Inventory op counters per entity: (window start, ops in window)
+    inventory_ops: Arc<DashMap<String, (Instant, u32)>>,
+    /// Max inventory ops per entity per second (0 = unlimited)
+    max_inventory_ops_per_sec: u32,
+    /// Max horizontal move speed in units/sec for client moves (None = unchecked)
+    max_move_speed: Option<f32>,
+    /// Enforce or shadow speed violations, with a tolerance on the limit
+    anticheat: AntiCheat,
+    /// Chunk size for chunk-crossing detection
+    chunk_size: f32,
+    /// Items given to every newly added player
+    starter_items: Vec<InventoryItem>,
+    /// Inventory slots for players without an override (0 = unlimited)
+    inventory_slots: u32,
+    /// Per-player slot counts (e.g. VIPs), applied whenever the player is added
+    inventory_slot_overrides: Arc<DashMap<String, u32>>,
+    /// Durable player progress (None = progress is lost on leave)
+    player_store: Option<Arc<dyn PlayerStore>>,
+    /// Players whose position or inventory changed since their last save
+    dirty: Arc<DashSet<String>>,
+    /// Records of removed players still being written by a blocking task, tagged with
+    /// a save number; a rejoin restores from here so it never reads an older file
+    pending_saves: Arc<DashMap<String, (u64, PlayerRecord)>>,
+    next_save: Arc<AtomicU64>,
+    /// When each dead entity died (cleared on respawn)
+    deaths: Arc<DashMap<String, Instant>>,
+    /// Minimum time between death and respawn
+    respawn_cooldown: Duration,
+    /// Where new (never saved) and respawned players are placed
+    spawns: Arc<SpawnManager>,
+    /// Gameplay trigger areas by area id (enter/leave published on `events`)
+    proximity_watchers: Arc<DashMap<String, ProximityWatch>>,
+    events: GameEventBus,
+    /// How long a disconnected player's entity is kept for a reconnect (zero = removed at once)
+    disconnect_linger: Duration,
+    /// When each lingering player disconnected (cleared on reconnect or removal)
+    disconnected: Arc<DashMap<String, Instant>>,
+    /// Recently removed entity ids; updates for them are dropped until the TTL passes
+    /// or the entity is explicitly added again
+    tombstones: Arc<DashMap<String, Instant>>,
+    tombstone_ttl: Duration,
+    /// Stale NPCs evicted to make room for a player, not yet announced to clients
+    evicted: Arc<DashSet<String>>,
+    /// Whether players may damage each other outside any PvP zone
+    pvp_default: bool,
+    /// Per-region PvP overrides; the last zone containing a chunk wins
+    pvp_zones: Arc<Vec<PvpZone>>,
+    /// Passive health regen for players in HP/sec (0 = disabled)
+    health_regen_per_sec: f32,
+    /// How long after taking damage regen stays off
+    health_regen_delay: Duration,
 }
 
 impl EntityStateManager {
     pub fn new(stale_timeout_secs: u64) -> Self {
         Self {
-            entities: Arc::new(DashMap::new()),
+            entities: Arc::new(InMemoryEntityStore::new()),
             stale_timeout: Duration::from_secs(stale_timeout_secs),
+            max_entities: DEFAULT_MAX_ENTITIES,
+            max_players: None,
+            inventory_ops: Arc::new(DashMap::new()),
+            max_inventory_ops_per_sec: DEFAULT_MAX_INVENTORY_OPS_PER_SEC,
+            max_move_speed: None,
+            anticheat: AntiCheat::default(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            starter_items: Vec::new(),
+            inventory_slots: DEFAULT_INVENTORY_SLOTS,
+            inventory_slot_overrides: Arc::new(DashMap::new()),
+            player_store: None,
+            dirty: Arc::new(DashSet::new()),
+            pending_saves: Arc::new(DashMap::new()),
+            next_save: Arc::new(AtomicU64::new(0)),
+            deaths: Arc::new(DashMap::new()),
+            respawn_cooldown: DEFAULT_RESPAWN_COOLDOWN,
+            spawns: Arc::new(SpawnManager::default()),
+            proximity_watchers: Arc::new(DashMap::new()),
+            events: GameEventBus::default(),
+            disconnect_linger: Duration::ZERO,
+            disconnected: Arc::new(DashMap::new()),
+            tombstones: Arc::new(DashMap::new()),
+            tombstone_ttl: DEFAULT_TOMBSTONE_TTL,
+            evicted: Arc::new(DashSet::new()),
+            pvp_default: true,
+            pvp_zones: Arc::new(Vec::new()),
+            health_regen_per_sec: 0.0,
+            health_regen_delay: Duration::ZERO,
+        }
+    }
+
+    /// Chunk size used for `PositionUpdateOutcome::crossed_chunk` (must match the environment)
+    pub fn with_chunk_size(mut self, chunk_size: f32) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Apply a position update and describe what changed
+    fn apply_position(&self, entity: &mut EntityState, position: Position, rotation: Option<Rotation>) -> PositionUpdateOutcome {
+        let old_chunk = ChunkCoord::from_position(&entity.position, self.chunk_size);
+        let new_chunk = ChunkCoord::from_position(&position, self.chunk_size);
+        let moved = entity.position != position;
+        entity.update_position(position, rotation);
+        if moved {
+            self.mark_dirty(entity);
+            self.events.publish(GameEvent::EntityMoved {
+                entity_id: entity.entity_id.clone(),
+                entity_type: entity.entity_type,
+                position: entity.position,
+            });
+            self.check_proximity(&entity.entity_id, &entity.position);
+        }
+        debug!(
+            entity_id = %entity.entity_id,
+            entity_type = ?entity.entity_type,
+            x = %position.x,
+            y = %position.y,
+            z = %position.z,
+            "Entity position updated"
+        );
+        PositionUpdateOutcome {
+            moved,
+            crossed_chunk: old_chunk != new_chunk,
+            new_chunk,
+            position: entity.position,
+            rotation: entity.rotation,
+        }
+    }
+
+    /// Starter kit added to each player's inventory when they join
+    /// Items that don't fit the player's slot limit, or whose metadata exceeds
+    /// MAX_ITEM_METADATA_LEN, are dropped with a warning
+    pub fn with_starter_items(mut self, items: Vec<InventoryItem>) -> Self {
+        self.starter_items = items
+            .into_iter()
+            .filter(|item| {
+                let len = item.metadata.as_ref().map_or(0, String::len);
+                if len > MAX_ITEM_METADATA_LEN {
+                    warn!(item_id = %item.item_id, len, "Dropping starter item with oversized metadata");
+                }
+                len <= MAX_ITEM_METADATA_LEN
+            })
+            .collect();
+        self
+    }
+
+    /// Inventory slots for new players (0 = unlimited); `set_inventory_slots` overrides it per player
+    pub fn with_inventory_slots(mut self, slots: u32) -> Self {
+        self.inventory_slots = slots;
+        self
+    }
+
+    /// Give a player a different slot count from the default (None clears the override)
+    /// Applies to the live entity at once and to every later `add_player`; items already
+    /// held are kept even if the new limit is smaller
+    pub fn set_inventory_slots(&self, user_id: &str, slots: Option<u32>) {
+        match slots {
+            Some(slots) => {
+                self.inventory_slot_overrides.insert(user_id.to_string(), slots);
+            }
+            None => {
+                self.inventory_slot_overrides.remove(user_id);
+            }
+        }
+        let slots = self.inventory_slots_for(user_id);
+        self.with_entity_mut(user_id, |entity| {
+            if entity.entity_type == EntityType::Player {
+                entity.inventory.max_slots = slots;
+            }
+        });
+    }
+
+    /// Slot count a player gets when added
+    pub fn inventory_slots_for(&self, user_id: &str) -> u32 {
+        self.inventory_slot_overrides.get(user_id).map_or(self.inventory_slots, |slots| *slots)
+    }
+
+    /// Save players to `store` on leave (and on `checkpoint`), restoring them on join
+    pub fn with_player_store(mut self, store: Arc<dyn PlayerStore>) -> Self {
+        self.player_store = Some(store);
+        self
+    }
+
+    /// Flag a player for the next checkpoint (non-players aren't persisted)
+    fn mark_dirty(&self, entity: &EntityState) {
+        if self.player_store.is_some() && entity.entity_type == EntityType::Player {
+            self.dirty.insert(entity.entity_id.clone());
+        }
+    }
+
+    fn save_player(&self, store: &dyn PlayerStore, entity: &EntityState) -> bool {
+        save_record(store, &PlayerRecord::from_entity(entity))
+    }
+
+    /// Save a removed player without blocking the runtime (inline outside of one)
+    fn save_removed_player(&self, store: &Arc<dyn PlayerStore>, entity: &EntityState) {
+        let record = PlayerRecord::from_entity(entity);
+        if tokio::runtime::Handle::try_current().is_err() {
+            save_record(store.as_ref(), &record);
+            return;
+        }
+        let seq = self.next_save.fetch_add(1, Ordering::Relaxed);
+        self.pending_saves.insert(record.user_id.clone(), (seq, record.clone()));
+        let (store, pending) = (store.clone(), self.pending_saves.clone());
+        tokio::task::spawn_blocking(move || {
+            save_record(store.as_ref(), &record);
+            pending.remove_if(&record.user_id, |_, (pending_seq, _)| *pending_seq == seq);
+        });
+    }
+
+    /// Saved progress for a player, including a save that is still being written
+    fn load_player(&self, store: &dyn PlayerStore, user_id: &str) -> Option<PlayerRecord> {
+        if let Some(pending) = self.pending_saves.get(user_id) {
+            return Some(pending.1.clone());
+        }
+        store
+            .load(user_id)
+            .map_err(|e| warn!(entity_id = %user_id, error = %e, "Failed to load player progress"))
+            .ok()
+            .flatten()
+    }
+
+    /// Publish `GameEvent`s (moves, area enter/leave) to a shared bus
+    pub fn with_event_bus(mut self, events: GameEventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Watch an area: entities moving in or out of it publish `EnteredArea` / `LeftArea`
+    /// Re-registering an id replaces the area and forgets who was inside
+    pub fn watch_area(&self, area_id: impl Into<String>, area: ProximityArea) {
+        self.proximity_watchers.insert(area_id.into(), ProximityWatch { area, occupants: HashSet::new() });
+    }
+
+    /// Compare an entity's new position against every watched area
+    fn check_proximity(&self, entity_id: &str, position: &Position) {
+        for mut watch in self.proximity_watchers.iter_mut() {
+            let inside = watch.area.contains(position);
+            let area_id = watch.key().clone();
+            if inside && watch.occupants.insert(entity_id.to_string()) {
+                debug!(entity_id = %entity_id, area_id = %area_id, "Entity entered watched area");
+                self.events.publish(GameEvent::EnteredArea { entity_id: entity_id.to_string(), area_id });
+            } else if !inside && watch.occupants.remove(entity_id) {
+                debug!(entity_id = %entity_id, area_id = %area_id, "Entity left watched area");
+                self.events.publish(GameEvent::LeftArea { entity_id: entity_id.to_string(), area_id });
+            }
+        }
+    }
+
+    /// Minimum wait between death and respawn (zero allows an immediate respawn)
+    pub fn with_respawn_cooldown(mut self, cooldown: Duration) -> Self {
+        self.respawn_cooldown = cooldown;
+        self
+    }
+
+    /// Where new players and `respawn_player` place players (default: the origin)
+    pub fn with_spawns(mut self, spawns: SpawnManager) -> Self {
+        self.spawns = Arc::new(spawns);
+        self
+    }
+
+    /// Keep disconnected players in the world for `linger` so a brief network blip
+    /// doesn't drop and re-add them (zero removes them on disconnect)
+    pub fn with_disconnect_linger(mut self, linger: Duration) -> Self {
+        self.disconnect_linger = linger;
+        self
+    }
+
+    /// A player's connection closed: start their linger window, or remove them right away
+    /// Returns the entity if it was removed immediately
+    pub fn disconnect_player(&self, entity_id: &str) -> Option<EntityState> {
+        if self.disconnect_linger.is_zero() {
+            return self.remove_entity(entity_id);
+        }
+        if self.entities.get(entity_id).is_some() {
+            debug!(entity_id = %entity_id, linger_secs = self.disconnect_linger.as_secs(), "Player disconnected, keeping entity for reconnect");
+            self.disconnected.insert(entity_id.to_string(), Instant::now());
+        }
+        None
+    }
+
+    /// A player reconnected; returns true if they were inside their linger window
+    pub fn reconnect_player(&self, entity_id: &str) -> bool {
+        let lingering = self.disconnected.remove(entity_id).is_some();
+        if lingering {
+            info!(entity_id = %entity_id, "Player reconnected within linger window");
+        }
+        lingering
+    }
+
+    /// Whether a player is disconnected but still lingering in the world
+    pub fn is_disconnected(&self, entity_id: &str) -> bool {
+        self.disconnected.contains_key(entity_id)
+    }
+
+    /// Remove players whose linger window ran out without a reconnect
+    pub fn expire_disconnected(&self) -> Vec<String> {
+        let expired: Vec<String> = self
+            .disconnected
+            .iter()
+            .filter(|entry| entry.value().elapsed() >= self.disconnect_linger)
+            .map(|entry| entry.key().clone())
+            .collect();
+        for entity_id in &expired {
+            self.remove_entity(entity_id);
+        }
+        if !expired.is_empty() {
+            info!(count = expired.len(), "Removed players whose reconnect window expired");
+        }
+        expired
+    }
+
+    /// Regenerate `per_sec` HP per second for living players who haven't taken damage
+    /// within `delay` (0 HP/sec disables regen)
+    pub fn with_health_regen(mut self, per_sec: f32, delay: Duration) -> Self {
+        self.health_regen_per_sec = per_sec.max(0.0);
+        self.health_regen_delay = delay;
+        self
+    }
+
+    /// Apply `elapsed` worth of passive regen; returns the players whose health changed
+    pub fn regen_health(&self, elapsed: Duration) -> Vec<EntityState> {
+        if self.health_regen_per_sec <= 0.0 {
+            return Vec::new();
+        }
+        let amount = self.health_regen_per_sec * elapsed.as_secs_f32();
+        let wounded: Vec<String> = self
+            .get_all_players()
+            .into_iter()
+            .filter(|player| player.is_alive && player.health < 100.0)
+            .map(|player| player.entity_id)
+            .collect();
+        wounded
+            .iter()
+            .filter_map(|entity_id| {
+                self.with_entity_mut(entity_id, |entity| {
+                    let in_combat = entity.last_damaged_at.is_some_and(|at| at.elapsed() < self.health_regen_delay);
+                    if in_combat || !entity.is_alive || entity.health >= 100.0 {
+                        return None;
+                    }
+                    entity.health = (entity.health + amount).min(100.0);
+                    entity.last_update = chrono::Utc::now().timestamp();
+                    Some(entity.clone())
+                })
+                .flatten()
+            })
+            .collect()
+    }
+
+    /// Reject client moves faster than `units_per_sec` (horizontal)
+    pub fn with_max_move_speed(mut self, units_per_sec: f32) -> Self {
+        self.max_move_speed = Some(units_per_sec);
+        self
+    }
+
+    /// Share an anti-cheat handle (shadow mode, tolerance) with other managers
+    pub fn with_anticheat(mut self, anticheat: AntiCheat) -> Self {
+        self.anticheat = anticheat;
+        self
+    }
+
+    /// Limit inventory add/remove operations per entity per second (0 disables the limit)
+    pub fn with_inventory_rate_limit(mut self, ops_per_sec: u32) -> Self {
+        self.max_inventory_ops_per_sec = ops_per_sec;
+        self
+    }
+
+    /// Count an inventory op against the entity's one-second window
+    /// Returns false if the entity is over its limit
+    fn allow_inventory_op(&self, entity_id: &str) -> bool {
+        if self.max_inventory_ops_per_sec == 0 {
+            return true;
+        }
+        let now = Instant::now();
+        let mut window = self.inventory_ops.entry(entity_id.to_string()).or_insert((now, 0));
+        if now.duration_since(window.0) >= Duration::from_secs(1) {
+            *window = (now, 0);
+        }
+        if window.1 >= self.max_inventory_ops_per_sec {
+            return false;
+        }
+        window.1 += 1;
+        true
+    }
+
+    /// Inventory ops the entity can still make in its current one-second window
+    /// (u32::MAX when unlimited); checking doesn't use one up
+    pub fn inventory_ops_remaining(&self, entity_id: &str) -> u32 {
+        if self.max_inventory_ops_per_sec == 0 {
+            return u32::MAX;
+        }
+        match self.inventory_ops.get(entity_id) {
+            Some(window) if window.0.elapsed() < Duration::from_secs(1) => self.max_inventory_ops_per_sec.saturating_sub(window.1),
+            _ => self.max_inventory_ops_per_sec,
+        }
+    }
+
+    /// Failure result for a rate-limited inventory op (inventory left untouched)
+    fn inventory_rate_limited(&self, entity_id: &str, item_id: &str) -> Option<(bool, Inventory)> {
+        let entity = self.entities.get(entity_id)?;
+        warn!(
+            entity_id = %entity_id,
+            item_id = %item_id,
+            limit = self.max_inventory_ops_per_sec,
+            "Inventory operation rate limited"
+        );
+        Some((false, entity.inventory))
+    }
+
+    /// Use a different storage backend (e.g. one shared across server instances)
+    pub fn with_store(mut self, store: Arc<dyn EntityStore>) -> Self {
+        self.entities = store;
+        self
+    }
+
+    /// Run `f` against an entity in the store, returning its result
+    /// Recently removed (tombstoned) entities are skipped so a late update can't resurrect them
+    fn with_entity_mut<R>(&self, entity_id: &str, f: impl FnOnce(&mut EntityState) -> R) -> Option<R> {
+        if self.is_tombstoned(entity_id) {
+            debug!(entity_id = %entity_id, "Dropped update for recently removed entity");
+            return None;
+        }
+        let mut f = Some(f);
+        let mut result = None;
+        self.entities.update(entity_id, &mut |entity| {
+            if let Some(f) = f.take() {
+                result = Some(f(entity));
+            }
+        });
+        result
+    }
+
+    /// How long updates for a removed entity are ignored
+    pub fn with_tombstone_ttl(mut self, ttl: Duration) -> Self {
+        self.tombstone_ttl = ttl;
+        self
+    }
+
+    /// Whether `entity_id` was removed within the tombstone TTL
+    pub fn is_tombstoned(&self, entity_id: &str) -> bool {
+        self.tombstones
+            .get(entity_id)
+            .is_some_and(|removed_at| removed_at.elapsed() < self.tombstone_ttl)
+    }
+
+    /// Store a newly added entity, lifting any tombstone for its id
+    fn insert_entity(&self, entity: EntityState) {
+        self.tombstones.remove(&entity.entity_id);
+        self.entities.insert(entity);
+    }
+
+    /// Cap the number of tracked entities
+    pub fn with_max_entities(mut self, max_entities: usize) -> Self {
+        self.max_entities = max_entities;
+        self
+    }
+
+    /// Cap the number of players; new players past the cap are turned away at connect time
+    pub fn with_max_players(mut self, max_players: usize) -> Self {
+        self.max_players = Some(max_players);
+        self
+    }
+
+    /// `(current, max)` players when `user_id` would be turned away by the player cap
+    /// Players still tracked (e.g. reconnecting within the linger window) always fit.
+    pub fn player_capacity_exceeded(&self, user_id: &str) -> Option<(usize, usize)> {
+        let max = self.max_players?;
+        if self.entities.contains(user_id) {
+            return None;
+        }
+        let current = self.player_count();
+        (current >= max).then_some((current, max))
+    }
+
+    /// Make room for a new entity, returns false if the world is full
+    /// Re-adding an existing id never counts against the cap. Players take priority:
+    /// when full, the least recently seen stale non-player entity is evicted for them.
+    fn reserve_slot(&self, entity_id: &str, for_player: bool) -> bool {
+        if self.entities.contains(entity_id) || self.entities.len() < self.max_entities {
+            return true;
+        }
+
+        if for_player {
+            let evict = self.entities
+                .all()
+                .into_iter()
+                .filter(|e| e.entity_type != EntityType::Player && e.is_stale(self.stale_timeout))
+                .min_by_key(|e| e.last_seen)
+                .map(|e| e.entity_id);
+            if let Some(evict_id) = evict {
+                self.remove_entity(&evict_id);
+                self.evicted.insert(evict_id.clone());
+                warn!(
+                    evicted_id = %evict_id,
+                    entity_id = %entity_id,
+                    max_entities = self.max_entities,
+                    "Entity cap reached, evicted stale NPC to make room for player"
+                );
+                return true;
+            }
         }
+
+        warn!(
+            entity_id = %entity_id,
+            max_entities = self.max_entities,
+            "Entity cap reached, refusing to add entity"
+        );
+        false
+    }
+
+    /// Entities evicted by the entity cap since the last call, for the caller to broadcast
+    pub fn take_evicted(&self) -> Vec<String> {
+        let evicted: Vec<String> = self.evicted.iter().map(|id| id.clone()).collect();
+        for entity_id in &evicted {
+            self.evicted.remove(entity_id);
+        }
+        evicted
     }
 
-    /// Add or update a player entity
-    pub fn add_player(&self, user_id: String, display_name: String) -> EntityState {
-        let entity = EntityState::new_player(user_id.clone(), display_name);
+    /// Add or update a player entity (None if the world is full)
+    pub fn add_player(&self, user_id: String, display_name: String) -> Option<EntityState> {
+        if !self.reserve_slot(&user_id, true) {
+            return None;
+        }
+        let mut entity = EntityState::new_player(user_id.clone(), display_name);
+        entity.inventory.max_slots = self.inventory_slots_for(&user_id);
+        let saved = self.player_store.as_deref().and_then(|store| self.load_player(store, &user_id));
+        if let Some(record) = saved {
+            debug!(entity_id = %user_id, saved_at = record.saved_at, "Restored saved player progress");
+            entity.position = record.position;
+            // Slot count follows the current config/override, not what it was when saved
+            entity.inventory = Inventory { max_slots: entity.inventory.max_slots, ..record.inventory };
+        } else {
+            entity.position = self.spawns.pick();
+            for item in &self.starter_items {
+                if !entity.inventory.add_item(item.item_id.clone(), item.quantity) {
+                    warn!(entity_id = %user_id, item_id = %item.item_id, "Starter item doesn't fit inventory, skipped");
+                }
+            }
+            self.mark_dirty(&entity);
+        }
         info!(
             entity_id = %user_id,
             entity_type = ?entity.entity_type,
@@ -410,12 +1375,15 @@ impl EntityStateManager {
             entity_count = self.entities.len() + 1,
             "Player entity added to game state"
         );
-        self.entities.insert(user_id, entity.clone());
-        entity
+        self.insert_entity(entity.clone());
+        Some(entity)
     }
 
-    /// Add an NPC entity
-    pub fn add_npc(&self, npc_id: String) -> EntityState {
+    /// Add an NPC entity (None if the world is full)
+    pub fn add_npc(&self, npc_id: String) -> Option<EntityState> {
+        if !self.reserve_slot(&npc_id, false) {
+            return None;
+        }
         let entity = EntityState::new_npc(npc_id.clone());
         info!(
             entity_id = %npc_id,
@@ -423,12 +1391,15 @@ impl EntityStateManager {
             entity_count = self.entities.len() + 1,
             "NPC entity added to game state"
         );
-        self.entities.insert(npc_id, entity.clone());
-        entity
+        self.insert_entity(entity.clone());
+        Some(entity)
     }
 
-    /// Add an enemy entity
-    pub fn add_enemy(&self, enemy_id: String) -> EntityState {
+    /// Add an enemy entity (None if the world is full)
+    pub fn add_enemy(&self, enemy_id: String) -> Option<EntityState> {
+        if !self.reserve_slot(&enemy_id, false) {
+            return None;
+        }
         let entity = EntityState::new_enemy(enemy_id.clone());
         info!(
             entity_id = %enemy_id,
@@ -436,12 +1407,15 @@ impl EntityStateManager {
             entity_count = self.entities.len() + 1,
             "Enemy entity added to game state"
         );
-        self.entities.insert(enemy_id, entity.clone());
-        entity
+        self.insert_entity(entity.clone());
+        Some(entity)
     }
 
-    /// Add a boss entity
-    pub fn add_boss(&self, boss_id: String, health: f32) -> EntityState {
+    /// Add a boss entity (None if the world is full)
+    pub fn add_boss(&self, boss_id: String, health: f32) -> Option<EntityState> {
+        if !self.reserve_slot(&boss_id, false) {
+            return None;
+        }
         let entity = EntityState::new_boss(boss_id.clone(), health);
         info!(
             entity_id = %boss_id,
@@ -450,14 +1424,32 @@ impl EntityStateManager {
             entity_count = self.entities.len() + 1,
             "Boss entity added to game state"
         );
-        self.entities.insert(boss_id, entity.clone());
-        entity
+        self.insert_entity(entity.clone());
+        Some(entity)
     }
 
     /// Remove an entity
     pub fn remove_entity(&self, entity_id: &str) -> Option<EntityState> {
-        let removed = self.entities.remove(entity_id).map(|(_, entity)| entity);
+        let removed = self.entities.remove(entity_id);
+        let ttl = self.tombstone_ttl;
+        self.tombstones.retain(|_, removed_at| removed_at.elapsed() < ttl);
+        if removed.is_some() && !ttl.is_zero() {
+            self.tombstones.insert(entity_id.to_string(), Instant::now());
+        }
+        self.inventory_ops.remove(entity_id);
+        self.dirty.remove(entity_id);
+        self.deaths.remove(entity_id);
+        self.disconnected.remove(entity_id);
+        for mut watch in self.proximity_watchers.iter_mut() {
+            if watch.occupants.remove(entity_id) {
+                let area_id = watch.key().clone();
+                self.events.publish(GameEvent::LeftArea { entity_id: entity_id.to_string(), area_id });
+            }
+        }
         if let Some(ref entity) = removed {
+            if let Some(store) = self.player_store.as_ref().filter(|_| entity.entity_type == EntityType::Player) {
+                self.save_removed_player(store, entity);
+            }
             info!(
                 entity_id = %entity_id,
                 entity_type = ?entity.entity_type,
@@ -474,61 +1466,238 @@ impl EntityStateManager {
         entity_id: &str,
         position: Position,
         rotation: Option<Rotation>,
-    ) -> Option<EntityState> {
-        self.entities.get_mut(entity_id).map(|mut entity| {
-            entity.update_position(position, rotation);
-            debug!(
-                entity_id = %entity_id,
-                entity_type = ?entity.entity_type,
-                x = %position.x,
-                y = %position.y,
-                z = %position.z,
-                "Entity position updated"
-            );
-            entity.clone()
+    ) -> Option<PositionUpdateOutcome> {
+        self.with_entity_mut(entity_id, |entity| self.apply_position(entity, position, rotation))
+    }
+
+    /// Move an entity on behalf of its client, enforcing the speed limit
+    /// (scaled by the anti-cheat tolerance; only logged in shadow mode)
+    /// Server-driven moves (teleports, NPCs) should use `update_position`
+    pub fn move_entity(
+        &self,
+        entity_id: &str,
+        position: Position,
+        rotation: Option<Rotation>,
+    ) -> Option<MoveResult> {
+        self.with_entity_mut(entity_id, |entity| {
+            if let Some(max_speed) = self.max_move_speed {
+                let window = entity.last_seen.elapsed().max(MIN_MOVE_WINDOW).as_secs_f32();
+                let dx = position.x - entity.position.x;
+                let dz = position.z - entity.position.z;
+                let distance = (dx * dx + dz * dz).sqrt();
+                let allowed = max_speed * window * self.anticheat.tolerance();
+                if distance > allowed && self.anticheat.report(Violation::Speed, entity_id) {
+                    warn!(
+                        entity_id = %entity_id,
+                        distance = %distance,
+                        allowed = %allowed,
+                        "Move rejected (too fast)"
+                    );
+                    return MoveResult::Rejected(Box::new(entity.clone()));
+                }
+            }
+            MoveResult::Moved(self.apply_position(entity, position, rotation))
         })
     }
 
     /// Update entity health
     pub fn update_health(&self, entity_id: &str, health: f32) -> Option<EntityState> {
-        self.entities.get_mut(entity_id).map(|mut entity| {
-            let was_alive = entity.is_alive;
-            entity.update_health(health);
-            if was_alive && !entity.is_alive {
-                warn!(
-                    entity_id = %entity_id,
-                    entity_type = ?entity.entity_type,
-                    health = %entity.health,
-                    "Entity died"
-                );
-            }
-            debug!(
-                entity_id = %entity_id,
+        self.with_entity_mut(entity_id, |entity| {
+            self.set_health(entity, health);
+            entity.clone()
+        })
+    }
+
+    /// Set health on a locked entity, recording the death if this kills it
+    fn set_health(&self, entity: &mut EntityState, health: f32) {
+        let was_alive = entity.is_alive;
+        entity.update_health(health);
+        if was_alive && !entity.is_alive {
+            self.deaths.insert(entity.entity_id.clone(), Instant::now());
+            warn!(
+                entity_id = %entity.entity_id,
                 entity_type = ?entity.entity_type,
-                health = %health,
-                is_alive = entity.is_alive,
-                "Entity health updated"
+                health = %entity.health,
+                "Entity died"
             );
-            entity.clone()
+        }
+        debug!(
+            entity_id = %entity.entity_id,
+            entity_type = ?entity.entity_type,
+            health = %health,
+            is_alive = entity.is_alive,
+            "Entity health updated"
+        );
+    }
+
+    /// PvP rule outside zones, plus per-chunk-region overrides (e.g. PvE-only towns)
+    pub fn with_pvp_zones(mut self, pvp_default: bool, zones: Vec<PvpZone>) -> Self {
+        self.pvp_default = pvp_default;
+        self.pvp_zones = Arc::new(zones);
+        self
+    }
+
+    /// Whether players may damage each other at `position`
+    pub fn pvp_allowed_at(&self, position: &Position) -> bool {
+        let chunk = ChunkCoord::from_position(position, self.chunk_size);
+        self.pvp_zones
+            .iter()
+            .rev()
+            .find(|zone| zone.contains(&chunk))
+            .map_or(self.pvp_default, |zone| zone.pvp)
+    }
+
+    /// Deal `amount` damage from `attacker_id` to `target_id`
+    /// Rejected for friendly fire (same team), player-vs-player where either side stands in
+    /// a PvE zone, dead or missing entities, and invalid amounts
+    /// Server-side damage sources reach this through POST /admin/entities/damage
+    pub fn apply_damage(&self, attacker_id: &str, target_id: &str, amount: f32) -> Result<EntityState, String> {
+        if !amount.is_finite() || amount < 0.0 {
+            return Err(format!("Invalid damage amount: {}", amount));
+        }
+        let attacker = self.entities.get(attacker_id).ok_or_else(|| "Attacker not found".to_string())?;
+        if !attacker.is_alive {
+            return Err("Attacker is dead".to_string());
+        }
+
+        self.with_entity_mut(target_id, |target| {
+            if !target.is_alive {
+                return Err("Target is already dead".to_string());
+            }
+            if attacker.is_teammate_of(target) {
+                debug!(attacker_id = %attacker_id, target_id = %target_id, team = ?target.team, "Friendly fire rejected");
+                return Err("Can't damage a teammate".to_string());
+            }
+            let player_vs_player = attacker.entity_type == EntityType::Player && target.entity_type == EntityType::Player;
+            if player_vs_player && !(self.pvp_allowed_at(&attacker.position) && self.pvp_allowed_at(&target.position)) {
+                debug!(attacker_id = %attacker_id, target_id = %target_id, "PvP attack rejected in PvE zone");
+                return Err("PvP is disabled in this zone".to_string());
+            }
+            let health = target.health - amount;
+            self.set_health(target, health);
+            Ok(target.clone())
+        })
+        .unwrap_or_else(|| Err("Target not found".to_string()))
+    }
+
+    /// Put an entity on a team (None leaves its team); false if the entity doesn't exist
+    pub fn set_team(&self, entity_id: &str, team: Option<String>) -> bool {
+        self.with_entity_mut(entity_id, |entity| {
+            entity.team = team;
+            entity.last_update = chrono::Utc::now().timestamp();
+        })
+        .is_some()
+    }
+
+    /// Other entities on the same team as `entity_id` (empty without a team)
+    pub fn teammates(&self, entity_id: &str) -> Vec<EntityState> {
+        let Some(entity) = self.entities.get(entity_id) else {
+            return Vec::new();
+        };
+        if entity.team.is_none() {
+            return Vec::new();
+        }
+        self.entities
+            .all()
+            .into_iter()
+            .filter(|other| other.entity_id != entity_id && other.is_teammate_of(&entity))
+            .collect()
+    }
+
+    /// Bring a dead entity back at full health at the spawn point
+    /// Fails if the entity is missing, alive, or still within the respawn cooldown
+    pub fn respawn_player(&self, entity_id: &str) -> Result<(EntityState, PositionUpdateOutcome), String> {
+        // Checked and applied under one entity guard so concurrent respawns can't both pass
+        self.with_entity_mut(entity_id, |entity| {
+            if entity.is_alive {
+                return Err("Can't respawn while alive".to_string());
+            }
+            if let Some(died_at) = self.deaths.get(entity_id).map(|d| *d) {
+                let remaining = self.respawn_cooldown.saturating_sub(died_at.elapsed());
+                if !remaining.is_zero() {
+                    return Err(format!("Respawn available in {}ms", remaining.as_millis()));
+                }
+            }
+            entity.update_health(100.0);
+            let outcome = self.apply_position(entity, self.spawns.pick(), None);
+            self.deaths.remove(entity_id);
+            info!(entity_id = %entity_id, position = ?entity.position, "Entity respawned");
+            Ok((entity.clone(), outcome))
         })
+        .unwrap_or_else(|| Err("Player not in game. Send 'join' first.".to_string()))
+    }
+
+    /// Change a server-driven entity's type (e.g. promote an NPC to an Enemy)
+    /// Players can't change type and nothing can become a player (player ids are user ids)
+    /// Returns the `EntityTypeChanged` message to broadcast
+    pub fn set_entity_type(&self, entity_id: &str, new_type: EntityType) -> Result<ServerMessage, String> {
+        if new_type == EntityType::Player {
+            return Err("Entities can't be changed into players".to_string());
+        }
+        let changed = self.with_entity_mut(entity_id, |entity| {
+            if entity.entity_type == EntityType::Player {
+                return Err("Players can't change type".to_string());
+            }
+            info!(
+                entity_id = %entity_id,
+                from = ?entity.entity_type,
+                to = ?new_type,
+                "Entity type changed"
+            );
+            entity.entity_type = new_type;
+            entity.last_update = chrono::Utc::now().timestamp();
+            Ok(())
+        });
+        match changed {
+            Some(Ok(())) => Ok(ServerMessage::EntityTypeChanged {
+                entity_id: entity_id.to_string(),
+                entity_type: new_type,
+            }),
+            Some(Err(e)) => Err(e),
+            None => Err("Entity not found".to_string()),
+        }
+    }
+
+    /// Entities AI should treat as hostile (enemies and bosses)
+    pub fn get_hostile_entities(&self) -> Vec<EntityState> {
+        self.entities
+            .all()
+            .into_iter()
+            .filter(|entity| entity.entity_type.is_hostile())
+            .collect()
     }
 
     /// Get an entity's current state
     pub fn get_entity(&self, entity_id: &str) -> Option<EntityState> {
-        self.entities.get(entity_id).map(|entity| entity.clone())
+        self.entities.get(entity_id)
+    }
+
+    /// Check whether `user_id` has authority over `entity_id`
+    /// Players only own their own entity; server-driven entities (NPCs, enemies, bosses) have no client owner
+    pub fn is_owned_by(&self, entity_id: &str, user_id: &str) -> bool {
+        self.entities
+            .get(entity_id)
+            .map(|entity| entity.entity_type == EntityType::Player && entity.entity_id == user_id)
+            .unwrap_or(false)
     }
 
     /// Get all entities
     pub fn get_all_entities(&self) -> Vec<EntityState> {
-        self.entities.iter().map(|entry| entry.value().clone()).collect()
+        self.entities.all()
+    }
+
+    /// One page of entities ordered by entity id, plus the total entity count
+    /// (for admin listings of large worlds; an offset past the end yields an empty page)
+    pub fn get_all_entities_paginated(&self, offset: usize, limit: usize) -> (Vec<EntityState>, usize) {
+        self.entities.page(offset, limit)
     }
 
     /// Get all player entities
     pub fn get_all_players(&self) -> Vec<EntityState> {
         self.entities
-            .iter()
-            .filter(|entry| entry.value().entity_type == EntityType::Player)
-            .map(|entry| entry.value().clone())
+            .all()
+            .into_iter()
+            .filter(|entity| entity.entity_type == EntityType::Player)
             .collect()
     }
 
@@ -539,17 +1708,20 @@ impl EntityStateManager {
 
     /// Get player count
     pub fn player_count(&self) -> usize {
-        self.entities
-            .iter()
-            .filter(|entry| entry.value().entity_type == EntityType::Player)
-            .count()
+        self.entities.count_of_type(EntityType::Player)
     }
 
     /// Add item to entity's inventory
+    /// Server-authorized sources only (harvest rewards, refunds); clients can't add items directly
+    /// Rate-limited ops return `(false, unchanged inventory)`
     pub fn add_item(&self, entity_id: &str, item_id: String, quantity: u32) -> Option<(bool, Inventory)> {
-        self.entities.get_mut(entity_id).map(|mut entity| {
+        if !self.allow_inventory_op(entity_id) {
+            return self.inventory_rate_limited(entity_id, &item_id);
+        }
+        self.with_entity_mut(entity_id, |entity| {
             let success = entity.inventory.add_item(item_id.clone(), quantity);
             if success {
+                self.mark_dirty(entity);
                 info!(
                     entity_id = %entity_id,
                     entity_type = ?entity.entity_type,
@@ -571,10 +1743,15 @@ impl EntityStateManager {
     }
 
     /// Remove item from entity's inventory
+    /// Rate-limited ops return `(false, unchanged inventory)`
     pub fn remove_item(&self, entity_id: &str, item_id: &str, quantity: u32) -> Option<(bool, Inventory)> {
-        self.entities.get_mut(entity_id).map(|mut entity| {
+        if !self.allow_inventory_op(entity_id) {
+            return self.inventory_rate_limited(entity_id, item_id);
+        }
+        self.with_entity_mut(entity_id, |entity| {
             let success = entity.inventory.remove_item(item_id, quantity);
             if success {
+                self.mark_dirty(entity);
                 info!(
                     entity_id = %entity_id,
                     entity_type = ?entity.entity_type,
@@ -595,22 +1772,64 @@ impl EntityStateManager {
         })
     }
 
+    /// Equip a tool (None unequips)
+    /// Returns None if the entity doesn't exist, Some(false) if the item isn't in the inventory
+    pub fn equip_tool(&self, entity_id: &str, item_id: Option<String>) -> Option<bool> {
+        self.with_entity_mut(entity_id, |entity| {
+            if let Some(id) = &item_id {
+                if !entity.inventory.has_item(id, 1) {
+                    return false;
+                }
+            }
+            debug!(entity_id = %entity_id, tool = ?item_id, "Tool equipped");
+            entity.equipped_tool = item_id;
+            true
+        })
+    }
+
+    /// Set a custom attribute on an entity (returns false if the entity doesn't exist)
+    /// Keys starting with `_` are kept out of snapshots sent to other clients
+    pub fn set_attribute(&self, entity_id: &str, key: impl Into<String>, value: serde_json::Value) -> bool {
+        let key = key.into();
+        self.with_entity_mut(entity_id, |entity| {
+            entity.attributes.insert(key, value);
+            entity.last_update = chrono::Utc::now().timestamp();
+        })
+        .is_some()
+    }
+
+    /// Read a custom attribute
+    pub fn get_attribute(&self, entity_id: &str, key: &str) -> Option<serde_json::Value> {
+        self.entities.get(entity_id).and_then(|entity| entity.attributes.get(key).cloned())
+    }
+
+    /// Remove a custom attribute, returning its old value
+    pub fn remove_attribute(&self, entity_id: &str, key: &str) -> Option<serde_json::Value> {
+        self.with_entity_mut(entity_id, |entity| entity.attributes.remove(key)).flatten()
+    }
+
+    /// Equipped tool, if it is still in the entity's inventory
+    pub fn get_equipped_tool(&self, entity_id: &str) -> Option<String> {
+        self.entities.get(entity_id).and_then(|entity| {
+            entity
+                .equipped_tool
+                .clone()
+                .filter(|tool| entity.inventory.has_item(tool, 1))
+        })
+    }
+
     /// Get entity's inventory
     pub fn get_inventory(&self, entity_id: &str) -> Option<Inventory> {
-        self.entities.get(entity_id).map(|entity| entity.inventory.clone())
+        self.entities.get(entity_id).map(|entity| entity.inventory)
     }
 
     /// Clean up stale entities (haven't sent updates in a while)
     pub fn cleanup_stale_entities(&self) -> Vec<String> {
         let stale_entities: Vec<String> = self.entities
-            .iter()
-            .filter_map(|entry| {
-                if entry.value().is_stale(self.stale_timeout) {
-                    Some(entry.key().clone())
-                } else {
-                    None
-                }
-            })
+            .all()
+            .into_iter()
+            .filter(|entity| entity.is_stale(self.stale_timeout))
+            .map(|entity| entity.entity_id)
             .collect();
 
         if !stale_entities.is_empty() {
@@ -627,6 +1846,58 @@ impl EntityStateManager {
         stale_entities
     }
 
+    /// Save every online player changed since the last checkpoint
+    /// Returns the number saved; failed saves stay dirty for the next checkpoint
+    pub fn checkpoint(&self) -> usize {
+        let Some(store) = self.player_store.as_deref() else {
+            return 0;
+        };
+        let dirty: Vec<String> = self.dirty.iter().map(|id| id.clone()).collect();
+        let mut saved = 0;
+        for entity_id in dirty {
+            self.dirty.remove(&entity_id);
+            // Players who left since were saved on removal
+            let Some(entity) = self.entities.get(&entity_id) else {
+                continue;
+            };
+            if self.save_player(store, &entity) {
+                saved += 1;
+            } else {
+                self.dirty.insert(entity_id);
+            }
+        }
+        saved
+    }
+
+    /// `checkpoint` on the blocking pool, for callers on the async runtime
+    pub async fn checkpoint_blocking(&self) -> usize {
+        let manager = self.clone();
+        match tokio::task::spawn_blocking(move || manager.checkpoint()).await {
+            Ok(saved) => saved,
+            Err(e) => {
+                warn!(error = %e, "Player checkpoint task failed");
+                0
+            }
+        }
+    }
+
+    /// Run periodic player checkpointing (no-op without a player store)
+    pub async fn run_checkpoint_task(self, checkpoint_interval: Duration) {
+        if self.player_store.is_none() {
+            return;
+        }
+        info!(checkpoint_interval_secs = checkpoint_interval.as_secs(), "Starting player checkpoint task");
+
+        let mut interval = tokio::time::interval(checkpoint_interval);
+        loop {
+            interval.tick().await;
+            let saved = self.checkpoint_blocking().await;
+            if saved > 0 {
+                debug!(saved, "Checkpointed player progress");
+            }
+        }
+    }
+
     /// Run periodic cleanup task
     pub async fn run_cleanup_task(self, cleanup_interval_secs: u64) {
         use tokio::time;
@@ -666,3 +1937,604 @@ impl Default for EntityStateManager {
         Self::new(120) // 2 minute timeout by default
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_lerp() {
+        let a = Position::new(0.0, 2.0, -4.0);
+        let b = Position::new(10.0, 4.0, 4.0);
+        assert_eq!(a.lerp(&b, 0.0), a);
+        assert_eq!(a.lerp(&b, 0.5), Position::new(5.0, 3.0, 0.0));
+        assert_eq!(a.lerp(&b, 1.0), b);
+    }
+
+    #[test]
+    fn test_game_message_parse_rejects_deep_nesting() {
+        assert!(matches!(GameMessage::parse(r#"{"type":"ping"}"#), Ok(GameMessage::Ping)));
+        // Brackets inside strings don't count towards depth
+        let bracey = format!(r#"{{"type":"remove_item","item_id":"{}","quantity":1}}"#, "[{".repeat(100));
+        assert!(GameMessage::parse(&bracey).is_ok());
+
+        let depth = MAX_GAME_MESSAGE_LEN / 2 - 20;
+        let nested = format!(r#"{{"type":"ping","extra":{}{}}}"#, "[".repeat(depth), "]".repeat(depth));
+        let start = Instant::now();
+        assert!(matches!(GameMessage::parse(&nested), Err(GameMessageError::TooDeep { .. })));
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        let huge = format!(r#"{{"type":"ping","extra":"{}"}}"#, "x".repeat(MAX_GAME_MESSAGE_LEN));
+        assert!(matches!(GameMessage::parse(&huge), Err(GameMessageError::TooLarge { .. })));
+        assert!(matches!(GameMessage::parse("hello"), Err(GameMessageError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_custom_attributes_roundtrip_and_snapshot() {
+        let manager = EntityStateManager::new(120);
+        manager.add_player("user-mage-00001".to_string(), "mage".to_string());
+        assert!(manager.set_attribute("user-mage-00001", "level", serde_json::json!(7)));
+        assert!(manager.set_attribute("user-mage-00001", "team", serde_json::json!("red")));
+        assert!(manager.set_attribute("user-mage-00001", "_quest_flags", serde_json::json!([1, 2])));
+        assert!(!manager.set_attribute("user-missing-0001", "level", serde_json::json!(1)));
+        assert_eq!(manager.get_attribute("user-mage-00001", "level"), Some(serde_json::json!(7)));
+
+        let entity = manager.get_entity("user-mage-00001").unwrap();
+        let snapshot = serde_json::to_value(&entity).unwrap();
+        assert_eq!(snapshot["attributes"]["team"], "red");
+        assert_eq!(snapshot["attributes"]["_quest_flags"], serde_json::json!([1, 2]));
+
+        // Other clients only see public attributes
+        let public = serde_json::to_value(entity.public()).unwrap();
+        assert_eq!(public["attributes"]["level"], 7);
+        assert!(public["attributes"].get("_quest_flags").is_none());
+
+        assert_eq!(manager.remove_attribute("user-mage-00001", "team"), Some(serde_json::json!("red")));
+        assert_eq!(manager.get_attribute("user-mage-00001", "team"), None);
+    }
+
+    #[test]
+    fn test_apply_damage_rejects_friendly_fire() {
+        let manager = EntityStateManager::new(120);
+        for id in ["user-red-000001", "user-red-000002", "user-blue-00001"] {
+            manager.add_player(id.to_string(), id.to_string());
+        }
+        assert!(manager.set_team("user-red-000001", Some("red".to_string())));
+        assert!(manager.set_team("user-red-000002", Some("red".to_string())));
+        assert!(manager.set_team("user-blue-00001", Some("blue".to_string())));
+
+        let teammates = manager.teammates("user-red-000001");
+        assert_eq!(teammates.len(), 1);
+        assert_eq!(teammates[0].entity_id, "user-red-000002");
+
+        assert!(manager.apply_damage("user-red-000001", "user-red-000002", 30.0).is_err());
+        assert_eq!(manager.get_entity("user-red-000002").unwrap().health, 100.0);
+
+        let target = manager.apply_damage("user-red-000001", "user-blue-00001", 30.0).unwrap();
+        assert_eq!(target.health, 70.0);
+        let target = manager.apply_damage("user-red-000002", "user-blue-00001", 80.0).unwrap();
+        assert!(!target.is_alive);
+        assert!(manager.apply_damage("user-red-000001", "user-blue-00001", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_get_all_entities_paginated_boundaries() {
+        let manager = EntityStateManager::new(120);
+        for i in 0..5 {
+            manager.add_npc(format!("npc-0000000{i}"));
+        }
+
+        let (first, total) = manager.get_all_entities_paginated(0, 2);
+        assert_eq!(total, 5);
+        let ids: Vec<_> = first.iter().map(|e| e.entity_id.as_str()).collect();
+        assert_eq!(ids, ["npc-00000000", "npc-00000001"]);
+
+        let (last, total) = manager.get_all_entities_paginated(4, 2);
+        assert_eq!(total, 5);
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].entity_id, "npc-00000004");
+
+        let (beyond, total) = manager.get_all_entities_paginated(10, 2);
+        assert!(beyond.is_empty());
+        assert_eq!(total, 5);
+    }
+
+    #[test]
+    fn test_update_position_reports_chunk_crossing() {
+        let manager = EntityStateManager::new(120).with_chunk_size(50.0);
+        manager.add_player("user-walker-0001".to_string(), "walker".to_string());
+        manager.update_position("user-walker-0001", Position::new(10.0, 0.0, 10.0), None);
+
+        let small = manager.update_position("user-walker-0001", Position::new(10.5, 0.0, 10.0), None).unwrap();
+        assert!(small.moved);
+        assert!(!small.crossed_chunk);
+        assert_eq!(small.new_chunk, ChunkCoord { x: 0, z: 0 });
+
+        let same = manager.update_position("user-walker-0001", Position::new(10.5, 0.0, 10.0), None).unwrap();
+        assert!(!same.moved);
+
+        let across = manager.update_position("user-walker-0001", Position::new(51.0, 0.0, 10.0), None).unwrap();
+        assert!(across.crossed_chunk);
+        assert_eq!(across.new_chunk, ChunkCoord { x: 1, z: 0 });
+        assert!(manager.update_position("missing", Position::default(), None).is_none());
+    }
+
+    #[test]
+    fn test_new_player_receives_starter_items() {
+        let starter = vec![
+            InventoryItem::new("stone_axe".to_string(), 1),
+            InventoryItem::new("seed".to_string(), 3),
+        ];
+        let manager = EntityStateManager::new(120).with_starter_items(starter);
+        let player = manager.add_player("user-newbie-0001".to_string(), "newbie".to_string()).unwrap();
+
+        let items: Vec<_> = player.inventory.items.iter().map(|i| (i.item_id.as_str(), i.quantity)).collect();
+        assert_eq!(items, vec![("stone_axe", 1), ("seed", 3)]);
+        assert_eq!(manager.get_inventory("user-newbie-0001").unwrap().items.len(), 2);
+        assert!(manager.add_npc("npc-00000001".to_string()).unwrap().inventory.items.is_empty());
+    }
+
+    #[test]
+    fn test_apply_damage_respects_pvp_zones() {
+        // Chunks around the origin are a PvE town; everything else allows PvP
+        let town = PvpZone { min: ChunkCoord { x: -1, z: -1 }, max: ChunkCoord { x: 1, z: 1 }, pvp: false };
+        let manager = EntityStateManager::new(120).with_pvp_zones(true, vec![town]);
+        for id in ["user-pvp-000001", "user-pvp-000002"] {
+            manager.add_player(id.to_string(), id.to_string());
+            manager.update_position(id, Position::new(10.0, 0.0, 10.0), None);
+        }
+        manager.add_enemy("enemy-00000001".to_string());
+        manager.update_position("enemy-00000001", Position::new(12.0, 0.0, 10.0), None);
+
+        let err = manager.apply_damage("user-pvp-000001", "user-pvp-000002", 10.0).unwrap_err();
+        assert!(err.contains("PvP"));
+        assert_eq!(manager.get_entity("user-pvp-000002").unwrap().health, 100.0);
+        assert!(manager.apply_damage("user-pvp-000001", "enemy-00000001", 10.0).is_ok());
+        assert!(manager.apply_damage("enemy-00000001", "user-pvp-000002", 10.0).is_ok());
+
+        // Both players out in the wilds
+        for id in ["user-pvp-000001", "user-pvp-000002"] {
+            manager.update_position(id, Position::new(500.0, 0.0, 500.0), None);
+        }
+        let target = manager.apply_damage("user-pvp-000001", "user-pvp-000002", 10.0).unwrap();
+        assert_eq!(target.health, 80.0);
+    }
+
+    #[test]
+    fn test_inventory_slots_configured_default_and_override() {
+        let manager = EntityStateManager::new(120).with_inventory_slots(30);
+        let regular = manager.add_player("user-regular-001".to_string(), "regular".to_string()).unwrap();
+        assert_eq!(regular.inventory.max_slots, 30);
+
+        manager.set_inventory_slots("user-vip-0000001", Some(50));
+        let vip = manager.add_player("user-vip-0000001".to_string(), "vip".to_string()).unwrap();
+        assert_eq!(vip.inventory.max_slots, 50);
+        assert_eq!(manager.inventory_slots_for("user-regular-001"), 30);
+
+        // Clearing the override applies to the live entity too
+        manager.set_inventory_slots("user-vip-0000001", None);
+        assert_eq!(manager.get_inventory("user-vip-0000001").unwrap().max_slots, 30);
+    }
+
+    #[test]
+    fn test_starter_items_respect_slot_limit() {
+        let starter = (0..25).map(|i| InventoryItem::new(format!("item_{i}"), 1)).collect();
+        let manager = EntityStateManager::new(120).with_starter_items(starter);
+        let player = manager.add_player("user-newbie-0002".to_string(), "newbie".to_string()).unwrap();
+        assert_eq!(player.inventory.items.len(), player.inventory.max_slots as usize);
+    }
+
+    #[test]
+    fn test_checkpoint_saves_only_dirty_players() {
+        use crate::game::player_store::InMemoryPlayerStore;
+
+        let store = Arc::new(InMemoryPlayerStore::new());
+        let manager = EntityStateManager::new(120).with_player_store(store.clone());
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+        manager.add_player("user-bbbb-0002".to_string(), "bob".to_string());
+
+        // First checkpoint writes the newly joined players
+        assert_eq!(manager.checkpoint(), 2);
+        assert_eq!(manager.checkpoint(), 0);
+
+        manager.add_item("user-aaaa-0001", "wood".to_string(), 5).unwrap();
+        let bob_saved_at = store.load("user-bbbb-0002").unwrap().unwrap().saved_at;
+        assert_eq!(manager.checkpoint(), 1);
+
+        let alice = store.load("user-aaaa-0001").unwrap().unwrap();
+        assert_eq!(alice.inventory.get_item_quantity("wood"), 5);
+        assert_eq!(store.load("user-bbbb-0002").unwrap().unwrap().saved_at, bob_saved_at);
+    }
+
+    /// Player store whose saves wait until the test releases them
+    struct GatedStore {
+        inner: crate::game::player_store::InMemoryPlayerStore,
+        gate: std::sync::Mutex<()>,
+        saved: std::sync::Mutex<std::sync::mpsc::Sender<String>>,
+    }
+
+    impl PlayerStore for GatedStore {
+        fn load(&self, user_id: &str) -> anyhow::Result<Option<PlayerRecord>> {
+            self.inner.load(user_id)
+        }
+
+        fn save(&self, record: &PlayerRecord) -> anyhow::Result<()> {
+            let _open = self.gate.lock().unwrap();
+            self.inner.save(record)?;
+            let _ = self.saved.lock().unwrap().send(record.user_id.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_leave_saves_off_runtime_and_rejoin_sees_pending_save() {
+        let (saved_tx, saved_rx) = std::sync::mpsc::channel();
+        let store = Arc::new(GatedStore {
+            inner: crate::game::player_store::InMemoryPlayerStore::new(),
+            gate: std::sync::Mutex::new(()),
+            saved: std::sync::Mutex::new(saved_tx),
+        });
+        let manager = EntityStateManager::new(120).with_player_store(store.clone());
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+        manager.add_item("user-aaaa-0001", "stone".to_string(), 2).unwrap();
+
+        // The save is stuck behind the gate, yet removal returns and a rejoin restores
+        let gate = store.gate.lock().unwrap();
+        assert!(manager.remove_entity("user-aaaa-0001").is_some());
+        assert!(store.inner.load("user-aaaa-0001").unwrap().is_none());
+        let alice = manager.add_player("user-aaaa-0001".to_string(), "alice".to_string()).unwrap();
+        assert_eq!(alice.inventory.get_item_quantity("stone"), 2);
+
+        drop(gate);
+        let saved = tokio::task::spawn_blocking(move || saved_rx.recv().unwrap()).await.unwrap();
+        assert_eq!(saved, "user-aaaa-0001");
+        assert_eq!(store.inner.load("user-aaaa-0001").unwrap().unwrap().inventory.get_item_quantity("stone"), 2);
+    }
+
+    #[test]
+    fn test_player_progress_restored_after_leave() {
+        use crate::game::player_store::InMemoryPlayerStore;
+
+        let store = Arc::new(InMemoryPlayerStore::new());
+        let starter = vec![InventoryItem::new("torch".to_string(), 1)];
+        let manager = EntityStateManager::new(120).with_player_store(store).with_starter_items(starter);
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+        manager.update_position("user-aaaa-0001", Position::new(8.0, 0.0, 3.0), None);
+        manager.add_item("user-aaaa-0001", "stone".to_string(), 2).unwrap();
+        manager.remove_entity("user-aaaa-0001");
+
+        let alice = manager.add_player("user-aaaa-0001".to_string(), "alice".to_string()).unwrap();
+        assert_eq!(alice.position, Position::new(8.0, 0.0, 3.0));
+        assert_eq!(alice.inventory.get_item_quantity("stone"), 2);
+        assert_eq!(alice.inventory.get_item_quantity("torch"), 1);
+    }
+
+    #[test]
+    fn test_respawn_dead_player() {
+        let manager = EntityStateManager::new(120).with_respawn_cooldown(Duration::ZERO);
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+        manager.update_position("user-aaaa-0001", Position::new(120.0, 0.0, 40.0), None);
+
+        // Living players can't respawn
+        assert!(manager.respawn_player("user-aaaa-0001").is_err());
+
+        manager.update_health("user-aaaa-0001", 0.0);
+        let (player, outcome) = manager.respawn_player("user-aaaa-0001").unwrap();
+        assert!(player.is_alive);
+        assert_eq!(player.health, 100.0);
+        assert_eq!(player.position, Position::default());
+        assert!(outcome.crossed_chunk);
+        assert!(manager.respawn_player("user-aaaa-0001").is_err());
+    }
+
+    #[test]
+    fn test_concurrent_respawns_only_one_succeeds() {
+        let manager = EntityStateManager::new(120).with_respawn_cooldown(Duration::ZERO);
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+        manager.update_health("user-aaaa-0001", 0.0);
+
+        let barrier = std::sync::Barrier::new(8);
+        let respawned = std::thread::scope(|scope| {
+            let attempts: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| {
+                    barrier.wait();
+                    manager.respawn_player("user-aaaa-0001").is_ok()
+                }))
+                .collect();
+            attempts.into_iter().map(|attempt| attempt.join().unwrap()).filter(|ok| *ok).count()
+        });
+        assert_eq!(respawned, 1);
+    }
+
+    #[test]
+    fn test_respawn_cooldown_enforced() {
+        let manager = EntityStateManager::new(120).with_respawn_cooldown(Duration::from_secs(30));
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+        manager.update_health("user-aaaa-0001", 0.0);
+
+        let err = manager.respawn_player("user-aaaa-0001").unwrap_err();
+        assert!(err.starts_with("Respawn available in"), "{err}");
+
+        // Backdate the death past the cooldown
+        manager.deaths.insert("user-aaaa-0001".to_string(), Instant::now() - Duration::from_secs(31));
+        assert!(manager.respawn_player("user-aaaa-0001").is_ok());
+    }
+
+    #[test]
+    fn test_proximity_area_enter_and_leave_once() {
+        let events = GameEventBus::default();
+        let mut triggers = events.subscribe();
+        let manager = EntityStateManager::new(120).with_event_bus(events);
+        manager.watch_area("boss_lair", ProximityArea { center: Position::new(50.0, 0.0, 50.0), radius: 10.0 });
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+
+        // Walk in, wander around inside, walk out
+        for (x, z) in [(30.0, 50.0), (42.0, 50.0), (50.0, 50.0), (55.0, 52.0), (70.0, 50.0), (80.0, 50.0)] {
+            manager.update_position("user-aaaa-0001", Position::new(x, 5.0, z), None);
+        }
+
+        let entered = GameEvent::EnteredArea { entity_id: "user-aaaa-0001".to_string(), area_id: "boss_lair".to_string() };
+        let left = GameEvent::LeftArea { entity_id: "user-aaaa-0001".to_string(), area_id: "boss_lair".to_string() };
+        // Every move is published too; only the area triggers matter here
+        let mut area_events = || {
+            std::iter::from_fn(|| triggers.try_recv().ok())
+                .filter(|e| !matches!(e, GameEvent::EntityMoved { .. }))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(area_events(), vec![entered.clone(), left.clone()]);
+
+        // Leaving the world while inside counts as leaving the area
+        manager.update_position("user-aaaa-0001", Position::new(50.0, 0.0, 50.0), None);
+        manager.remove_entity("user-aaaa-0001");
+        assert_eq!(area_events(), vec![entered, left]);
+    }
+
+    #[test]
+    fn test_is_owned_by() {
+        let manager = EntityStateManager::new(120);
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+        manager.add_player("user-bbbb-0002".to_string(), "bob".to_string());
+        manager.add_npc("npc-00000001".to_string());
+
+        assert!(manager.is_owned_by("user-aaaa-0001", "user-aaaa-0001"));
+        assert!(!manager.is_owned_by("user-bbbb-0002", "user-aaaa-0001"));
+        assert!(!manager.is_owned_by("npc-00000001", "user-aaaa-0001"));
+        assert!(!manager.is_owned_by("missing", "missing"));
+    }
+
+    #[test]
+    fn test_entity_cap_refuses_npcs_but_evicts_stale_npc_for_player() {
+        let manager = EntityStateManager::new(60).with_max_entities(3);
+        for i in 0..3 {
+            assert!(manager.add_npc(format!("npc-0000000{}", i)).is_some());
+        }
+        assert!(manager.add_npc("npc-00000009".to_string()).is_none());
+        assert_eq!(manager.entity_count(), 3);
+
+        // Backdate the NPCs past the stale timeout
+        for i in 0..3 {
+            manager.with_entity_mut(&format!("npc-0000000{}", i), |npc| npc.last_seen = Instant::now() - Duration::from_secs(61));
+        }
+        assert!(manager.add_player("user-late-0001".to_string(), "late".to_string()).is_some());
+        assert_eq!(manager.entity_count(), 3);
+        assert_eq!(manager.player_count(), 1);
+
+        // The evicted NPC went through the normal removal and is handed out once for broadcast
+        let evicted = manager.take_evicted();
+        assert_eq!(evicted.len(), 1);
+        assert!(manager.is_tombstoned(&evicted[0]));
+        assert!(manager.take_evicted().is_empty());
+    }
+
+    #[test]
+    fn test_player_cap_ignores_npcs_and_known_players() {
+        let manager = EntityStateManager::new(120).with_max_players(1);
+        manager.add_npc("npc-00000001".to_string()).unwrap();
+        assert_eq!(manager.player_capacity_exceeded("user-first-0001"), None);
+
+        manager.add_player("user-first-0001".to_string(), "first".to_string()).unwrap();
+        assert_eq!(manager.player_capacity_exceeded("user-next-00001"), Some((1, 1)));
+        // An already tracked player (e.g. reconnecting) still fits
+        assert_eq!(manager.player_capacity_exceeded("user-first-0001"), None);
+    }
+
+    #[test]
+    fn test_entity_cap_keeps_live_npcs() {
+        let manager = EntityStateManager::new(120).with_max_entities(2);
+        manager.add_npc("npc-00000001".to_string());
+        manager.add_npc("npc-00000002".to_string());
+
+        assert!(manager.add_player("user-late-0001".to_string(), "late".to_string()).is_none());
+        // Re-adding an existing id is an update, not a new slot
+        assert!(manager.add_npc("npc-00000001".to_string()).is_some());
+    }
+
+    #[test]
+    fn test_npc_promoted_to_enemy_is_hostile() {
+        let manager = EntityStateManager::new(120);
+        manager.add_npc("npc-00000001".to_string());
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+        assert!(manager.get_hostile_entities().is_empty());
+
+        let msg = manager.set_entity_type("npc-00000001", EntityType::Enemy).unwrap();
+        assert!(matches!(msg, ServerMessage::EntityTypeChanged { entity_type: EntityType::Enemy, .. }));
+        let hostile = manager.get_hostile_entities();
+        assert_eq!(hostile.len(), 1);
+        assert_eq!(hostile[0].entity_id, "npc-00000001");
+
+        assert!(manager.set_entity_type("user-aaaa-0001", EntityType::Npc).is_err());
+        assert!(manager.set_entity_type("npc-00000001", EntityType::Player).is_err());
+        assert!(manager.set_entity_type("missing", EntityType::Boss).is_err());
+        assert_eq!(manager.get_entity("user-aaaa-0001").unwrap().entity_type, EntityType::Player);
+    }
+
+    #[test]
+    fn test_inventory_ops_rate_limited() {
+        let manager = EntityStateManager::new(120).with_inventory_rate_limit(3);
+        manager.add_player("user-duper-0001".to_string(), "duper".to_string());
+
+        assert!(manager.add_item("user-duper-0001", "gold".to_string(), 10).unwrap().0);
+        assert!(manager.remove_item("user-duper-0001", "gold", 4).unwrap().0);
+        assert!(manager.add_item("user-duper-0001", "gold".to_string(), 1).unwrap().0);
+
+        // Over the limit: rejected without touching the inventory
+        let (added, inventory) = manager.add_item("user-duper-0001", "gold".to_string(), 100).unwrap();
+        assert!(!added);
+        assert_eq!(inventory.get_item_quantity("gold"), 7);
+        let (removed, _) = manager.remove_item("user-duper-0001", "gold", 7).unwrap();
+        assert!(!removed);
+        assert_eq!(manager.get_inventory("user-duper-0001").unwrap().get_item_quantity("gold"), 7);
+    }
+
+    #[test]
+    fn test_reconnect_within_linger_keeps_entity() {
+        let manager = EntityStateManager::new(120).with_disconnect_linger(Duration::from_secs(30));
+        manager.add_player("user-blip-0001".to_string(), "blip".to_string());
+        manager.add_item("user-blip-0001", "wood".to_string(), 3);
+
+        assert!(manager.disconnect_player("user-blip-0001").is_none());
+        assert!(manager.is_disconnected("user-blip-0001"));
+        assert!(manager.expire_disconnected().is_empty());
+
+        assert!(manager.reconnect_player("user-blip-0001"));
+        assert!(!manager.is_disconnected("user-blip-0001"));
+        assert_eq!(manager.get_inventory("user-blip-0001").unwrap().get_item_quantity("wood"), 3);
+    }
+
+    #[test]
+    fn test_linger_timeout_removes_entity() {
+        let manager = EntityStateManager::new(120).with_disconnect_linger(Duration::from_secs(15));
+        manager.add_player("user-gone-0001".to_string(), "gone".to_string());
+        manager.disconnect_player("user-gone-0001");
+        assert_eq!(manager.player_count(), 1);
+        assert!(manager.expire_disconnected().is_empty());
+
+        // Backdate the disconnect past the linger window
+        manager.disconnected.insert("user-gone-0001".to_string(), Instant::now() - Duration::from_secs(16));
+        assert_eq!(manager.expire_disconnected(), vec!["user-gone-0001".to_string()]);
+        assert!(manager.get_entity("user-gone-0001").is_none());
+        assert!(!manager.reconnect_player("user-gone-0001"));
+
+        // Without a linger window players are removed on disconnect
+        let immediate = EntityStateManager::new(120);
+        immediate.add_player("user-quit-0001".to_string(), "quit".to_string());
+        assert!(immediate.disconnect_player("user-quit-0001").is_some());
+        assert!(!immediate.is_disconnected("user-quit-0001"));
+    }
+
+    #[test]
+    fn test_speed_violation_enforced_or_shadowed() {
+        use crate::game::anticheat::{AntiCheat, AntiCheatMode};
+
+        let anticheat = AntiCheat::new(AntiCheatMode::Enforce, 1.0);
+        let manager = EntityStateManager::new(120).with_max_move_speed(10.0).with_anticheat(anticheat.clone());
+        manager.add_player("user-speedy-0001".to_string(), "speedy".to_string());
+
+        let teleport = Position::new(500.0, 0.0, 500.0);
+        let result = manager.move_entity("user-speedy-0001", teleport, None).unwrap();
+        assert!(matches!(result, MoveResult::Rejected(_)));
+        assert_eq!(manager.get_entity("user-speedy-0001").unwrap().position, Position::default());
+
+        // Shadow mode: counted and logged, but the move goes through
+        anticheat.set_mode(AntiCheatMode::Shadow);
+        let result = manager.move_entity("user-speedy-0001", teleport, None).unwrap();
+        assert!(matches!(result, MoveResult::Moved(_)));
+        assert_eq!(manager.get_entity("user-speedy-0001").unwrap().position, teleport);
+        assert_eq!(anticheat.violations().speed, 2);
+    }
+
+    #[test]
+    fn test_speed_tolerance_widens_limit() {
+        use crate::game::anticheat::{AntiCheat, AntiCheatMode};
+
+        // 10 units/s over the 250ms minimum window allows 2.5 units, 5 with 2x tolerance
+        let strict = EntityStateManager::new(120).with_max_move_speed(10.0);
+        let lenient = EntityStateManager::new(120)
+            .with_max_move_speed(10.0)
+            .with_anticheat(AntiCheat::new(AntiCheatMode::Enforce, 2.0));
+        for manager in [&strict, &lenient] {
+            manager.add_player("user-laggy-0001".to_string(), "laggy".to_string());
+        }
+
+        let step = Position::new(4.0, 0.0, 0.0);
+        assert!(matches!(strict.move_entity("user-laggy-0001", step, None), Some(MoveResult::Rejected(_))));
+        assert!(matches!(lenient.move_entity("user-laggy-0001", step, None), Some(MoveResult::Moved(_))));
+    }
+
+    #[test]
+    fn test_new_players_spawn_at_configured_points() {
+        use crate::game::spawn::{SpawnManager, SpawnPoint, SpawnSelection};
+
+        let north = Position::new(0.0, 0.0, 100.0);
+        let south = Position::new(0.0, 0.0, -100.0);
+        let spawns = SpawnManager::new(vec![SpawnPoint::new(north), SpawnPoint::new(south)], SpawnSelection::RoundRobin);
+        let manager = EntityStateManager::new(120).with_spawns(spawns).with_respawn_cooldown(Duration::ZERO);
+
+        assert_eq!(manager.add_player("user-aaaa-0001".to_string(), "alice".to_string()).unwrap().position, north);
+        assert_eq!(manager.add_player("user-bbbb-0002".to_string(), "bob".to_string()).unwrap().position, south);
+
+        // Respawns draw from the same points
+        manager.update_health("user-aaaa-0001", 0.0);
+        let (respawned, _) = manager.respawn_player("user-aaaa-0001").unwrap();
+        assert_eq!(respawned.position, north);
+
+        // No configured points: origin
+        let unconfigured = EntityStateManager::new(120);
+        let player = unconfigured.add_player("user-cccc-0003".to_string(), "carol".to_string()).unwrap();
+        assert_eq!(player.position, Position::default());
+    }
+
+    #[test]
+    fn test_player_joined_broadcast_omits_private_fields() {
+        let manager = EntityStateManager::new(120);
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+        manager.add_item("user-aaaa-0001", "wood".to_string(), 5);
+        let player = manager.get_entity("user-aaaa-0001").unwrap();
+
+        let payload = serde_json::to_value(ServerMessage::PlayerJoined { player: player.public() }).unwrap();
+        let fields = payload["player"].as_object().unwrap();
+        assert_eq!(fields["display_name"], "alice");
+        for private in ["email", "inventory", "last_update"] {
+            assert!(!fields.contains_key(private), "broadcast leaked {private}: {payload}");
+        }
+    }
+
+    #[test]
+    fn test_item_metadata_length_limit() {
+        let within = "x".repeat(MAX_ITEM_METADATA_LEN);
+        let item = InventoryItem::with_metadata("sword".to_string(), 1, within.clone()).unwrap();
+        assert_eq!(item.metadata, Some(within));
+
+        let oversized = "x".repeat(MAX_ITEM_METADATA_LEN + 1);
+        assert!(InventoryItem::with_metadata("sword".to_string(), 1, oversized.clone()).is_err());
+
+        // Config-loaded items bypass with_metadata, so the starter kit is checked too
+        let bloated = InventoryItem { item_id: "cursed".to_string(), quantity: 1, metadata: Some(oversized) };
+        let manager = EntityStateManager::new(120).with_starter_items(vec![item, bloated]);
+        let player = manager.add_player("user-aaaa-0001".to_string(), "alice".to_string()).unwrap();
+        assert!(player.inventory.has_item("sword", 1));
+        assert!(!player.inventory.has_item("cursed", 1));
+    }
+
+    #[test]
+    fn test_health_regen_waits_out_combat_window() {
+        let manager = EntityStateManager::new(120).with_health_regen(10.0, Duration::from_secs(5));
+        manager.add_player("user-hurt-0001".to_string(), "hurt".to_string());
+        manager.update_health("user-hurt-0001", 50.0);
+
+        assert!(manager.regen_health(Duration::from_secs(1)).is_empty(), "no regen right after damage");
+        assert_eq!(manager.get_entity("user-hurt-0001").unwrap().health, 50.0);
+
+        // Backdate the damage past the combat window
+        manager.with_entity_mut("user-hurt-0001", |player| player.last_damaged_at = Some(Instant::now() - Duration::from_secs(6)));
+        let healed = manager.regen_health(Duration::from_secs(1));
+        assert_eq!(healed.len(), 1);
+        assert_eq!(healed[0].health, 60.0);
+
+        // Clamped at full health, and full-health players aren't reported again
+        manager.regen_health(Duration::from_secs(10));
+        assert_eq!(manager.get_entity("user-hurt-0001").unwrap().health, 100.0);
+        assert!(manager.regen_health(Duration::from_secs(1)).is_empty());
+    }
+}
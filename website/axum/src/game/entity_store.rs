@@ -0,0 +1,252 @@
+// src/game/entity_store.rs
+// Storage backend abstraction for entity state
+//
+// EntityStateManager keeps its game logic (caps, logging, inventory rules) and delegates
+// storage to an EntityStore. The in-process DashMap store is the default; a shared
+// backend (e.g. Redis) can implement the same trait to span multiple server instances.
+
+use dashmap::DashMap;
+
+use super::entity_state::{EntityState, EntityType, Position};
+
+/// Entity storage backend
+pub trait EntityStore: Send + Sync {
+    /// Current state of an entity
+    fn get(&self, entity_id: &str) -> Option<EntityState>;
+
+    /// Insert or replace an entity
+    fn insert(&self, entity: EntityState);
+
+    /// Mutate an entity in place (atomically for that entity)
+    /// Returns false if the entity doesn't exist
+    fn update(&self, entity_id: &str, f: &mut dyn FnMut(&mut EntityState)) -> bool;
+
+    /// Remove an entity, returning its last state
+    fn remove(&self, entity_id: &str) -> Option<EntityState>;
+
+    /// Snapshot of every entity
+    fn all(&self) -> Vec<EntityState>;
+
+    /// Up to `limit` entities starting at `offset`, ordered by entity id, plus the total count
+    /// Backends should override this to avoid materializing every entity
+    fn page(&self, offset: usize, limit: usize) -> (Vec<EntityState>, usize) {
+        let mut all = self.all();
+        all.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+        let total = all.len();
+        (all.into_iter().skip(offset).take(limit).collect(), total)
+    }
+
+    /// Entities within `radius` of a position
+    fn near(&self, position: &Position, radius: f32) -> Vec<EntityState> {
+        self.all()
+            .into_iter()
+            .filter(|entity| entity.position.distance_to(position) <= radius)
+            .collect()
+    }
+
+    fn contains(&self, entity_id: &str) -> bool {
+        self.get(entity_id).is_some()
+    }
+
+    /// Number of entities of one type
+    /// Backends should override this to count without materializing every entity
+    fn count_of_type(&self, entity_type: EntityType) -> usize {
+        self.all().iter().filter(|entity| entity.entity_type == entity_type).count()
+    }
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Single-process store backed by a DashMap
+#[derive(Default)]
+pub struct InMemoryEntityStore {
+    entities: DashMap<String, EntityState>,
+}
+
+impl InMemoryEntityStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl EntityStore for InMemoryEntityStore {
+    fn get(&self, entity_id: &str) -> Option<EntityState> {
+        self.entities.get(entity_id).map(|entity| entity.clone())
+    }
+
+    fn insert(&self, entity: EntityState) {
+        self.entities.insert(entity.entity_id.clone(), entity);
+    }
+
+    fn update(&self, entity_id: &str, f: &mut dyn FnMut(&mut EntityState)) -> bool {
+        match self.entities.get_mut(entity_id) {
+            Some(mut entity) => {
+                f(&mut entity);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove(&self, entity_id: &str) -> Option<EntityState> {
+        self.entities.remove(entity_id).map(|(_, entity)| entity)
+    }
+
+    fn all(&self) -> Vec<EntityState> {
+        self.entities.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    fn page(&self, offset: usize, limit: usize) -> (Vec<EntityState>, usize) {
+        // Sort ids only; just the requested page's entities are cloned
+        let mut ids: Vec<String> = self.entities.iter().map(|entry| entry.key().clone()).collect();
+        ids.sort_unstable();
+        let total = ids.len();
+        let page = ids
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .filter_map(|id| self.get(id))
+            .collect();
+        (page, total)
+    }
+
+    fn contains(&self, entity_id: &str) -> bool {
+        self.entities.contains_key(entity_id)
+    }
+
+    fn count_of_type(&self, entity_type: EntityType) -> usize {
+        self.entities.iter().filter(|entry| entry.entity_type == entity_type).count()
+    }
+
+    fn len(&self) -> usize {
+        self.entities.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::entity_state::{EntityStateManager, EntityType};
+    use std::sync::Arc;
+
+    fn store() -> Arc<dyn EntityStore> {
+        Arc::new(InMemoryEntityStore::new())
+    }
+
+    #[test]
+    fn test_store_get_update_remove() {
+        let store = store();
+        store.insert(EntityState::new_player("user-aaaa-0001".to_string(), "alice".to_string()));
+        store.insert(EntityState::new_npc("npc-00000001".to_string()));
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.count_of_type(EntityType::Player), 1);
+        assert_eq!(store.count_of_type(EntityType::Boss), 0);
+
+        assert!(store.update("user-aaaa-0001", &mut |e| e.position = Position::new(5.0, 0.0, 0.0)));
+        assert!(!store.update("missing", &mut |_| {}));
+        assert_eq!(store.get("user-aaaa-0001").unwrap().position, Position::new(5.0, 0.0, 0.0));
+
+        let near = store.near(&Position::new(0.0, 0.0, 0.0), 1.0);
+        assert_eq!(near.len(), 1);
+        assert_eq!(near[0].entity_type, EntityType::Npc);
+
+        assert!(store.remove("npc-00000001").is_some());
+        assert!(!store.contains("npc-00000001"));
+        assert_eq!(store.all().len(), 1);
+    }
+
+    #[test]
+    fn test_manager_parity_through_trait_object() {
+        let manager = EntityStateManager::new(120).with_store(store());
+        manager.add_player("user-aaaa-0001".to_string(), "alice".to_string());
+        manager.add_player("user-bbbb-0002".to_string(), "bob".to_string());
+        manager.add_npc("npc-00000001".to_string());
+
+        assert!(manager.is_owned_by("user-aaaa-0001", "user-aaaa-0001"));
+        assert!(!manager.is_owned_by("npc-00000001", "user-aaaa-0001"));
+        assert_eq!(manager.player_count(), 2);
+
+        assert!(manager.update_position("user-aaaa-0001", Position::new(1.0, 2.0, 3.0), None).is_some());
+        assert_eq!(manager.get_entity("user-aaaa-0001").unwrap().position, Position::new(1.0, 2.0, 3.0));
+
+        let (added, inventory) = manager.add_item("user-aaaa-0001", "wood".to_string(), 5).unwrap();
+        assert!(added);
+        assert_eq!(inventory.items[0].quantity, 5);
+        assert_eq!(manager.equip_tool("user-aaaa-0001", Some("wood".to_string())), Some(true));
+        assert_eq!(manager.get_equipped_tool("user-aaaa-0001").as_deref(), Some("wood"));
+
+        assert!(manager.remove_entity("user-bbbb-0002").is_some());
+        assert_eq!(manager.entity_count(), 2);
+    }
+
+    /// Backend whose update is an upsert (e.g. a write-through cache); a late update
+    /// for a removed id would recreate it without the manager's tombstones
+    struct UpsertStore(InMemoryEntityStore);
+
+    impl EntityStore for UpsertStore {
+        fn get(&self, entity_id: &str) -> Option<EntityState> {
+            self.0.get(entity_id)
+        }
+
+        fn insert(&self, entity: EntityState) {
+            self.0.insert(entity)
+        }
+
+        fn update(&self, entity_id: &str, f: &mut dyn FnMut(&mut EntityState)) -> bool {
+            let mut entity = self.0.get(entity_id)
+                .unwrap_or_else(|| EntityState::new_player(entity_id.to_string(), String::new()));
+            f(&mut entity);
+            self.0.insert(entity);
+            true
+        }
+
+        fn remove(&self, entity_id: &str) -> Option<EntityState> {
+            self.0.remove(entity_id)
+        }
+
+        fn all(&self) -> Vec<EntityState> {
+            self.0.all()
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    #[test]
+    fn test_stale_update_after_remove_does_not_resurrect() {
+        let manager = EntityStateManager::new(120).with_store(Arc::new(UpsertStore(InMemoryEntityStore::new())));
+        manager.add_player("user-gone-00001".to_string(), "gone".to_string());
+        assert!(manager.remove_entity("user-gone-00001").is_some());
+
+        assert!(manager.update_position("user-gone-00001", Position::new(9.0, 0.0, 9.0), None).is_none());
+        assert!(manager.get_entity("user-gone-00001").is_none());
+        assert_eq!(manager.entity_count(), 0);
+
+        // Rejoining lifts the tombstone
+        manager.add_player("user-gone-00001".to_string(), "gone".to_string());
+        assert!(manager.update_position("user-gone-00001", Position::new(9.0, 0.0, 9.0), None).is_some());
+
+        // Without tombstones the upserting backend brings the entity back
+        let unguarded = EntityStateManager::new(120)
+            .with_tombstone_ttl(std::time::Duration::ZERO)
+            .with_store(Arc::new(UpsertStore(InMemoryEntityStore::new())));
+        unguarded.add_player("user-gone-00001".to_string(), "gone".to_string());
+        unguarded.remove_entity("user-gone-00001");
+        unguarded.update_position("user-gone-00001", Position::new(9.0, 0.0, 9.0), None);
+        assert!(unguarded.get_entity("user-gone-00001").is_some());
+    }
+
+    #[test]
+    fn test_manager_entity_cap_through_trait_object() {
+        let manager = EntityStateManager::new(120).with_max_entities(2).with_store(store());
+        manager.add_npc("npc-00000001".to_string());
+        manager.add_npc("npc-00000002".to_string());
+        assert!(manager.add_player("user-late-0001".to_string(), "late".to_string()).is_none());
+        assert!(manager.add_npc("npc-00000001".to_string()).is_some());
+    }
+}
@@ -3,8 +3,10 @@
 // Trees, rocks, bushes, grass - all managed by server for true multiplayer sync
 
 use dashmap::DashMap;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{debug, info, warn, error};
@@ -17,7 +19,30 @@ use tokio::time;
 //   - Updated send_initial_objects(), update_player_chunks(), start_respawn_task() to use optimized methods
 //   - Reduces allocations in hot paths when handling thousands of objects
 
+use super::anticheat::{AntiCheat, Violation};
 use super::entity_state::Position;
+use super::environment_gen::{parse_object_id, Biome, EnvironmentGenerator};
+use super::events::{GameEvent, GameEventBus};
+use super::harvest_audit::{HarvestAuditEntry, HarvestAuditLog};
+use super::harvest_policy::{HarvestContext, HarvestPolicy, StandardHarvestPolicy};
+
+/// Largest radius `ChunkCoord::neighbors` will expand (65x65 = 4225 chunks)
+pub const MAX_NEIGHBOR_RADIUS: i32 = 32;
+
+/// Max chunks remembered per player for diff-based re-entry (bounds memory while exploring)
+const MAX_REMEMBERED_CHUNKS: usize = 256;
+
+/// Default reach for non-harvest interactions (examine, read a sign)
+pub const DEFAULT_INTERACTION_RANGE: f32 = 25.0;
+
+/// Default cap on objects taken by one "harvest all in range" request
+pub const DEFAULT_HARVEST_ALL_LIMIT: usize = 10;
+
+/// Inventory item consumed when planting
+pub const SEED_ITEM_ID: &str = "seed";
+const SAPLING_ASSET: &str = "Tree_Sapling_01";
+/// Minimum distance between a planted sapling and any existing object
+const MIN_PLANT_SPACING: f32 = 1.5;
 
 /// Helper function to get current Unix timestamp in seconds
 /// Returns 0 if system time is before UNIX_EPOCH (should never happen)
@@ -29,8 +54,13 @@ fn unix_time_secs() -> i64 {
         .as_secs() as i64
 }
 
+/// 64-bit FNV-1a hash; stable across runs and platforms, unlike `DefaultHasher`
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
 /// 3D scale vector
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Scale {
     pub x: f32,
     pub y: f32,
@@ -41,6 +71,11 @@ impl Scale {
     pub fn uniform(value: f32) -> Self {
         Self { x: value, y: value, z: value }
     }
+
+    /// All axes finite and strictly positive (clients can't render zero or mirrored scales)
+    pub fn is_valid(&self) -> bool {
+        [self.x, self.y, self.z].iter().all(|v| v.is_finite() && *v > 0.0)
+    }
 }
 
 impl Default for Scale {
@@ -50,7 +85,7 @@ impl Default for Scale {
 }
 
 /// Quaternion rotation
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Quaternion {
     pub x: f32,
     pub y: f32,
@@ -64,6 +99,20 @@ impl Default for Quaternion {
     }
 }
 
+/// Quaternions shorter than this can't be normalized meaningfully
+const MIN_QUATERNION_LENGTH: f32 = 1e-6;
+
+impl Quaternion {
+    /// Unit-length copy; falls back to identity for near-zero or non-finite input
+    pub fn normalized(&self) -> Self {
+        let length = (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt();
+        if !length.is_finite() || length < MIN_QUATERNION_LENGTH {
+            return Self::default();
+        }
+        Self { x: self.x / length, y: self.y / length, z: self.z / length, w: self.w / length }
+    }
+}
+
 /// Environment object types (must match Unity enum)
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "PascalCase")]
@@ -85,6 +134,35 @@ pub enum ResourceType {
     None = 4,
 }
 
+impl ResourceType {
+    /// Inventory item granted for a harvest of this resource (None yields nothing)
+    pub fn item_id(self) -> Option<&'static str> {
+        match self {
+            Self::Wood => Some("wood"),
+            Self::Stone => Some("stone"),
+            Self::Berries => Some("berries"),
+            Self::Herbs => Some("herbs"),
+            Self::None => None,
+        }
+    }
+}
+
+/// Why a mutating environment operation was refused
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum EnvironmentError {
+    #[error("object {0} already exists")]
+    DuplicateObject(String),
+    #[error("chunk ({}, {}) has not been generated", .0.x, .0.z)]
+    ChunkNotGenerated(ChunkCoord),
+    #[error("object {0} not found")]
+    ObjectNotFound(String),
+    /// Non-finite position (NaN or infinite coordinates)
+    #[error("object {0} is outside the world")]
+    OutOfBounds(String),
+    #[error("invalid scale for {id}: ({}, {}, {})", .scale.x, .scale.y, .scale.z)]
+    InvalidScale { id: String, scale: Scale },
+}
+
 /// Environment object in the game world
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -101,28 +179,57 @@ pub struct EnvironmentObject {
     pub is_harvested: bool,
     pub harvested_at: Option<i64>,     // Unix timestamp in seconds (i64 for Postgres BIGINT compatibility)
     pub respawn_time_seconds: Option<u32>, // e.g., 300 (5 minutes)
+    /// Completed harvests over the object's lifetime (seeds randomized yields)
+    #[serde(default)]
+    pub harvest_count: u32,
+    /// Solid footprint radius for movement validation (0 = walk-through)
+    #[serde(default)]
+    pub collision_radius: f32,
+    /// Client render hints (tint seed, wind sway); opaque to the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
 }
 
 impl EnvironmentObject {
     /// Check if this object should respawn
     pub fn should_respawn(&self) -> bool {
+        self.should_respawn_at(unix_time_secs(), 0)
+    }
+
+    /// Check if this object should respawn at `now`, delayed by its share of `jitter_secs`
+    /// The delay is derived from the object id, so it is stable but differs between objects
+    /// - `respawn_time_seconds: None` never respawns (e.g. planted saplings)
+    /// - `Some(0)` respawns instantly, ignoring jitter and the harvest timestamp
+    /// - a `harvested_at` in the future (clock skew) counts as zero elapsed time
+    pub fn should_respawn_at(&self, now: i64, jitter_secs: u32) -> bool {
         if !self.is_harvested {
             return false;
         }
 
-        if let (Some(harvested_at), Some(respawn_time)) = (self.harvested_at, self.respawn_time_seconds) {
-            let now = unix_time_secs();
-            let elapsed = now.saturating_sub(harvested_at);
-            elapsed >= respawn_time as i64
-        } else {
-            false
+        match (self.harvested_at, self.respawn_time_seconds) {
+            (_, None) => false,
+            (_, Some(0)) => true,
+            (Some(harvested_at), Some(respawn_time)) => {
+                let elapsed = now.saturating_sub(harvested_at).max(0);
+                elapsed >= respawn_time as i64 + self.respawn_jitter(jitter_secs)
+            }
+            (None, Some(_)) => false,
         }
     }
 
+    /// Per-object delay in [0, jitter_secs] (FNV-1a of the object id)
+    fn respawn_jitter(&self, jitter_secs: u32) -> i64 {
+        if jitter_secs == 0 {
+            return 0;
+        }
+        (fnv1a(&self.object_id) % (jitter_secs as u64 + 1)) as i64
+    }
+
     /// Mark as harvested
     pub fn mark_harvested(&mut self) {
         self.is_harvested = true;
         self.harvested_at = Some(unix_time_secs());
+        self.harvest_count += 1;
     }
 
     /// Respawn the object
@@ -143,12 +250,77 @@ impl EnvironmentObject {
             resource_type: self.resource_type,
             resource_amount: self.resource_amount,
             harvest_time: self.harvest_time,
+            collision_radius: self.collision_radius,
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+/// Stored object: mutable harvest state plus a shared, immutable network view
+/// The network view is built once on insert and handed out as `Arc` clones, so spawn,
+/// respawn and snapshot messages share it instead of cloning per recipient. Only harvest
+/// state (`is_harvested`, `harvested_at`) may change through `DerefMut`; anything that
+/// alters network fields must go through `add_object` to rebuild the view.
+#[derive(Debug)]
+struct StoredObject {
+    object: EnvironmentObject,
+    network: Arc<EnvironmentObjectData>,
+}
+
+impl StoredObject {
+    fn new(object: EnvironmentObject) -> Self {
+        let network = Arc::new(object.to_network_data());
+        Self { object, network }
+    }
+}
+
+impl std::ops::Deref for StoredObject {
+    type Target = EnvironmentObject;
+
+    fn deref(&self) -> &EnvironmentObject {
+        &self.object
+    }
+}
+
+impl std::ops::DerefMut for StoredObject {
+    fn deref_mut(&mut self) -> &mut EnvironmentObject {
+        &mut self.object
+    }
+}
+
+/// One player's snapshots of chunks they have left, capped at MAX_REMEMBERED_CHUNKS
+/// with the least recently left chunk forgotten first
+#[derive(Debug, Default)]
+struct ChunkMemory {
+    snapshots: HashMap<ChunkCoord, HashSet<String>>,
+    /// Remembered chunks, least recently left first
+    order: VecDeque<ChunkCoord>,
+}
+
+impl ChunkMemory {
+    /// Take the snapshot of a chunk being re-entered
+    fn take(&mut self, chunk: &ChunkCoord) -> Option<HashSet<String>> {
+        let snapshot = self.snapshots.remove(chunk)?;
+        self.order.retain(|remembered| remembered != chunk);
+        Some(snapshot)
+    }
+
+    /// Remember what was visible in a chunk the player just left
+    fn remember(&mut self, chunk: ChunkCoord, object_ids: HashSet<String>) {
+        if self.snapshots.insert(chunk, object_ids).is_some() {
+            self.order.retain(|remembered| *remembered != chunk);
+        }
+        self.order.push_back(chunk);
+        while self.order.len() > MAX_REMEMBERED_CHUNKS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.snapshots.remove(&oldest);
+            }
         }
     }
 }
 
 /// Network data for environment objects (sent to clients)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EnvironmentObjectData {
     pub object_id: String,
@@ -160,18 +332,34 @@ pub struct EnvironmentObjectData {
     pub resource_type: ResourceType,
     pub resource_amount: u32,
     pub harvest_time: f32,
+    #[serde(default)]
+    pub collision_radius: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
 }
 
 /// Network messages
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EnvironmentObjectsSpawnMessage {
-    pub objects: Vec<EnvironmentObjectData>,
+    pub objects: Vec<Arc<EnvironmentObjectData>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EnvironmentObjectsDespawnMessage {
+    /// Every despawned id, across all chunks
+    pub object_ids: Vec<String>,
+    /// The same ids grouped by chunk (only with `with_grouped_despawns`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<ChunkDespawn>,
+}
+
+/// Objects despawned from one chunk, so clients can unload the whole chunk at once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkDespawn {
+    pub chunk: ChunkCoord,
     pub object_ids: Vec<String>,
 }
 
@@ -182,6 +370,25 @@ pub struct HarvestObjectRequest {
     pub player_position: Position,
 }
 
+/// Machine-readable reason a harvest was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HarvestErrorCode {
+    NotFound,
+    AlreadyHarvested,
+    TooFar,
+    /// Reserved for per-player harvest cooldowns
+    OnCooldown,
+    /// The object type requires a tool (or a higher tier) the player hasn't equipped
+    MissingTool,
+    /// Inside the spawn protection zone
+    Protected,
+    /// The player has no room for the resource
+    InventoryFull,
+    /// The player is over the inventory operation rate limit
+    RateLimited,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HarvestObjectResponse {
@@ -192,16 +399,101 @@ pub struct HarvestObjectResponse {
     pub resource_amount: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
+    /// Set on failure so clients can branch without parsing `error_message`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<HarvestErrorCode>,
+    /// Client visual effect to play on a successful harvest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effect_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound_id: Option<String>,
+}
+
+impl HarvestObjectResponse {
+    pub fn failure(object_id: String, player_id: &str, error_code: HarvestErrorCode, error_message: String) -> Self {
+        Self {
+            success: false,
+            object_id,
+            player_id: player_id.to_string(),
+            resource_type: ResourceType::None,
+            resource_amount: 0,
+            error_message: Some(error_message),
+            error_code: Some(error_code),
+            effect_id: None,
+            sound_id: None,
+        }
+    }
+}
+
+/// Effect and sound a client plays when a resource is harvested
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HarvestEffect {
+    pub effect_id: String,
+    #[serde(default)]
+    pub sound_id: Option<String>,
+}
+
+impl HarvestEffect {
+    pub fn new(effect_id: &str, sound_id: &str) -> Self {
+        Self {
+            effect_id: effect_id.to_string(),
+            sound_id: Some(sound_id.to_string()),
+        }
+    }
+}
+
+/// Inclusive range a randomized harvest yield is drawn from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarvestYieldRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl HarvestYieldRange {
+    /// Yield for one harvest, reproducible for the same (seed, object, harvest number)
+    pub fn roll(&self, seed: u64, object_id: &str, harvest_count: u32) -> u32 {
+        let (low, high) = (self.min.min(self.max), self.min.max(self.max));
+        let mixed = (seed ^ fnv1a(object_id)).wrapping_add((harvest_count as u64).wrapping_mul(0x9E3779B97F4A7C15));
+        ChaCha8Rng::seed_from_u64(mixed).gen_range(low..=high)
+    }
+}
+
+/// Safe zone around spawn: no harvesting, and enemy AI should not aggro on players inside
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpawnProtection {
+    pub center: Position,
+    /// Horizontal (x/z) radius in meters
+    pub radius: f32,
+}
+
+impl SpawnProtection {
+    pub fn contains(&self, position: &Position) -> bool {
+        let dx = position.x - self.center.x;
+        let dz = position.z - self.center.z;
+        (dx * dx + dz * dz).sqrt() <= self.radius
+    }
+}
+
+/// Interaction a player can perform on an environment object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InteractAction {
+    /// Inspect an object without changing it
+    Examine,
+    /// Same as HarvestObject
+    Harvest,
+    /// Plant a sapling (consumes a seed)
+    Plant,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EnvironmentObjectRespawnMessage {
-    pub object_data: EnvironmentObjectData,
+    pub object_data: Arc<EnvironmentObjectData>,
 }
 
 /// Chunk coordinate
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ChunkCoord {
     pub x: i32,
     pub z: i32,
@@ -216,8 +508,12 @@ impl ChunkCoord {
     }
 
     /// Get neighboring chunks within radius
+    /// The radius is capped at MAX_NEIGHBOR_RADIUS so a misconfigured view distance can't
+    /// turn one join into a multi-million element allocation (negative radii yield nothing)
     pub fn neighbors(&self, radius: i32) -> Vec<ChunkCoord> {
-        let mut neighbors = Vec::new();
+        let radius = radius.min(MAX_NEIGHBOR_RADIUS);
+        let side = (2 * radius + 1).max(0) as usize;
+        let mut neighbors = Vec::with_capacity(side * side);
         for dx in -radius..=radius {
             for dz in -radius..=radius {
                 neighbors.push(ChunkCoord {
@@ -233,18 +529,60 @@ impl ChunkCoord {
 /// Environment manager - server-side authority for all environment objects
 pub struct EnvironmentManager {
     /// All objects in the world (object_id -> object)
-    objects: Arc<DashMap<String, EnvironmentObject>>,
+    objects: Arc<DashMap<String, StoredObject>>,
 
     /// Chunk to object IDs mapping
     chunk_objects: Arc<DashMap<ChunkCoord, Vec<String>>>,
 
+    /// Chunks already populated by the generator, with their biome
+    generated_chunks: Arc<DashMap<ChunkCoord, Biome>>,
+
     /// Player to visible chunks mapping
     player_chunks: Arc<DashMap<String, HashSet<ChunkCoord>>>,
 
+    /// Per-player view distance overrides (clamped to view_distance_chunks)
+    player_view_distance: Arc<DashMap<String, i32>>,
+
+    /// Per-player snapshot of visible object ids in chunks they have left
+    /// Used to send only changes when a chunk is re-entered
+    player_chunk_memory: Arc<DashMap<String, ChunkMemory>>,
+
     /// Configuration
     chunk_size: f32,
     view_distance_chunks: i32,
     max_harvest_range: f32,
+    /// Reach for non-harvest interactions (examine); usually larger than harvest range
+    interaction_range: f32,
+    chunk_diffing: bool,
+    /// Also send despawns grouped by chunk (the flat id list is always sent)
+    grouped_despawns: bool,
+    /// Targets below this height (client terrain y) are water and can't be planted on
+    water_level: Option<f32>,
+    spawn_protection: Option<SpawnProtection>,
+    respawn_check_interval: Duration,
+    respawn_jitter_secs: u32,
+    /// Harvest effect per resource type (absent types send no effect)
+    harvest_effects: HashMap<ResourceType, HarvestEffect>,
+    /// Per object type harvest range overrides (absent types use max_harvest_range)
+    harvest_ranges: HashMap<EnvironmentObjectType, f32>,
+    /// Per object type radius (in chunks) around the object's chunk that harvest/respawn
+    /// broadcasts reach (absent types: only players watching the object's chunk)
+    broadcast_radii: HashMap<EnvironmentObjectType, i32>,
+    /// Player radius for collision checks against solid objects (None = disabled)
+    collision_player_radius: Option<f32>,
+    /// Game-mode rules consulted before a harvest is granted
+    harvest_policy: Arc<dyn HarvestPolicy>,
+    /// Most objects one "harvest all in range" request may take (0 = action disabled)
+    harvest_all_limit: usize,
+    /// Randomized yields per resource type (absent types yield `resource_amount`)
+    harvest_yields: HashMap<ResourceType, HarvestYieldRange>,
+    harvest_yield_seed: u64,
+    /// Observers of successful harvests (quests, achievements)
+    events: GameEventBus,
+    /// Every harvest attempt, granted or rejected (anti-cheat forensics)
+    harvest_audit: HarvestAuditLog,
+    /// Enforce or shadow harvest range violations, with a tolerance on the range
+    anticheat: AntiCheat,
 }
 
 impl EnvironmentManager {
@@ -252,26 +590,299 @@ impl EnvironmentManager {
         Self {
             objects: Arc::new(DashMap::new()),
             chunk_objects: Arc::new(DashMap::new()),
+            generated_chunks: Arc::new(DashMap::new()),
             player_chunks: Arc::new(DashMap::new()),
+            player_view_distance: Arc::new(DashMap::new()),
+            player_chunk_memory: Arc::new(DashMap::new()),
             chunk_size,
             view_distance_chunks,
             max_harvest_range,
+            interaction_range: DEFAULT_INTERACTION_RANGE,
+            chunk_diffing: false,
+            grouped_despawns: false,
+            water_level: None,
+            spawn_protection: None,
+            respawn_check_interval: Duration::from_secs(10),
+            respawn_jitter_secs: 0,
+            harvest_effects: HashMap::new(),
+            harvest_ranges: HashMap::new(),
+            broadcast_radii: HashMap::new(),
+            collision_player_radius: None,
+            harvest_policy: Arc::new(StandardHarvestPolicy::default()),
+            harvest_all_limit: DEFAULT_HARVEST_ALL_LIMIT,
+            harvest_yields: HashMap::new(),
+            harvest_yield_seed: 0,
+            events: GameEventBus::default(),
+            harvest_audit: HarvestAuditLog::default(),
+            anticheat: AntiCheat::default(),
+        }
+    }
+
+    /// Empty manager with the same settings, for replaying recorded sessions
+    /// Objects, players, events and anti-cheat counters start fresh so nothing leaks
+    /// into the live world
+    pub fn empty_copy(&self) -> Self {
+        Self {
+            interaction_range: self.interaction_range,
+            chunk_diffing: self.chunk_diffing,
+            grouped_despawns: self.grouped_despawns,
+            water_level: self.water_level,
+            spawn_protection: self.spawn_protection,
+            respawn_check_interval: self.respawn_check_interval,
+            respawn_jitter_secs: self.respawn_jitter_secs,
+            harvest_effects: self.harvest_effects.clone(),
+            harvest_ranges: self.harvest_ranges.clone(),
+            broadcast_radii: self.broadcast_radii.clone(),
+            collision_player_radius: self.collision_player_radius,
+            harvest_policy: self.harvest_policy.clone(),
+            harvest_all_limit: self.harvest_all_limit,
+            harvest_yields: self.harvest_yields.clone(),
+            harvest_yield_seed: self.harvest_yield_seed,
+            anticheat: AntiCheat::new(self.anticheat.mode(), self.anticheat.tolerance()),
+            ..Self::new(self.chunk_size, self.view_distance_chunks, self.max_harvest_range)
         }
     }
 
+    /// Enable diff-based chunk re-entry
+    /// Clients must keep (hidden) objects of chunks they leave; re-entering a chunk then only
+    /// sends objects that respawned and despawns objects harvested while the player was away
+    pub fn with_chunk_diffing(mut self, enabled: bool) -> Self {
+        self.chunk_diffing = enabled;
+        self
+    }
+
+    /// Group despawn ids by chunk as well, so clients can drop whole chunks at once
+    /// The flat `object_ids` list stays for clients that don't read the groups
+    pub fn with_grouped_despawns(mut self, enabled: bool) -> Self {
+        self.grouped_despawns = enabled;
+        self
+    }
+
+    /// Reject planting below this height (positions carry the client's terrain height)
+    pub fn with_water_level(mut self, water_level: f32) -> Self {
+        self.water_level = Some(water_level);
+        self
+    }
+
+    /// Configure the respawn task: how often it checks, and up to how many seconds each
+    /// object's respawn is spread out so due objects don't all respawn on the same check
+    pub fn with_respawn_timing(mut self, check_interval: Duration, jitter_secs: u32) -> Self {
+        self.respawn_check_interval = check_interval;
+        self.respawn_jitter_secs = jitter_secs;
+        self
+    }
+
+    /// Effects returned with successful harvests, keyed by resource type
+    pub fn with_harvest_effects(mut self, effects: HashMap<ResourceType, HarvestEffect>) -> Self {
+        self.harvest_effects = effects;
+        self
+    }
+
+    /// Harvest range overrides per object type (e.g. grass only up close)
+    pub fn with_harvest_ranges(mut self, ranges: HashMap<EnvironmentObjectType, f32>) -> Self {
+        self.harvest_ranges = ranges;
+        self
+    }
+
+    /// Broadcast radius overrides per object type, in chunks (e.g. tall trees seen from afar)
+    pub fn with_broadcast_radii(mut self, radii: HashMap<EnvironmentObjectType, i32>) -> Self {
+        self.broadcast_radii = radii;
+        self
+    }
+
+    /// Roll each harvest's base yield from a per-resource range instead of the fixed
+    /// `resource_amount`, seeded by (seed, object id, harvest count)
+    pub fn with_harvest_yields(mut self, yields: HashMap<ResourceType, HarvestYieldRange>, seed: u64) -> Self {
+        self.harvest_yields = yields;
+        self.harvest_yield_seed = seed;
+        self
+    }
+
+    /// Publish `GameEvent`s to a shared bus (default: a private bus nobody listens to)
+    pub fn with_event_bus(mut self, events: GameEventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Keep the last `capacity` harvest attempts (default: DEFAULT_HARVEST_AUDIT_CAPACITY)
+    pub fn with_harvest_audit_capacity(mut self, capacity: usize) -> Self {
+        self.harvest_audit = HarvestAuditLog::new(capacity);
+        self
+    }
+
+    /// Share an anti-cheat handle (shadow mode, tolerance) with other managers
+    pub fn with_anticheat(mut self, anticheat: AntiCheat) -> Self {
+        self.anticheat = anticheat;
+        self
+    }
+
+    /// Audit trail of recent harvest attempts
+    pub fn harvest_audit(&self) -> &HarvestAuditLog {
+        &self.harvest_audit
+    }
+
+    /// Replace the harvest rules (default: StandardHarvestPolicy)
+    pub fn with_harvest_policy(mut self, policy: Arc<dyn HarvestPolicy>) -> Self {
+        self.harvest_policy = policy;
+        self
+    }
+
+    /// Reject moves that end inside a solid object (opt-in: costs a chunk scan per move)
+    pub fn with_collision_checks(mut self, player_radius: f32) -> Self {
+        self.collision_player_radius = Some(player_radius);
+        self
+    }
+
+    /// Solid, unharvested object in the position's chunk that a player standing there would overlap
+    /// Always None unless collision checks are enabled
+    pub fn blocking_object_at(&self, position: &Position) -> Option<String> {
+        let player_radius = self.collision_player_radius?;
+        let chunk = self.chunk_for_position(position);
+        let ids = self.chunk_objects.get(&chunk)?;
+        ids.iter()
+            .find(|id| {
+                self.objects.get(*id).is_some_and(|obj| {
+                    let (dx, dz) = (obj.position.x - position.x, obj.position.z - position.z);
+                    let reach = obj.collision_radius + player_radius;
+                    !obj.is_harvested && obj.collision_radius > 0.0 && dx * dx + dz * dz < reach * reach
+                })
+            })
+            .cloned()
+    }
+
+    /// Harvest range for an object type (override or the global max)
+    pub fn harvest_range_for(&self, object_type: EnvironmentObjectType) -> f32 {
+        self.harvest_ranges.get(&object_type).copied().unwrap_or(self.max_harvest_range)
+    }
+
+    /// Reach for examine and other non-harvest interactions
+    pub fn with_interaction_range(mut self, range: f32) -> Self {
+        self.interaction_range = range;
+        self
+    }
+
+    /// Cap objects per "harvest all in range" request (0 disables the action)
+    pub fn with_harvest_all_limit(mut self, limit: usize) -> Self {
+        self.harvest_all_limit = limit;
+        self
+    }
+
+    /// Whether "harvest all in range" requests are accepted
+    pub fn harvest_all_enabled(&self) -> bool {
+        self.harvest_all_limit > 0
+    }
+
+    /// Broadcast radius in chunks for an object type (0 = the object's chunk only)
+    pub fn broadcast_radius_for(&self, object_type: EnvironmentObjectType) -> i32 {
+        self.broadcast_radii.get(&object_type).copied().unwrap_or(0).max(0)
+    }
+
+    /// Disable harvesting within a radius of the spawn point
+    pub fn with_spawn_protection(mut self, protection: SpawnProtection) -> Self {
+        self.spawn_protection = Some(protection);
+        self
+    }
+
+    /// Whether a position is inside the spawn protection zone
+    /// (enemy AI should use this to skip aggro on protected players)
+    pub fn is_spawn_protected(&self, position: &Position) -> bool {
+        self.spawn_protection.is_some_and(|p| p.contains(position))
+    }
+
     /// Add an object to the world
-    pub fn add_object(&self, object: EnvironmentObject) {
+    /// Rejects duplicate ids, non-finite positions and non-positive scales; rotations are
+    /// normalized to unit length
+    pub fn add_object(&self, object: EnvironmentObject) -> Result<(), EnvironmentError> {
+        let object = Self::validated(object)?;
         let chunk = ChunkCoord::from_position(&object.position, self.chunk_size);
         let object_id = object.object_id.clone();
 
-        // Add to objects map
-        self.objects.insert(object_id.clone(), object);
+        // Add to objects map (never overwrite: the old object would linger in its chunk list)
+        match self.objects.entry(object_id.clone()) {
+            dashmap::mapref::entry::Entry::Occupied(_) => return Err(EnvironmentError::DuplicateObject(object_id)),
+            dashmap::mapref::entry::Entry::Vacant(slot) => {
+                slot.insert(StoredObject::new(object));
+            }
+        }
 
         // Add to chunk mapping
         self.chunk_objects
             .entry(chunk)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(object_id);
+
+        Ok(())
+    }
+
+    /// Add many objects at once (world loading, chunk generation)
+    /// Same validation as `add_object`, but chunk mappings are grouped so each chunk's entry is
+    /// locked once instead of once per object. Rejected objects are skipped with a warning.
+    /// Returns the number of objects added
+    pub fn add_objects(&self, objects: Vec<EnvironmentObject>) -> usize {
+        let mut by_chunk: HashMap<ChunkCoord, Vec<String>> = HashMap::new();
+        let mut count = 0;
+        for object in objects {
+            let object = match Self::validated(object) {
+                Ok(object) => object,
+                Err(e) => {
+                    warn!("Skipping object: {}", e);
+                    continue;
+                }
+            };
+            let chunk = ChunkCoord::from_position(&object.position, self.chunk_size);
+            let object_id = object.object_id.clone();
+            match self.objects.entry(object_id.clone()) {
+                dashmap::mapref::entry::Entry::Occupied(_) => {
+                    warn!("Skipping object: {}", EnvironmentError::DuplicateObject(object_id));
+                    continue;
+                }
+                dashmap::mapref::entry::Entry::Vacant(slot) => {
+                    slot.insert(StoredObject::new(object));
+                }
+            }
+            by_chunk.entry(chunk).or_default().push(object_id);
+            count += 1;
+        }
+        for (chunk, ids) in by_chunk {
+            self.chunk_objects.entry(chunk).or_default().extend(ids);
+        }
+        count
+    }
+
+    /// Reject degenerate scales and positions, and normalize rotation before an object is stored
+    fn validated(mut object: EnvironmentObject) -> Result<EnvironmentObject, EnvironmentError> {
+        let Position { x, y, z } = object.position;
+        if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+            return Err(EnvironmentError::OutOfBounds(object.object_id));
+        }
+        if !object.scale.is_valid() {
+            return Err(EnvironmentError::InvalidScale { id: object.object_id, scale: object.scale });
+        }
+        object.rotation = object.rotation.normalized();
+        Ok(object)
+    }
+
+    /// Populate a chunk from the generator unless it was already generated
+    /// Returns the number of objects added (0 if the chunk was already claimed)
+    pub fn ensure_chunk_generated(&self, generator: &EnvironmentGenerator, chunk: &ChunkCoord) -> usize {
+        // Claim first so concurrent callers never generate the same chunk twice
+        match self.generated_chunks.entry(*chunk) {
+            dashmap::mapref::entry::Entry::Occupied(_) => return 0,
+            dashmap::mapref::entry::Entry::Vacant(slot) => {
+                slot.insert(generator.classify_biome(chunk));
+            }
+        }
+        self.add_objects(generator.generate_chunk(chunk))
+    }
+
+    /// Whether a chunk has been populated by the generator
+    pub fn is_chunk_generated(&self, chunk: &ChunkCoord) -> bool {
+        self.generated_chunks.contains_key(chunk)
+    }
+
+    /// Biome of a generated chunk (None until the generator has populated it)
+    pub fn chunk_biome(&self, chunk: &ChunkCoord) -> Option<Biome> {
+        self.generated_chunks.get(chunk).map(|biome| *biome)
     }
 
     /// Get objects in specific chunks
@@ -283,7 +894,7 @@ impl EnvironmentManager {
                 for object_id in object_ids.iter() {
                     if let Some(object) = self.objects.get(object_id) {
                         if !object.is_harvested {
-                            objects.push(object.clone());
+                            objects.push(object.object.clone());
                         }
                     }
                 }
@@ -293,9 +904,9 @@ impl EnvironmentManager {
         objects
     }
 
-    /// Get objects in specific chunks as network data (optimized - avoids intermediate clone)
+    /// Get objects in specific chunks as shared network data (no per-call object clones)
     /// This is more efficient than get_objects_in_chunks() followed by to_network_data()
-    pub fn get_objects_in_chunks_network(&self, chunks: &[ChunkCoord]) -> Vec<EnvironmentObjectData> {
+    pub fn get_objects_in_chunks_network(&self, chunks: &[ChunkCoord]) -> Vec<Arc<EnvironmentObjectData>> {
         let mut result = Vec::new();
 
         for chunk in chunks {
@@ -303,7 +914,32 @@ impl EnvironmentManager {
                 for object_id in object_ids.iter() {
                     if let Some(object) = self.objects.get(object_id) {
                         if !object.is_harvested {
-                            result.push(object.to_network_data());
+                            result.push(object.network.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Ids of un-harvested objects within harvest range of a player (horizontal distance, per-type ranges)
+    /// Lets clients highlight what they can harvest from where they stand
+    pub fn get_harvestable_near(&self, player_position: &Position) -> Vec<String> {
+        let widest_range = self.harvest_ranges.values().copied().fold(self.max_harvest_range, f32::max);
+        let chunk_radius = (widest_range / self.chunk_size).ceil() as i32;
+        let chunks = ChunkCoord::from_position(player_position, self.chunk_size).neighbors(chunk_radius);
+
+        let mut result = Vec::new();
+        for chunk in &chunks {
+            if let Some(object_ids) = self.chunk_objects.get(chunk) {
+                for object_id in object_ids.iter() {
+                    if let Some(object) = self.objects.get(object_id) {
+                        let dx = object.position.x - player_position.x;
+                        let dz = object.position.z - player_position.z;
+                        if !object.is_harvested && (dx * dx + dz * dz).sqrt() <= self.harvest_range_for(object.object_type) {
+                            result.push(object_id.clone());
                         }
                     }
                 }
@@ -319,9 +955,32 @@ impl EnvironmentManager {
         center_chunk.neighbors(self.view_distance_chunks)
     }
 
+    /// Set a player's requested view distance (e.g. smaller for mobile clients)
+    /// Clamped to [1, view_distance_chunks]; returns the effective value
+    pub fn set_player_view_distance(&self, player_id: &str, requested: i32) -> i32 {
+        let effective = requested.clamp(1, self.view_distance_chunks.max(1));
+        self.player_view_distance.insert(player_id.to_string(), effective);
+        debug!("Player {} view distance set to {} (requested {})", player_id, effective, requested);
+        effective
+    }
+
+    /// Get a player's effective view distance (server default if not set)
+    pub fn get_player_view_distance(&self, player_id: &str) -> i32 {
+        self.player_view_distance
+            .get(player_id)
+            .map(|d| *d)
+            .unwrap_or(self.view_distance_chunks)
+    }
+
+    /// Get nearby chunks for a position using the player's view distance
+    pub fn get_nearby_chunks_for_player(&self, player_id: &str, position: &Position) -> Vec<ChunkCoord> {
+        let center_chunk = ChunkCoord::from_position(position, self.chunk_size);
+        center_chunk.neighbors(self.get_player_view_distance(player_id))
+    }
+
     /// Send initial objects to a player when they join
     pub fn send_initial_objects(&self, player_id: &str, player_position: &Position) -> EnvironmentObjectsSpawnMessage {
-        let chunks = self.get_nearby_chunks(player_position);
+        let chunks = self.get_nearby_chunks_for_player(player_id, player_position);
         let objects = self.get_objects_in_chunks_network(&chunks);
 
         // Store player's visible chunks
@@ -335,9 +994,14 @@ impl EnvironmentManager {
         EnvironmentObjectsSpawnMessage { objects }
     }
 
+    /// Whether a player's visible chunks are being tracked
+    pub fn is_tracking_player(&self, player_id: &str) -> bool {
+        self.player_chunks.contains_key(player_id)
+    }
+
     /// Update player's visible chunks (call when player moves)
     pub fn update_player_chunks(&self, player_id: &str, new_position: &Position) -> (Option<EnvironmentObjectsSpawnMessage>, Option<EnvironmentObjectsDespawnMessage>) {
-        let new_chunks: HashSet<ChunkCoord> = self.get_nearby_chunks(new_position).into_iter().collect();
+        let new_chunks: HashSet<ChunkCoord> = self.get_nearby_chunks_for_player(player_id, new_position).into_iter().collect();
 
         let old_chunks = self.player_chunks
             .get(player_id)
@@ -350,21 +1014,56 @@ impl EnvironmentManager {
         // Update stored chunks
         self.player_chunks.insert(player_id.to_string(), new_chunks);
 
-        let spawn_msg = if !enter_chunks.is_empty() {
-            let objects = self.get_objects_in_chunks_network(&enter_chunks);
-            Some(EnvironmentObjectsSpawnMessage { objects })
+        let mut spawn_objects = Vec::new();
+        let mut despawn_groups = Vec::new();
+
+        if self.chunk_diffing {
+            let mut memory = self.player_chunk_memory
+                .entry(player_id.to_string())
+                .or_default();
+
+            for chunk in &enter_chunks {
+                match memory.take(chunk) {
+                    Some(remembered) => {
+                        // Re-entry: only send what changed since the player left
+                        let current = self.visible_object_ids(chunk);
+                        for object_id in current.difference(&remembered) {
+                            if let Some(object) = self.objects.get(object_id) {
+                                spawn_objects.push(object.network.clone());
+                            }
+                        }
+                        let removed: Vec<String> = remembered.difference(&current).cloned().collect();
+                        if !removed.is_empty() {
+                            despawn_groups.push(ChunkDespawn { chunk: *chunk, object_ids: removed });
+                        }
+                    }
+                    None => spawn_objects.extend(self.get_objects_in_chunks_network(&[*chunk])),
+                }
+            }
+
+            for chunk in &exit_chunks {
+                memory.remember(*chunk, self.visible_object_ids(chunk));
+            }
+        } else {
+            spawn_objects = self.get_objects_in_chunks_network(&enter_chunks);
+        }
+
+        for chunk in &exit_chunks {
+            if let Some(ids) = self.chunk_objects.get(chunk).filter(|ids| !ids.is_empty()) {
+                despawn_groups.push(ChunkDespawn { chunk: *chunk, object_ids: ids.iter().cloned().collect() });
+            }
+        }
+
+        let spawn_msg = if !spawn_objects.is_empty() {
+            Some(EnvironmentObjectsSpawnMessage { objects: spawn_objects })
         } else {
             None
         };
 
-        let despawn_msg = if !exit_chunks.is_empty() {
-            let mut object_ids = Vec::new();
-            for chunk in exit_chunks {
-                if let Some(ids) = self.chunk_objects.get(&chunk) {
-                    object_ids.extend(ids.iter().cloned());
-                }
-            }
-            Some(EnvironmentObjectsDespawnMessage { object_ids })
+        let despawn_msg = if !despawn_groups.is_empty() {
+            let object_ids = despawn_groups.iter().flat_map(|group| group.object_ids.iter().cloned()).collect();
+            let chunks = if self.grouped_despawns { despawn_groups } else { Vec::new() };
+            Some(EnvironmentObjectsDespawnMessage { object_ids, chunks })
         } else {
             None
         };
@@ -372,58 +1071,92 @@ impl EnvironmentManager {
         (spawn_msg, despawn_msg)
     }
 
+    /// IDs of un-harvested objects currently in a chunk
+    fn visible_object_ids(&self, chunk: &ChunkCoord) -> HashSet<String> {
+        self.chunk_objects
+            .get(chunk)
+            .map(|ids| {
+                ids.iter()
+                    .filter(|id| self.objects.get(*id).map(|o| !o.is_harvested).unwrap_or(false))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Handle harvest request from player
-    pub fn handle_harvest_request(&self, player_id: &str, request: HarvestObjectRequest) -> HarvestObjectResponse {
+    /// `tool` is the player's equipped tool item id; its tier scales the yield
+    /// Every attempt is recorded in the harvest audit log
+    pub fn handle_harvest_request(&self, player_id: &str, request: HarvestObjectRequest, tool: Option<&str>) -> HarvestObjectResponse {
+        let player_position = request.player_position;
+        let response = self.resolve_harvest(player_id, request, tool);
+        let distance = self.objects.get(&response.object_id).map(|o| o.position.distance_to(&player_position));
+        self.harvest_audit.record(HarvestAuditEntry::from_response(&response, distance));
+        response
+    }
+
+    fn resolve_harvest(&self, player_id: &str, request: HarvestObjectRequest, tool: Option<&str>) -> HarvestObjectResponse {
         // Get object
         let mut object = match self.objects.get_mut(&request.object_id) {
             Some(obj) => obj,
             None => {
-                return HarvestObjectResponse {
-                    success: false,
-                    object_id: request.object_id,
-                    player_id: player_id.to_string(),
-                    resource_type: ResourceType::None,
-                    resource_amount: 0,
-                    error_message: Some("Object not found".to_string()),
-                };
+                return HarvestObjectResponse::failure(request.object_id, player_id, HarvestErrorCode::NotFound, "Object not found".to_string());
             }
         };
 
         // Check if already harvested
         if object.is_harvested {
-            return HarvestObjectResponse {
-                success: false,
-                object_id: request.object_id,
-                player_id: player_id.to_string(),
-                resource_type: ResourceType::None,
-                resource_amount: 0,
-                error_message: Some("Already harvested".to_string()),
-            };
+            return HarvestObjectResponse::failure(request.object_id, player_id, HarvestErrorCode::AlreadyHarvested, "Already harvested".to_string());
         }
 
-        // Validate range (anti-cheat)
-        let distance = object.position.distance_to(&request.player_position);
-        if distance > self.max_harvest_range {
-            warn!("Player {} attempted to harvest from too far: {} > {}",
-                  player_id, distance, self.max_harvest_range);
-            return HarvestObjectResponse {
-                success: false,
-                object_id: request.object_id,
-                player_id: player_id.to_string(),
-                resource_type: ResourceType::None,
-                resource_amount: 0,
-                error_message: Some(format!("Too far: {:.1}m > {:.1}m", distance, self.max_harvest_range)),
-            };
+        // Game-mode rules (spawn protection and range by default)
+        let context = HarvestContext {
+            player_position: &request.player_position,
+            tool,
+            max_range: self.harvest_range_for(object.object_type) * self.anticheat.tolerance(),
+            spawn_protection: self.spawn_protection,
+        };
+        if let Err(code) = self.harvest_policy.validate(player_id, &object, &context) {
+            // Range is anti-cheat (may be shadowed); other codes are game rules and always apply
+            let enforced = code != HarvestErrorCode::TooFar || self.anticheat.report(Violation::HarvestRange, player_id);
+            if enforced {
+                let message = match code {
+                    HarvestErrorCode::Protected => format!(
+                        "Spawn protection: harvesting disabled within {:.0}m of spawn",
+                        self.spawn_protection.map_or(0.0, |p| p.radius)
+                    ),
+                    HarvestErrorCode::TooFar => format!(
+                        "Too far: {:.1}m > {:.1}m",
+                        object.position.distance_to(&request.player_position),
+                        context.max_range
+                    ),
+                    other => format!("Harvest not allowed ({other:?})"),
+                };
+                return HarvestObjectResponse::failure(request.object_id, player_id, code, message);
+            }
         }
 
         // SUCCESS: Mark as harvested
         let resource_type = object.resource_type;
-        let resource_amount = object.resource_amount;
+        let multiplier = super::tools::yield_multiplier(tool, object.object_type);
+        let base_amount = match self.harvest_yields.get(&resource_type) {
+            Some(range) => range.roll(self.harvest_yield_seed, &object.object_id, object.harvest_count),
+            None => object.resource_amount,
+        };
+        let resource_amount = (base_amount as f32 * multiplier).round() as u32;
         object.mark_harvested();
+        drop(object);
 
-        info!("Player {} harvested {} for {}x {:?}",
-              player_id, request.object_id, resource_amount, resource_type);
+        info!("Player {} harvested {} for {}x {:?} (tool: {:?})",
+              player_id, request.object_id, resource_amount, resource_type, tool);
+        self.events.publish(GameEvent::Harvested {
+            player_id: player_id.to_string(),
+            object_id: request.object_id.clone(),
+            resource_type,
+            amount: resource_amount,
+        });
 
+        let effect = self.harvest_effects.get(&resource_type);
         HarvestObjectResponse {
             success: true,
             object_id: request.object_id,
@@ -431,7 +1164,156 @@ impl EnvironmentManager {
             resource_type,
             resource_amount,
             error_message: None,
+            error_code: None,
+            effect_id: effect.map(|e| e.effect_id.clone()),
+            sound_id: effect.and_then(|e| e.sound_id.clone()),
+        }
+    }
+
+    /// Harvest every eligible object around the player, nearest first, up to the harvest-all limit
+    /// Objects that are harvested, out of range or refused by the harvest policy (tool,
+    /// cooldown, spawn protection) are skipped rather than reported; only successes are returned.
+    /// `admit` is asked about each candidate's resource in harvest order before anything is
+    /// harvested, so callers can stop at what the player can actually receive.
+    pub fn harvest_all_in_range(
+        &self,
+        player_id: &str,
+        player_position: &Position,
+        tool: Option<&str>,
+        mut admit: impl FnMut(ResourceType) -> bool,
+    ) -> Vec<HarvestObjectResponse> {
+        let reach = self.harvest_ranges.values().copied().fold(self.max_harvest_range, f32::max);
+        let radius = (reach / self.chunk_size).ceil() as i32;
+        let chunk = self.chunk_for_position(player_position);
+
+        let mut candidates: Vec<(f32, String, ResourceType)> = Vec::new();
+        for neighbor in chunk.neighbors(radius) {
+            let Some(ids) = self.chunk_objects.get(&neighbor) else {
+                continue;
+            };
+            for id in ids.iter() {
+                let Some(object) = self.objects.get(id) else {
+                    continue;
+                };
+                let distance = object.position.distance_to(player_position);
+                let max_range = self.harvest_range_for(object.object_type);
+                if object.is_harvested || distance > max_range {
+                    continue;
+                }
+                let context = HarvestContext {
+                    player_position,
+                    tool,
+                    max_range,
+                    spawn_protection: self.spawn_protection,
+                };
+                if self.harvest_policy.validate(player_id, &object, &context).is_ok() {
+                    candidates.push((distance, id.clone(), object.resource_type));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let admitted: Vec<String> = candidates
+            .into_iter()
+            .filter(|(_, _, resource_type)| admit(*resource_type))
+            .take(self.harvest_all_limit)
+            .map(|(_, object_id, _)| object_id)
+            .collect();
+
+        admitted
+            .into_iter()
+            .map(|object_id| {
+                let request = HarvestObjectRequest { object_id, player_position: *player_position };
+                self.handle_harvest_request(player_id, request, tool)
+            })
+            .filter(|response| response.success)
+            .collect()
+    }
+
+    /// Get a copy of an object by id (including harvested objects)
+    pub fn get_object(&self, object_id: &str) -> Option<EnvironmentObject> {
+        self.objects.get(object_id).map(|o| o.object.clone())
+    }
+
+    /// Examine an object from up to the interaction range (including harvested objects)
+    /// Read-only, so an out-of-range request is refused without an anti-cheat report
+    pub fn examine_object(&self, player_id: &str, object_id: &str, player_position: &Position) -> Result<EnvironmentObject, String> {
+        let object = self.get_object(object_id).ok_or_else(|| "Object not found".to_string())?;
+        let distance = object.position.distance_to(player_position);
+        let max_range = self.interaction_range * self.anticheat.tolerance();
+        if distance > max_range {
+            debug!("Player {} attempted to examine {} from too far: {} > {}", player_id, object_id, distance, max_range);
+            return Err(format!("Too far: {:.1}m > {:.1}m", distance, max_range));
         }
+        Ok(object)
+    }
+
+    /// Get an object's network data by id (avoids cloning the full object)
+    /// Harvested objects still return data; check `get_object` for harvest state
+    pub fn get_object_network(&self, object_id: &str) -> Option<EnvironmentObjectData> {
+        self.objects.get(object_id).map(|o| (*o.network).clone())
+    }
+
+    /// Check whether a player at `player_position` may plant at `target` (range, bounds,
+    /// water and spacing), without planting; lets callers validate before taking the seed
+    pub fn check_plant(&self, player_id: &str, player_position: &Position, target: &Position) -> Result<(), String> {
+        if !(target.x.is_finite() && target.y.is_finite() && target.z.is_finite()) {
+            return Err("Invalid plant position".to_string());
+        }
+
+        let distance = player_position.distance_to(target);
+        if distance > self.max_harvest_range {
+            warn!("Player {} attempted to plant too far: {} > {}", player_id, distance, self.max_harvest_range);
+            return Err(format!("Too far: {:.1}m > {:.1}m", distance, self.max_harvest_range));
+        }
+
+        if self.water_level.is_some_and(|level| target.y < level) {
+            return Err("Cannot plant on water".to_string());
+        }
+
+        let chunk = ChunkCoord::from_position(target, self.chunk_size);
+        for neighbor in chunk.neighbors(1) {
+            if let Some(ids) = self.chunk_objects.get(&neighbor) {
+                let blocked = ids.iter().any(|id| {
+                    self.objects
+                        .get(id)
+                        .map(|o| o.position.distance_to(target) < MIN_PLANT_SPACING)
+                        .unwrap_or(false)
+                });
+                if blocked {
+                    return Err("Too close to another object".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Plant a sapling at `target` on behalf of a player
+    /// Validates like `check_plant`; consuming the seed is up to the caller
+    pub fn plant_sapling(&self, player_id: &str, player_position: &Position, target: Position) -> Result<EnvironmentObjectData, String> {
+        self.check_plant(player_id, player_position, &target)?;
+
+        let sapling = EnvironmentObject {
+            object_id: format!("sapling_{}", ulid::Ulid::new()),
+            asset_name: SAPLING_ASSET.to_string(),
+            position: target,
+            rotation: Quaternion::default(),
+            scale: Scale::uniform(0.5),
+            object_type: EnvironmentObjectType::Tree,
+            resource_type: ResourceType::Wood,
+            resource_amount: 1,
+            harvest_time: 1.0,
+            is_harvested: false,
+            harvested_at: None,
+            respawn_time_seconds: None, // Planted objects are gone once harvested
+            harvest_count: 0,
+            collision_radius: 0.0,
+            metadata: None,
+        };
+        let data = sapling.to_network_data();
+        info!("Player {} planted {} at ({:.1}, {:.1})", player_id, sapling.object_id, target.x, target.z);
+        self.add_object(sapling).map_err(|e| e.to_string())?;
+
+        Ok(data)
     }
 
     /// Get objects that should respawn
@@ -441,7 +1323,7 @@ impl EnvironmentManager {
             .filter_map(|entry| {
                 let object = entry.value();
                 if object.should_respawn() {
-                    Some(object.clone())
+                    Some(object.object.clone())
                 } else {
                     None
                 }
@@ -452,11 +1334,16 @@ impl EnvironmentManager {
     /// Get IDs of objects that should respawn (optimized - avoids cloning full objects)
     /// More efficient than get_respawnable_objects() when you only need IDs
     pub fn get_respawnable_object_ids(&self) -> Vec<String> {
+        self.get_respawnable_object_ids_at(unix_time_secs())
+    }
+
+    /// IDs of objects due to respawn at `now`, including per-object jitter
+    pub fn get_respawnable_object_ids_at(&self, now: i64) -> Vec<String> {
         self.objects
             .iter()
             .filter_map(|entry| {
                 let object = entry.value();
-                if object.should_respawn() {
+                if object.should_respawn_at(now, self.respawn_jitter_secs) {
                     Some(object.object_id.clone())
                 } else {
                     None
@@ -466,15 +1353,18 @@ impl EnvironmentManager {
     }
 
     /// Respawn an object
-    pub fn respawn_object(&self, object_id: &str) -> Option<EnvironmentObjectRespawnMessage> {
+    /// Unknown ids in chunks the generator hasn't populated yet report ChunkNotGenerated
+    pub fn respawn_object(&self, object_id: &str) -> Result<EnvironmentObjectRespawnMessage, EnvironmentError> {
         if let Some(mut object) = self.objects.get_mut(object_id) {
             object.respawn();
             info!("Respawned object: {}", object_id);
-            Some(EnvironmentObjectRespawnMessage {
-                object_data: object.to_network_data(),
-            })
-        } else {
-            None
+            return Ok(EnvironmentObjectRespawnMessage {
+                object_data: object.network.clone(),
+            });
+        }
+        match self.get_object_chunk(object_id) {
+            Some(chunk) if !self.is_chunk_generated(&chunk) => Err(EnvironmentError::ChunkNotGenerated(chunk)),
+            _ => Err(EnvironmentError::ObjectNotFound(object_id.to_string())),
         }
     }
 
@@ -492,11 +1382,48 @@ impl EnvironmentManager {
         players
     }
 
+    /// Players who should hear about changes to an object (harvest despawn, respawn):
+    /// everyone watching a chunk within the object type's broadcast radius of its chunk
+    pub fn get_players_near_object(&self, object_id: &str) -> Vec<String> {
+        let object_type = self.objects.get(object_id)
+            .map(|obj| obj.object_type)
+            .or_else(|| parse_object_id(object_id).map(|(object_type, _, _)| object_type));
+        let (Some(chunk), Some(object_type)) = (self.get_object_chunk(object_id), object_type) else {
+            return Vec::new();
+        };
+        let radius = self.broadcast_radius_for(object_type);
+        if radius == 0 {
+            return self.get_players_in_chunk(&chunk);
+        }
+        let area: HashSet<ChunkCoord> = chunk.neighbors(radius).into_iter().collect();
+        self.player_chunks
+            .iter()
+            .filter(|entry| !entry.value().is_disjoint(&area))
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    /// Chunks currently visible to a player (None if the player isn't tracked)
+    pub fn get_player_chunks(&self, player_id: &str) -> Option<HashSet<ChunkCoord>> {
+        self.player_chunks.get(player_id).map(|c| c.clone())
+    }
+
+    /// Track a fixed set of visible chunks for a player (e.g. a spectator's region)
+    pub fn set_player_chunks(&self, player_id: &str, chunks: &[ChunkCoord]) {
+        self.player_chunks.insert(player_id.to_string(), chunks.iter().copied().collect());
+    }
+
+    /// Chunk containing a position
+    pub fn chunk_for_position(&self, position: &Position) -> ChunkCoord {
+        ChunkCoord::from_position(position, self.chunk_size)
+    }
+
     /// Get chunk coordinate for an object ID
     pub fn get_object_chunk(&self, object_id: &str) -> Option<ChunkCoord> {
-        self.objects.get(object_id).map(|obj| {
-            ChunkCoord::from_position(&obj.position, self.chunk_size)
-        })
+        // Fall back to the id encoding for objects no longer in the map
+        self.objects.get(object_id)
+            .map(|obj| ChunkCoord::from_position(&obj.position, self.chunk_size))
+            .or_else(|| parse_object_id(object_id).map(|(_, chunk, _)| chunk))
     }
 
     /// Background task to handle respawns
@@ -513,7 +1440,7 @@ impl EnvironmentManager {
     ///
     /// For now, objects respawn server-side but clients only see them on reconnect or chunk reload
     pub async fn start_respawn_task(self: Arc<Self>) {
-        let mut interval = time::interval(Duration::from_secs(10)); // Check every 10 seconds
+        let mut interval = time::interval(self.respawn_check_interval);
 
         loop {
             interval.tick().await;
@@ -523,11 +1450,11 @@ impl EnvironmentManager {
                 debug!("Found {} objects ready to respawn", respawnable_ids.len());
 
                 for object_id in respawnable_ids {
-                    if let Some(_respawn_msg) = self.respawn_object(&object_id) {
+                    if let Ok(_respawn_msg) = self.respawn_object(&object_id) {
                         // Get chunk for this object
                         if let Some(chunk) = self.get_object_chunk(&object_id) {
-                            // Get all players who can see this chunk
-                            let player_ids = self.get_players_in_chunk(&chunk);
+                            // Get all players within the object's broadcast radius
+                            let player_ids = self.get_players_near_object(&object_id);
 
                             if !player_ids.is_empty() {
                                 debug!(
@@ -546,12 +1473,34 @@ impl EnvironmentManager {
         }
     }
 
+    /// Whether any per-player state is held for this player
+    pub fn has_player_state(&self, player_id: &str) -> bool {
+        self.player_chunks.contains_key(player_id)
+            || self.player_view_distance.contains_key(player_id)
+            || self.player_chunk_memory.contains_key(player_id)
+    }
+
     /// Remove player from tracking (call on disconnect)
+    /// Clears every per-player map; new per-player state must be dropped here too
     pub fn remove_player(&self, player_id: &str) {
         self.player_chunks.remove(player_id);
+        self.player_view_distance.remove(player_id);
+        self.player_chunk_memory.remove(player_id);
         debug!("Removed player {} from environment tracking", player_id);
     }
 
+    /// Remove every object and generated-chunk marker (world reseed)
+    /// Pending harvests of old objects fail with NotFound afterwards
+    pub fn clear_objects(&self) {
+        self.generated_chunks.clear();
+        self.chunk_objects.clear();
+        self.objects.clear();
+        // Forget what players were shown so their next move sends the new world's chunks
+        self.player_chunks.clear();
+        self.player_chunk_memory.clear();
+        info!("Cleared all environment objects");
+    }
+
     /// Get statistics
     pub fn get_stats(&self) -> EnvironmentStats {
         let total_objects = self.objects.len();
@@ -576,3 +1525,749 @@ pub struct EnvironmentStats {
     pub tracked_players: usize,
     pub loaded_chunks: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::fixtures::test_object;
+
+    #[test]
+    fn test_fnv1a_matches_reference_values() {
+        // Yields and respawn jitter depend on these staying fixed
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a("foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn test_should_respawn_edge_cases() {
+        let now = 1_000_000;
+        let mut object = test_object("tree_0_0_idx_0", 5.0, 5.0);
+        object.is_harvested = true;
+        object.harvested_at = Some(now);
+
+        // Zero respawn time is instant, even with jitter
+        object.respawn_time_seconds = Some(0);
+        assert!(object.should_respawn_at(now, 60));
+
+        // No respawn time never respawns
+        object.respawn_time_seconds = None;
+        assert!(!object.should_respawn_at(now + 1_000_000, 0));
+
+        // Harvest timestamp from the future (clock skew) is not negative elapsed time
+        object.respawn_time_seconds = Some(300);
+        object.harvested_at = Some(now + 3600);
+        assert!(!object.should_respawn_at(now, 0));
+        assert!(!object.should_respawn_at(now + 299, 0));
+        object.harvested_at = Some(now - 300);
+        assert!(object.should_respawn_at(now, 0));
+
+        // Unharvested objects never respawn
+        object.is_harvested = false;
+        assert!(!object.should_respawn_at(now + 1_000_000, 0));
+    }
+
+    /// One object at the center of every chunk in a 9x9 grid around the origin
+    fn grid_manager() -> EnvironmentManager {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0);
+        for cx in -4..=4 {
+            for cz in -4..=4 {
+                let id = format!("tree_{}_{}_idx_0", cx, cz);
+                manager.add_object(test_object(&id, cx as f32 * 50.0 + 25.0, cz as f32 * 50.0 + 25.0)).unwrap();
+            }
+        }
+        manager
+    }
+
+    #[test]
+    fn test_get_harvestable_near_filters_range_and_harvested() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0);
+        manager.add_object(test_object("close", 3.0, 4.0)).unwrap(); // 5m
+        manager.add_object(test_object("edge", 10.0, 0.0)).unwrap(); // exactly in range
+        manager.add_object(test_object("far", 10.5, 0.0)).unwrap();
+        manager.add_object(test_object("other_chunk", 48.0, 52.0)).unwrap();
+        manager.add_object(test_object("across_border", -6.0, -2.0)).unwrap(); // neighbouring chunk, in range
+        let mut high = test_object("high", 0.0, 8.0);
+        high.position.y = 30.0; // height is ignored
+        manager.add_object(high).unwrap();
+        manager.add_object(test_object("harvested", 1.0, 1.0)).unwrap();
+        manager.objects.get_mut("harvested").unwrap().mark_harvested();
+
+        let mut ids = manager.get_harvestable_near(&Position::new(0.0, 0.0, 0.0));
+        ids.sort();
+        assert_eq!(ids, vec!["across_border", "close", "edge", "high"]);
+    }
+
+    #[test]
+    fn test_add_object_normalizes_rotation_and_rejects_bad_scale() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0);
+
+        let mut degenerate = test_object("degenerate", 1.0, 1.0);
+        degenerate.rotation = Quaternion { x: 0.0, y: 0.0, z: 0.0, w: 0.0 };
+        manager.add_object(degenerate).unwrap();
+        assert_eq!(manager.get_object("degenerate").unwrap().rotation, Quaternion::default());
+
+        let mut scaled = test_object("scaled", 2.0, 2.0);
+        scaled.rotation = Quaternion { x: 0.0, y: 2.0, z: 0.0, w: 0.0 };
+        manager.add_object(scaled).unwrap();
+        assert_eq!(manager.get_object("scaled").unwrap().rotation, Quaternion { x: 0.0, y: 1.0, z: 0.0, w: 0.0 });
+
+        let mut flat = test_object("flat", 3.0, 3.0);
+        flat.scale = Scale { x: 1.0, y: 0.0, z: 1.0 };
+        assert!(manager.add_object(flat).is_err());
+        assert!(manager.get_object("flat").is_none());
+    }
+
+    #[test]
+    fn test_bulk_insert_matches_individual_inserts() {
+        let generator = EnvironmentGenerator::new(42, 50.0);
+        let origin = ChunkCoord { x: 0, z: 0 };
+        let objects = generator.generate_area(&origin, 2);
+        let individual = EnvironmentManager::new(50.0, 3, 10.0);
+        for object in objects.clone() {
+            individual.add_object(object).unwrap();
+        }
+        let bulk = EnvironmentManager::new(50.0, 3, 10.0);
+        let mut with_invalid = objects.clone();
+        let mut flat = test_object("flat", 3.0, 3.0);
+        flat.scale = Scale { x: 1.0, y: 0.0, z: 1.0 };
+        with_invalid.push(flat);
+        assert_eq!(bulk.add_objects(with_invalid), objects.len());
+
+        let (a, b) = (individual.get_stats(), bulk.get_stats());
+        assert_eq!((a.total_objects, a.active_objects, a.loaded_chunks), (b.total_objects, b.active_objects, b.loaded_chunks));
+        for chunk in origin.neighbors(2) {
+            let ids = |manager: &EnvironmentManager| {
+                let mut ids: Vec<_> = manager.get_objects_in_chunks(&[chunk]).into_iter().map(|o| o.object_id).collect();
+                ids.sort();
+                ids
+            };
+            assert_eq!(ids(&individual), ids(&bulk));
+        }
+    }
+
+    #[test]
+    fn test_add_object_rejects_duplicate_id() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0);
+        manager.add_object(test_object("tree_a", 5.0, 5.0)).unwrap();
+
+        let mut impostor = test_object("tree_a", 80.0, 80.0);
+        impostor.resource_amount = 99;
+        assert_eq!(manager.add_object(impostor), Err(EnvironmentError::DuplicateObject("tree_a".to_string())));
+        assert_eq!(manager.get_object("tree_a").unwrap().resource_amount, 5);
+        assert_eq!(manager.get_stats().loaded_chunks, 1);
+
+        let mut lost = test_object("lost", 0.0, 0.0);
+        lost.position.x = f32::NAN;
+        assert_eq!(manager.add_object(lost), Err(EnvironmentError::OutOfBounds("lost".to_string())));
+    }
+
+    #[test]
+    fn test_respawn_unknown_object_errors() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0);
+        let generator = EnvironmentGenerator::new(42, 50.0);
+        let home = ChunkCoord { x: 0, z: 0 };
+        manager.ensure_chunk_generated(&generator, &home);
+
+        assert_eq!(manager.respawn_object("tree_0_0_idx_999").err(), Some(EnvironmentError::ObjectNotFound("tree_0_0_idx_999".to_string())));
+        assert_eq!(manager.respawn_object("tree_7_7_idx_0").err(), Some(EnvironmentError::ChunkNotGenerated(ChunkCoord { x: 7, z: 7 })));
+    }
+
+    #[test]
+    fn test_harvest_policy_can_forbid_object_types() {
+        // Hardcore-style rule: trees can't be felled bare-handed
+        struct NoTreeFelling;
+        impl HarvestPolicy for NoTreeFelling {
+            fn validate(&self, _player_id: &str, object: &EnvironmentObject, context: &HarvestContext) -> Result<(), HarvestErrorCode> {
+                match (object.object_type, context.tool) {
+                    (EnvironmentObjectType::Tree, None) => Err(HarvestErrorCode::MissingTool),
+                    _ => Ok(()),
+                }
+            }
+        }
+        let request = || HarvestObjectRequest {
+            object_id: "tree".to_string(),
+            player_position: Position::new(1.0, 0.0, 1.0),
+        };
+
+        // Default policy allows a tree in range
+        let standard = EnvironmentManager::new(50.0, 3, 10.0);
+        standard.add_object(test_object("tree", 2.0, 2.0)).unwrap();
+        assert!(standard.handle_harvest_request("p", request(), None).success);
+
+        let restricted = EnvironmentManager::new(50.0, 3, 10.0).with_harvest_policy(Arc::new(NoTreeFelling));
+        restricted.add_object(test_object("tree", 2.0, 2.0)).unwrap();
+        let response = restricted.handle_harvest_request("p", request(), None);
+        assert!(!response.success);
+        assert_eq!(response.error_code, Some(HarvestErrorCode::MissingTool));
+        assert!(!restricted.get_object("tree").unwrap().is_harvested);
+        assert!(restricted.handle_harvest_request("p", request(), Some("iron_axe")).success);
+    }
+
+    #[test]
+    fn test_standard_policy_enforces_required_tool_tiers() {
+        let policy = StandardHarvestPolicy::with_required_tools(HashMap::from([(EnvironmentObjectType::Tree, 2)]));
+        let manager = EnvironmentManager::new(50.0, 3, 10.0).with_harvest_policy(Arc::new(policy));
+        manager.add_object(test_object("tree", 2.0, 2.0)).unwrap();
+        let harvest = |tool| {
+            let request = HarvestObjectRequest { object_id: "tree".to_string(), player_position: Position::new(1.0, 0.0, 1.0) };
+            manager.handle_harvest_request("p", request, tool)
+        };
+
+        // Bare hands, a tool for another object type and a tier too low are all refused
+        for tool in [None, Some("steel_pickaxe"), Some("stone_axe")] {
+            assert_eq!(harvest(tool).error_code, Some(HarvestErrorCode::MissingTool), "{tool:?}");
+        }
+        assert!(!manager.get_object("tree").unwrap().is_harvested);
+        assert!(harvest(Some("iron_axe")).success);
+    }
+
+    #[test]
+    fn test_harvest_all_in_range_skips_ineligible_objects() {
+        // Trees need a tool; anything tagged "cooling" is on cooldown
+        struct ToolsAndCooldowns;
+        impl HarvestPolicy for ToolsAndCooldowns {
+            fn validate(&self, _player_id: &str, object: &EnvironmentObject, context: &HarvestContext) -> Result<(), HarvestErrorCode> {
+                if object.object_id.starts_with("cooling") {
+                    return Err(HarvestErrorCode::OnCooldown);
+                }
+                match (object.object_type, context.tool) {
+                    (EnvironmentObjectType::Tree, None) => Err(HarvestErrorCode::MissingTool),
+                    _ => Ok(()),
+                }
+            }
+        }
+        let rock = |id: &str, x: f32| EnvironmentObject {
+            object_type: EnvironmentObjectType::Rock,
+            resource_type: ResourceType::Stone,
+            ..test_object(id, x, 1.0)
+        };
+        let setup = || {
+            let manager = EnvironmentManager::new(50.0, 3, 10.0).with_harvest_policy(Arc::new(ToolsAndCooldowns));
+            manager.add_object(rock("rock_near", 47.0)).unwrap();
+            manager.add_object(rock("rock_next_chunk", 52.0)).unwrap(); // across the chunk border
+            manager.add_object(rock("rock_far", 30.0)).unwrap();
+            manager.add_object(rock("cooling_rock", 48.0)).unwrap();
+            manager.add_object(test_object("tree_near", 49.0, 1.0)).unwrap();
+            let mut stump = rock("rock_stump", 50.0);
+            stump.mark_harvested();
+            manager.add_object(stump).unwrap();
+            manager
+        };
+        let player = Position::new(49.0, 0.0, 0.0);
+
+        let manager = setup();
+        let mut harvested: Vec<String> = manager
+            .harvest_all_in_range("p", &player, None, |_| true)
+            .into_iter()
+            .map(|r| r.object_id)
+            .collect();
+        harvested.sort();
+        assert_eq!(harvested, vec!["rock_near", "rock_next_chunk"]);
+        assert!(!manager.get_object("tree_near").unwrap().is_harvested);
+        assert!(!manager.get_object("cooling_rock").unwrap().is_harvested);
+        assert!(!manager.get_object("rock_far").unwrap().is_harvested);
+
+        // The limit keeps the nearest objects
+        let limited = setup().with_harvest_all_limit(1);
+        let results = limited.harvest_all_in_range("p", &player, Some("iron_axe"), |_| true);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].object_id, "tree_near");
+    }
+
+    #[test]
+    fn test_spawn_protection_blocks_harvest_inside_radius() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0).with_spawn_protection(SpawnProtection {
+            center: Position::new(0.0, 0.0, 0.0),
+            radius: 20.0,
+        });
+        manager.add_object(test_object("inside", 19.5, 0.0)).unwrap();
+        manager.add_object(test_object("outside", 20.5, 0.0)).unwrap();
+        let player = Position::new(20.0, 0.0, 1.0);
+
+        let inside = manager.handle_harvest_request("p", HarvestObjectRequest {
+            object_id: "inside".to_string(),
+            player_position: player,
+        }, None);
+        assert!(!inside.success);
+        assert_eq!(inside.error_code, Some(HarvestErrorCode::Protected));
+        assert!(inside.error_message.unwrap().starts_with("Spawn protection"));
+
+        let outside = manager.handle_harvest_request("p", HarvestObjectRequest {
+            object_id: "outside".to_string(),
+            player_position: player,
+        }, None);
+        assert!(outside.success);
+        assert!(manager.is_spawn_protected(&Position::new(0.0, 0.0, 19.0)));
+    }
+
+    #[test]
+    fn test_tree_respawn_reaches_neighboring_chunk() {
+        let manager = EnvironmentManager::new(50.0, 1, 10.0)
+            .with_broadcast_radii(HashMap::from([(EnvironmentObjectType::Tree, 1)]));
+        manager.add_object(test_object("tree", 10.0, 10.0)).unwrap();
+        let mut grass = test_object("grass", 12.0, 10.0);
+        grass.object_type = EnvironmentObjectType::Grass;
+        manager.add_object(grass).unwrap();
+        // Players in chunks 0, 2 and 3 along x, each seeing one chunk around them
+        manager.send_initial_objects("home", &Position::new(10.0, 0.0, 10.0));
+        manager.send_initial_objects("neighbor", &Position::new(125.0, 0.0, 10.0));
+        manager.send_initial_objects("far", &Position::new(175.0, 0.0, 10.0));
+
+        for id in ["tree", "grass"] {
+            manager.handle_harvest_request("home", HarvestObjectRequest {
+                object_id: id.to_string(),
+                player_position: Position::new(11.0, 0.0, 10.0),
+            }, None);
+            assert!(manager.respawn_object(id).is_ok());
+        }
+
+        let mut tree = manager.get_players_near_object("tree");
+        tree.sort();
+        assert_eq!(tree, vec!["home", "neighbor"]);
+        assert_eq!(manager.get_players_near_object("grass"), vec!["home"]);
+    }
+
+    #[test]
+    fn test_per_type_harvest_ranges() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0)
+            .with_harvest_ranges(HashMap::from([(EnvironmentObjectType::Grass, 2.0)]));
+        for (id, x) in [("grass_in", 2.0), ("grass_out", 2.5), ("tree_in", 10.0), ("tree_out", 10.5)] {
+            let mut object = test_object(id, x, 0.0);
+            if id.starts_with("grass") {
+                object.object_type = EnvironmentObjectType::Grass;
+            }
+            manager.add_object(object).unwrap();
+        }
+        let harvest = |object_id: &str| {
+            manager.handle_harvest_request("p", HarvestObjectRequest {
+                object_id: object_id.to_string(),
+                player_position: Position::new(0.0, 0.0, 0.0),
+            }, None)
+        };
+
+        let mut near = manager.get_harvestable_near(&Position::new(0.0, 0.0, 0.0));
+        near.sort();
+        assert_eq!(near, vec!["grass_in", "tree_in"]);
+
+        assert!(harvest("grass_in").success);
+        assert_eq!(harvest("grass_out").error_code, Some(HarvestErrorCode::TooFar));
+        assert!(harvest("tree_in").success);
+        assert_eq!(harvest("tree_out").error_code, Some(HarvestErrorCode::TooFar));
+    }
+
+    #[test]
+    fn test_harvest_rejections_set_error_code() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0);
+        manager.add_object(test_object("tree_a", 5.0, 5.0)).unwrap();
+        let harvest = |object_id: &str, player_position: Position| {
+            manager.handle_harvest_request("p", HarvestObjectRequest {
+                object_id: object_id.to_string(),
+                player_position,
+            }, None)
+        };
+
+        assert_eq!(harvest("missing", Position::new(5.0, 0.0, 5.0)).error_code, Some(HarvestErrorCode::NotFound));
+        assert_eq!(harvest("tree_a", Position::new(40.0, 0.0, 40.0)).error_code, Some(HarvestErrorCode::TooFar));
+        let ok = harvest("tree_a", Position::new(5.0, 0.0, 5.0));
+        assert!(ok.success);
+        assert_eq!(ok.error_code, None);
+        assert_eq!(harvest("tree_a", Position::new(5.0, 0.0, 5.0)).error_code, Some(HarvestErrorCode::AlreadyHarvested));
+
+        let json = serde_json::to_value(harvest("tree_a", Position::new(5.0, 0.0, 5.0))).unwrap();
+        assert_eq!(json["errorCode"], "already_harvested");
+    }
+
+    #[test]
+    fn test_harvest_returns_effect_for_resource_type() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0)
+            .with_harvest_effects(crate::game::WorldConfig::default().harvest_effects);
+        manager.add_object(test_object("tree_a", 5.0, 5.0)).unwrap();
+        let mut rock = test_object("rock_a", 6.0, 5.0);
+        rock.object_type = EnvironmentObjectType::Rock;
+        rock.resource_type = ResourceType::Stone;
+        manager.add_object(rock).unwrap();
+
+        let harvest = |object_id: &str| manager.handle_harvest_request("p", HarvestObjectRequest {
+            object_id: object_id.to_string(),
+            player_position: Position::new(5.0, 0.0, 6.0),
+        }, None);
+
+        assert_eq!(harvest("tree_a").effect_id.as_deref(), Some("fx_wood_chips"));
+        assert_eq!(harvest("rock_a").effect_id.as_deref(), Some("fx_stone_debris"));
+        // Failed harvests carry no effect
+        assert!(harvest("tree_a").effect_id.is_none());
+    }
+
+    #[test]
+    fn test_metadata_round_trips_through_network_data() {
+        let mut object = test_object("tree_0_0_idx_0", 5.0, 5.0);
+        let plain = serde_json::to_value(object.to_network_data()).unwrap();
+        assert!(plain.get("metadata").is_none());
+
+        object.metadata = Some(serde_json::json!({ "tintSeed": 42, "windSway": 0.5 }));
+        let data = object.to_network_data();
+        let json = serde_json::to_string(&data).unwrap();
+        let decoded: EnvironmentObjectData = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(decoded.metadata.unwrap()["tintSeed"], 42);
+    }
+
+    #[test]
+    fn test_network_data_shared_across_reads_and_respawn() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0);
+        manager.add_object(test_object("tree_0_0_idx_0", 5.0, 5.0)).unwrap();
+        let chunks = [ChunkCoord { x: 0, z: 0 }];
+
+        let first = manager.get_objects_in_chunks_network(&chunks);
+        let second = manager.get_objects_in_chunks_network(&chunks);
+        assert!(Arc::ptr_eq(&first[0], &second[0]));
+
+        // Harvest state lives beside the shared view and is still honored
+        let request = HarvestObjectRequest { object_id: "tree_0_0_idx_0".to_string(), player_position: Position::new(5.0, 0.0, 5.0) };
+        assert!(manager.handle_harvest_request("p1", request, None).success);
+        assert!(manager.get_objects_in_chunks_network(&chunks).is_empty());
+        assert!(manager.get_object("tree_0_0_idx_0").unwrap().is_harvested);
+
+        let respawned = manager.respawn_object("tree_0_0_idx_0").unwrap();
+        assert!(Arc::ptr_eq(&respawned.object_data, &first[0]));
+        assert!(!manager.get_object("tree_0_0_idx_0").unwrap().is_harvested);
+        assert_eq!(manager.get_objects_in_chunks_network(&chunks).len(), 1);
+
+        // Re-adding the id is refused and leaves the shared view alone
+        let mut replacement = test_object("tree_0_0_idx_0", 5.0, 5.0);
+        replacement.asset_name = "Tree_Pine_01".to_string();
+        assert!(matches!(manager.add_object(replacement), Err(EnvironmentError::DuplicateObject(_))));
+        assert!(Arc::ptr_eq(&manager.get_objects_in_chunks_network(&chunks)[0], &first[0]));
+    }
+
+    #[test]
+    fn test_get_object_accessors() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0);
+        manager.add_object(test_object("tree_a", 5.0, 5.0)).unwrap();
+
+        let object = manager.get_object("tree_a").unwrap();
+        assert!(!object.is_harvested);
+        assert_eq!(manager.get_object_network("tree_a").unwrap(), object.to_network_data());
+
+        manager.handle_harvest_request("p", HarvestObjectRequest {
+            object_id: "tree_a".to_string(),
+            player_position: Position::new(5.0, 0.0, 6.0),
+        }, None);
+        assert!(manager.get_object("tree_a").unwrap().is_harvested);
+        assert_eq!(manager.get_object_network("tree_a").unwrap().object_id, "tree_a");
+
+        assert!(manager.get_object("missing").is_none());
+        assert!(manager.get_object_network("missing").is_none());
+    }
+
+    #[test]
+    fn test_respawn_jitter_spreads_due_objects_across_ticks() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0).with_respawn_timing(Duration::from_secs(10), 30);
+        let harvested_at = 1_700_000_000;
+        for i in 0..200 {
+            let mut object = test_object(&format!("tree_0_0_idx_{}", i), 5.0, 5.0);
+            object.is_harvested = true;
+            object.harvested_at = Some(harvested_at);
+            manager.add_object(object).unwrap();
+        }
+
+        // Simulate respawn checks every 10s from the moment all objects are due
+        let mut per_tick = Vec::new();
+        let mut respawned = 0;
+        for tick in 0..4 {
+            let ids = manager.get_respawnable_object_ids_at(harvested_at + 300 + tick * 10);
+            per_tick.push(ids.len());
+            for id in &ids {
+                manager.respawn_object(id).unwrap();
+            }
+            respawned += ids.len();
+        }
+
+        assert_eq!(respawned, 200);
+        assert!(per_tick.iter().filter(|&&n| n > 0).count() >= 3, "respawns per tick: {:?}", per_tick);
+        assert!(per_tick[0] < 200);
+    }
+
+    #[test]
+    fn test_higher_tool_tier_yields_more() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0);
+        manager.add_object(test_object("tree_a", 5.0, 5.0)).unwrap();
+        let harvest = |tool| {
+            let response = manager.handle_harvest_request("p", HarvestObjectRequest {
+                object_id: "tree_a".to_string(),
+                player_position: Position::new(5.0, 0.0, 6.0),
+            }, tool);
+            manager.respawn_object("tree_a").unwrap();
+            response.resource_amount
+        };
+
+        let bare = harvest(None);
+        let stone = harvest(Some("stone_axe"));
+        let iron = harvest(Some("iron_axe"));
+        let wrong_tool = harvest(Some("iron_pickaxe"));
+
+        assert_eq!(bare, 5);
+        assert_eq!(stone, 5);
+        assert!(iron > stone);
+        assert_eq!(wrong_tool, 5);
+    }
+
+    #[test]
+    fn test_randomized_yields_reproducible_across_respawns() {
+        let yields = HashMap::from([(ResourceType::Wood, HarvestYieldRange { min: 1, max: 20 })]);
+        let run = |seed| {
+            let manager = EnvironmentManager::new(50.0, 3, 10.0).with_harvest_yields(yields.clone(), seed);
+            manager.add_object(test_object("tree_a", 5.0, 5.0)).unwrap();
+            (0..8)
+                .map(|_| {
+                    let response = manager.handle_harvest_request("p", HarvestObjectRequest {
+                        object_id: "tree_a".to_string(),
+                        player_position: Position::new(5.0, 0.0, 6.0),
+                    }, None);
+                    manager.respawn_object("tree_a").unwrap();
+                    response.resource_amount
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let amounts = run(42);
+        assert_eq!(amounts, run(42), "same seed must replay the same yields");
+        assert!(amounts.iter().all(|a| (1..=20).contains(a)));
+        assert!(amounts.iter().any(|a| *a != amounts[0]), "yields should vary between harvests: {amounts:?}");
+        assert_ne!(amounts, run(7));
+
+        // Unconfigured resource types keep the fixed amount
+        let fixed = EnvironmentManager::new(50.0, 3, 10.0).with_harvest_yields(HashMap::new(), 42);
+        fixed.add_object(test_object("tree_b", 5.0, 5.0)).unwrap();
+        let response = fixed.handle_harvest_request("p", HarvestObjectRequest {
+            object_id: "tree_b".to_string(),
+            player_position: Position::new(5.0, 0.0, 6.0),
+        }, None);
+        assert_eq!(response.resource_amount, 5);
+        assert_eq!(fixed.get_object("tree_b").unwrap().harvest_count, 1);
+    }
+
+    #[test]
+    fn test_harvests_are_audited() {
+        use crate::game::harvest_audit::HarvestAuditResult;
+
+        let manager = EnvironmentManager::new(50.0, 3, 10.0);
+        manager.add_object(test_object("tree_a", 5.0, 5.0)).unwrap();
+        manager.add_object(test_object("tree_b", 40.0, 40.0)).unwrap();
+        let request = |object_id: &str| HarvestObjectRequest {
+            object_id: object_id.to_string(),
+            player_position: Position::new(5.0, 0.0, 6.0),
+        };
+
+        assert!(manager.handle_harvest_request("player-1", request("tree_a"), None).success);
+        assert!(!manager.handle_harvest_request("player-1", request("tree_b"), None).success);
+        assert!(!manager.handle_harvest_request("player-2", request("tree_x"), None).success);
+
+        let entries = manager.harvest_audit().recent(Some("player-1"), 10);
+        assert_eq!(entries.len(), 2);
+        let (rejected, granted) = (&entries[0], &entries[1]);
+        assert_eq!(granted.object_id, "tree_a");
+        assert_eq!(granted.result, HarvestAuditResult::Granted);
+        assert_eq!(granted.distance, Some(1.0));
+        assert!(granted.amount > 0);
+        assert_eq!(rejected.object_id, "tree_b");
+        assert_eq!(rejected.result, HarvestAuditResult::Rejected);
+        assert_eq!(rejected.error_code, Some(HarvestErrorCode::TooFar));
+        assert_eq!(rejected.amount, 0);
+
+        let missing = &manager.harvest_audit().recent(Some("player-2"), 10)[0];
+        assert_eq!(missing.error_code, Some(HarvestErrorCode::NotFound));
+        assert_eq!(missing.distance, None);
+    }
+
+    #[test]
+    fn test_successful_harvest_publishes_event() {
+        let events = GameEventBus::default();
+        let mut quests = events.subscribe();
+        let manager = EnvironmentManager::new(50.0, 3, 10.0).with_event_bus(events);
+        manager.add_object(test_object("tree_a", 5.0, 5.0)).unwrap();
+        let request = || HarvestObjectRequest {
+            object_id: "tree_a".to_string(),
+            player_position: Position::new(5.0, 0.0, 6.0),
+        };
+
+        assert!(manager.handle_harvest_request("player-1", request(), None).success);
+        assert_eq!(quests.try_recv().unwrap(), GameEvent::Harvested {
+            player_id: "player-1".to_string(),
+            object_id: "tree_a".to_string(),
+            resource_type: ResourceType::Wood,
+            amount: 5,
+        });
+
+        // Failed harvests publish nothing
+        assert!(!manager.handle_harvest_request("player-1", request(), None).success);
+        assert!(quests.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_plant_sapling_validation() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0).with_water_level(-1.0);
+        manager.add_object(test_object("tree_0_0_idx_0", 5.0, 5.0)).unwrap();
+        let player = Position::new(0.0, 0.0, 0.0);
+
+        assert!(manager.plant_sapling("p", &player, Position::new(20.0, 0.0, 0.0)).is_err());
+        assert!(manager.plant_sapling("p", &player, Position::new(2.0, -3.0, 2.0)).is_err());
+        assert!(manager.plant_sapling("p", &player, Position::new(5.5, 0.0, 5.0)).is_err());
+
+        let planted = manager.plant_sapling("p", &player, Position::new(2.0, 0.0, 2.0)).unwrap();
+        assert_eq!(planted.asset_name, SAPLING_ASSET);
+        assert!(manager.get_object(&planted.object_id).is_some());
+    }
+
+    #[test]
+    fn test_player_view_distance_limits_initial_objects() {
+        let manager = grid_manager();
+        let spawn = Position::new(25.0, 0.0, 25.0);
+
+        assert_eq!(manager.set_player_view_distance("mobile", 1), 1);
+        let mobile = manager.send_initial_objects("mobile", &spawn);
+        let desktop = manager.send_initial_objects("desktop", &spawn);
+
+        assert_eq!(mobile.objects.len(), 9);   // 3x3
+        assert_eq!(desktop.objects.len(), 49); // 7x7
+    }
+
+    /// Objects only in chunk (-1, 0); a view distance of 1 and moving between
+    /// chunk (0, 0) and (1, 0) toggles only that chunk in and out of view
+    fn diffing_manager() -> EnvironmentManager {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0).with_chunk_diffing(true);
+        for i in 0..3 {
+            manager.add_object(test_object(&format!("tree_-1_0_idx_{}", i), -25.0 + i as f32, 25.0)).unwrap();
+        }
+        manager.set_player_view_distance("p1", 1);
+        manager
+    }
+
+    #[test]
+    fn test_neighbors_radius_clamped() {
+        let center = ChunkCoord { x: 0, z: 0 };
+        assert_eq!(center.neighbors(1).len(), 9);
+        assert!(center.neighbors(-3).is_empty());
+
+        let side = (2 * MAX_NEIGHBOR_RADIUS + 1) as usize;
+        let huge = center.neighbors(i32::MAX / 4);
+        assert_eq!(huge.len(), side * side);
+        assert!(huge.capacity() <= side * side);
+        assert!(huge.iter().all(|c| c.x.abs() <= MAX_NEIGHBOR_RADIUS && c.z.abs() <= MAX_NEIGHBOR_RADIUS));
+    }
+
+    #[test]
+    fn test_chunk_reentry_sends_only_changes() {
+        let manager = diffing_manager();
+        let home = Position::new(25.0, 0.0, 25.0);
+        let away = Position::new(75.0, 0.0, 25.0);
+
+        assert_eq!(manager.send_initial_objects("p1", &home).objects.len(), 3);
+
+        // Leave: full despawn of the chunk
+        let (spawn, despawn) = manager.update_player_chunks("p1", &away);
+        assert!(spawn.is_none());
+        assert_eq!(despawn.unwrap().object_ids.len(), 3);
+
+        // Harvest while away
+        manager.objects.get_mut("tree_-1_0_idx_1").unwrap().mark_harvested();
+
+        // Re-enter: only a despawn for the harvested object
+        let (spawn, despawn) = manager.update_player_chunks("p1", &home);
+        assert!(spawn.is_none());
+        assert_eq!(despawn.unwrap().object_ids, vec!["tree_-1_0_idx_1".to_string()]);
+    }
+
+    #[test]
+    fn test_grouped_despawns_one_entry_per_exited_chunk() {
+        let manager = EnvironmentManager::new(50.0, 3, 10.0).with_grouped_despawns(true);
+        manager.add_object(test_object("tree_-1_0_idx_0", -25.0, 25.0)).unwrap();
+        manager.add_object(test_object("tree_-1_0_idx_1", -20.0, 25.0)).unwrap();
+        manager.add_object(test_object("tree_-1_1_idx_0", -25.0, 75.0)).unwrap();
+        manager.set_player_view_distance("p1", 1);
+        manager.send_initial_objects("p1", &Position::new(25.0, 0.0, 25.0));
+
+        // Moving one chunk east drops the whole x = -1 column; two of its chunks have objects
+        let (_, despawn) = manager.update_player_chunks("p1", &Position::new(75.0, 0.0, 25.0));
+        let despawn = despawn.unwrap();
+        assert_eq!(despawn.object_ids.len(), 3);
+
+        let mut groups: Vec<_> = despawn.chunks.iter().map(|g| (g.chunk, g.object_ids.len())).collect();
+        groups.sort_by_key(|(chunk, _)| chunk.z);
+        assert_eq!(groups, vec![(ChunkCoord { x: -1, z: 0 }, 2), (ChunkCoord { x: -1, z: 1 }, 1)]);
+
+        // Without the option only the flat list is sent
+        let flat = EnvironmentManager::new(50.0, 3, 10.0);
+        flat.add_object(test_object("tree_-1_0_idx_0", -25.0, 25.0)).unwrap();
+        flat.set_player_view_distance("p1", 1);
+        flat.send_initial_objects("p1", &Position::new(25.0, 0.0, 25.0));
+        let (_, despawn) = flat.update_player_chunks("p1", &Position::new(75.0, 0.0, 25.0));
+        assert!(despawn.unwrap().chunks.is_empty());
+    }
+
+    #[test]
+    fn test_chunk_memory_forgets_least_recently_left_chunk() {
+        let mut memory = ChunkMemory::default();
+        let chunk = |x: i32| ChunkCoord { x, z: 0 };
+        for x in 0..MAX_REMEMBERED_CHUNKS as i32 {
+            memory.remember(chunk(x), HashSet::new());
+        }
+
+        // Re-enter and leave the oldest chunk again: it's now the most recent
+        assert!(memory.take(&chunk(0)).is_some());
+        memory.remember(chunk(0), HashSet::new());
+
+        memory.remember(chunk(-1), HashSet::new());
+        assert_eq!(memory.snapshots.len(), MAX_REMEMBERED_CHUNKS);
+        assert!(memory.snapshots.contains_key(&chunk(0)));
+        assert!(memory.snapshots.contains_key(&chunk(-1)));
+        assert!(!memory.snapshots.contains_key(&chunk(1)));
+        assert_eq!(memory.order.len(), MAX_REMEMBERED_CHUNKS);
+    }
+
+    #[test]
+    fn test_chunk_reentry_sends_respawned_objects() {
+        let manager = diffing_manager();
+        let home = Position::new(25.0, 0.0, 25.0);
+        let away = Position::new(75.0, 0.0, 25.0);
+
+        manager.objects.get_mut("tree_-1_0_idx_2").unwrap().mark_harvested();
+        manager.send_initial_objects("p1", &home);
+        manager.update_player_chunks("p1", &away);
+        manager.respawn_object("tree_-1_0_idx_2").unwrap();
+
+        let (spawn, despawn) = manager.update_player_chunks("p1", &home);
+        let spawned: Vec<_> = spawn.unwrap().objects.iter().map(|o| o.object_id.clone()).collect();
+        assert_eq!(spawned, vec!["tree_-1_0_idx_2".to_string()]);
+        assert!(despawn.is_none());
+    }
+
+    #[test]
+    fn test_remove_player_clears_all_per_player_state() {
+        let manager = grid_manager().with_chunk_diffing(true);
+        manager.set_player_view_distance("player-gone", 1);
+        manager.send_initial_objects("player-gone", &Position::new(25.0, 0.0, 25.0));
+        manager.update_player_chunks("player-gone", &Position::new(175.0, 0.0, 25.0));
+        manager.send_initial_objects("player-stays", &Position::new(25.0, 0.0, 25.0));
+        assert!(manager.player_chunk_memory.get("player-gone").is_some_and(|m| !m.snapshots.is_empty()));
+
+        manager.remove_player("player-gone");
+
+        assert!(!manager.player_chunks.contains_key("player-gone"));
+        assert!(!manager.player_view_distance.contains_key("player-gone"));
+        assert!(!manager.player_chunk_memory.contains_key("player-gone"));
+        assert!(!manager.has_player_state("player-gone"));
+        assert!(manager.has_player_state("player-stays"));
+    }
+
+    #[test]
+    fn test_player_view_distance_clamped_to_server_max() {
+        let manager = grid_manager();
+        assert_eq!(manager.set_player_view_distance("greedy", 50), 3);
+        assert_eq!(manager.set_player_view_distance("zero", 0), 1);
+
+        manager.remove_player("greedy");
+        assert_eq!(manager.get_player_view_distance("greedy"), 3);
+    }
+}
@@ -11,13 +11,25 @@
 //   - Uses Perlin noise for biome-like density variation (forests vs plains)
 //   - Separate noise layers for tree density, rock placement, bush clustering
 //   - Creates more natural, organic distributions instead of pure random
+//
+// [AUDIT]: 10-16-2026 - Per-object RNG streams:
+//   - Each object draws from its own RNG seeded by seed + chunk + kind + index
+//   - Any single object can be rebuilt from its id (regenerate_object)
+//   - Seeds are NOT stable across this change: the same WORLD_SEED produces a different
+//     layout (and different object positions behind the same ids) than earlier builds,
+//     so anything that cached a world by seed must be regenerated
 
 use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
 
 use super::environment::*;
 use super::entity_state::Position;
+use super::world_config::{AssetTable, WorldConfig};
 
 /// Mix seed with chunk coordinates for better RNG distribution
 /// Handles negative coordinates properly and provides better per-chunk separation
@@ -34,19 +46,138 @@ fn mix_seed(base: u64, x: i32, z: i32) -> u64 {
     h
 }
 
+/// Ids that looked generated but failed to parse (malformed or externally inserted)
+static OBJECT_ID_PARSE_FAILURES: AtomicU64 = AtomicU64::new(0);
+
+/// Total object id parse failures since startup
+pub fn object_id_parse_failures() -> u64 {
+    OBJECT_ID_PARSE_FAILURES.load(Ordering::Relaxed)
+}
+
+/// Id prefix for each generated object kind
+fn kind_prefix(kind: EnvironmentObjectType) -> &'static str {
+    match kind {
+        EnvironmentObjectType::Tree => "tree",
+        EnvironmentObjectType::Rock => "rock",
+        EnvironmentObjectType::Bush => "bush",
+        EnvironmentObjectType::Grass => "grass",
+    }
+}
+
+/// Salt per generated object kind, mixed into the per-object RNG seed
+fn kind_salt(kind: EnvironmentObjectType) -> i32 {
+    match kind {
+        EnvironmentObjectType::Tree => 1,
+        EnvironmentObjectType::Rock => 2,
+        EnvironmentObjectType::Bush => 3,
+        EnvironmentObjectType::Grass => 4,
+    }
+}
+
+/// Format a generated object id (`<kind>_<chunk_x>_<chunk_z>_idx_<index>`)
+pub fn format_object_id(kind: EnvironmentObjectType, chunk: &ChunkCoord, index: u32) -> String {
+    format!("{}_{}_{}_idx_{}", kind_prefix(kind), chunk.x, chunk.z, index)
+}
+
+/// Parse a generated object id (`<kind>_<chunk_x>_<chunk_z>_idx_<index>`)
+/// Planted saplings are not generated and return None without counting as a failure;
+/// any other id that doesn't parse is logged and counted
+pub fn parse_object_id(object_id: &str) -> Option<(EnvironmentObjectType, ChunkCoord, u32)> {
+    if object_id.starts_with("sapling_") {
+        return None;
+    }
+    let parsed = (|| {
+        let parts: Vec<&str> = object_id.split('_').collect();
+        let [kind, x, z, "idx", index] = parts.as_slice() else {
+            return None;
+        };
+        let kind = match *kind {
+            "tree" => EnvironmentObjectType::Tree,
+            "rock" => EnvironmentObjectType::Rock,
+            "bush" => EnvironmentObjectType::Bush,
+            "grass" => EnvironmentObjectType::Grass,
+            _ => return None,
+        };
+        let chunk = ChunkCoord { x: x.parse().ok()?, z: z.parse().ok()? };
+        Some((kind, chunk, index.parse().ok()?))
+    })();
+    if parsed.is_none() {
+        OBJECT_ID_PARSE_FAILURES.fetch_add(1, Ordering::Relaxed);
+        warn!(object_id = %object_id, "Failed to parse environment object id");
+    }
+    parsed
+}
+
+/// Deterministic client render hints, drawn last from the object's RNG
+/// so adding hints doesn't change any other generated property
+fn render_hints(rng: &mut ChaCha8Rng, wind_sway: Option<std::ops::Range<f32>>) -> serde_json::Value {
+    let mut hints = serde_json::json!({ "tintSeed": rng.gen::<u32>() });
+    if let Some(range) = wind_sway {
+        // Two decimals is plenty for a shader input and keeps the JSON text exact
+        let sway = (rng.gen_range(range) as f64 * 100.0).round() / 100.0;
+        hints["windSway"] = serde_json::json!(sway);
+    }
+    hints
+}
+
+/// Noise fields sampled by the generator (exposed for debug visualization)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseLayer {
+    TreeDensity,
+    TreeType,
+    Rock,
+    Bush,
+}
+
+/// Coarse per-chunk biome for client ambience (audio, fog)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Biome {
+    Forest,
+    Plains,
+    Rocky,
+}
+
+/// Rock density (0..1) above which a chunk is Rocky, regardless of trees
+const ROCKY_THRESHOLD: f32 = 0.65;
+/// Tree density (0..1) above which a chunk is Forest
+const FOREST_THRESHOLD: f32 = 0.55;
+
+/// Trunk/boulder footprint at scale 1.0 (scaled with the object)
+const TREE_COLLISION_RADIUS: f32 = 0.4;
+const ROCK_COLLISION_RADIUS: f32 = 0.6;
+
+/// Chunk radius generated around spawn at boot and on reseed (11x11 chunks)
+pub const INITIAL_AREA_RADIUS: i32 = 5;
+
 /// Noise-based procedural generation for environment objects
 pub struct EnvironmentGenerator {
     seed: u64,
     chunk_size: f32,
+    /// Kept so the world can be regenerated with a new seed
+    config: WorldConfig,
     // Noise generators for different aspects of world generation
     tree_density_noise: FastNoiseLite,    // Controls where forests vs plains are
     tree_type_noise: FastNoiseLite,       // Controls oak vs pine distribution
     rock_density_noise: FastNoiseLite,    // Controls rocky areas
     bush_cluster_noise: FastNoiseLite,    // Controls bush clustering
+    // Weighted asset variant tables (from WorldConfig)
+    oak_assets: AssetTable,
+    pine_assets: AssetTable,
+    rock_assets: AssetTable,
+    bush_assets: AssetTable,
 }
 
 impl EnvironmentGenerator {
     pub fn new(seed: u64, chunk_size: f32) -> Self {
+        Self::with_config(seed, chunk_size, &WorldConfig::default())
+    }
+
+    /// Create a generator using designer-provided world configuration
+    pub fn with_config(seed: u64, chunk_size: f32, config: &WorldConfig) -> Self {
+        let defaults = WorldConfig::default();
+
         // Tree density noise - Large scale for biomes (forests vs plains)
         let mut tree_density_noise = FastNoiseLite::with_seed(seed as i32);
         tree_density_noise.set_noise_type(Some(NoiseType::Perlin));
@@ -74,66 +205,201 @@ impl EnvironmentGenerator {
         Self {
             seed,
             chunk_size,
+            config: config.clone(),
             tree_density_noise,
             tree_type_noise,
             rock_density_noise,
             bush_cluster_noise,
+            oak_assets: AssetTable::new(&config.oak_variants, &defaults.oak_variants),
+            pine_assets: AssetTable::new(&config.pine_variants, &defaults.pine_variants),
+            rock_assets: AssetTable::new(&config.rock_variants, &defaults.rock_variants),
+            bush_assets: AssetTable::new(&config.bush_variants, &defaults.bush_variants),
         }
     }
 
-    /// Generate objects for a specific chunk
-    /// Uses deterministic RNG based on seed + chunk coords for consistency
-    /// Uses noise for natural biome-like density variation
-    pub fn generate_chunk(&self, chunk_coord: &ChunkCoord) -> Vec<EnvironmentObject> {
-        // Create deterministic RNG from seed and chunk coords
-        // Uses improved mixing for better distribution with negative coordinates
-        let chunk_seed = mix_seed(self.seed, chunk_coord.x, chunk_coord.z);
+    /// Sample a raw noise field at a world position (range [-1, 1])
+    pub fn sample_noise(&self, layer: NoiseLayer, x: f32, z: f32) -> f32 {
+        let noise = match layer {
+            NoiseLayer::TreeDensity => &self.tree_density_noise,
+            NoiseLayer::TreeType => &self.tree_type_noise,
+            NoiseLayer::Rock => &self.rock_density_noise,
+            NoiseLayer::Bush => &self.bush_cluster_noise,
+        };
+        noise.get_noise_2d(x, z)
+    }
 
-        let mut rng = ChaCha8Rng::seed_from_u64(chunk_seed);
-        let mut objects = Vec::new();
+    /// Sample a `width` x `height` grid of noise values starting at (x0, z0), `step` world units apart
+    /// Rows are indexed by z, columns by x
+    pub fn sample_noise_grid(&self, layer: NoiseLayer, x0: f32, z0: f32, width: usize, height: usize, step: f32) -> Vec<Vec<f32>> {
+        (0..height)
+            .map(|row| {
+                let z = z0 + row as f32 * step;
+                (0..width)
+                    .map(|col| self.sample_noise(layer, x0 + col as f32 * step, z))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// World seed
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Chunk edge length in world units
+    pub fn chunk_size(&self) -> f32 {
+        self.chunk_size
+    }
 
+    /// Same configuration with a different seed
+    pub fn with_seed(&self, seed: u64) -> Self {
+        Self::with_config(seed, self.chunk_size, &self.config)
+    }
+
+    /// RNG for one object, independent of every other object in the chunk
+    fn object_rng(&self, chunk: &ChunkCoord, kind: EnvironmentObjectType, index: u32) -> ChaCha8Rng {
+        let chunk_seed = mix_seed(self.seed, chunk.x, chunk.z);
+        ChaCha8Rng::seed_from_u64(mix_seed(chunk_seed, kind_salt(kind), index as i32))
+    }
+
+    /// Tree, rock and bush density of a chunk, each in [0, 1]
+    fn chunk_densities(&self, chunk_coord: &ChunkCoord) -> (f32, f32, f32) {
         // Calculate chunk world position (center of chunk for noise sampling)
-        let chunk_x = chunk_coord.x as f32 * self.chunk_size;
-        let chunk_z = chunk_coord.z as f32 * self.chunk_size;
-        let chunk_center_x = chunk_x + self.chunk_size * 0.5;
-        let chunk_center_z = chunk_z + self.chunk_size * 0.5;
+        let chunk_center_x = chunk_coord.x as f32 * self.chunk_size + self.chunk_size * 0.5;
+        let chunk_center_z = chunk_coord.z as f32 * self.chunk_size + self.chunk_size * 0.5;
 
         // Sample noise at chunk center to determine biome characteristics
         // Noise returns values in range [-1, 1], we map to [0, 1]
-        let tree_density = (self.tree_density_noise.get_noise_2d(chunk_center_x, chunk_center_z) + 1.0) * 0.5;
-        let rock_density = (self.rock_density_noise.get_noise_2d(chunk_center_x, chunk_center_z) + 1.0) * 0.5;
-        let bush_density = (self.bush_cluster_noise.get_noise_2d(chunk_center_x, chunk_center_z) + 1.0) * 0.5;
-
-        // Use noise to modulate object counts
-        // Dense forest: 10-20 trees, Plains: 2-6 trees
-        let tree_count = (2.0 + tree_density * 18.0) as u32;
-        for i in 0..tree_count {
-            let object = self.generate_tree(&mut rng, chunk_coord, i, chunk_x, chunk_z);
-            objects.push(object);
+        let density = |layer| (self.sample_noise(layer, chunk_center_x, chunk_center_z) + 1.0) * 0.5;
+        (density(NoiseLayer::TreeDensity), density(NoiseLayer::Rock), density(NoiseLayer::Bush))
+    }
+
+    /// Classify a chunk's biome from the same density noise that drives object counts
+    /// Rocky wins over Forest so boulder fields read as rocky even under tree cover
+    pub fn classify_biome(&self, chunk: &ChunkCoord) -> Biome {
+        let (tree_density, rock_density, _) = self.chunk_densities(chunk);
+        if rock_density >= ROCKY_THRESHOLD {
+            Biome::Rocky
+        } else if tree_density >= FOREST_THRESHOLD {
+            Biome::Forest
+        } else {
+            Biome::Plains
         }
+    }
 
-        // Rocky areas: 6-12 rocks, Normal: 0-3 rocks
-        let rock_count = (rock_density * 12.0) as u32;
-        for i in 0..rock_count {
-            let object = self.generate_rock(&mut rng, chunk_coord, i, chunk_x, chunk_z);
-            objects.push(object);
+    /// Number of trees, rocks, bushes and grass patches in a chunk
+    fn object_counts(&self, chunk_coord: &ChunkCoord) -> (u32, u32, u32, u32) {
+        // Create deterministic RNG from seed and chunk coords
+        // Uses improved mixing for better distribution with negative coordinates
+        let chunk_seed = mix_seed(self.seed, chunk_coord.x, chunk_coord.z);
+        let mut rng = ChaCha8Rng::seed_from_u64(chunk_seed);
+
+        let (tree_density, rock_density, bush_density) = self.chunk_densities(chunk_coord);
+
+        (
+            // Dense forest: 10-20 trees, Plains: 2-6 trees
+            (2.0 + tree_density * 18.0) as u32,
+            // Rocky areas: 6-12 rocks, Normal: 0-3 rocks
+            (rock_density * 12.0) as u32,
+            // Bush clusters: 15-25 bushes, Sparse: 3-8 bushes
+            (3.0 + bush_density * 22.0) as u32,
+            // Grass is fairly uniform across all areas (10-30 per chunk)
+            rng.gen_range(10..=30),
+        )
+    }
+
+    /// Build one object of a chunk
+    fn generate_object(&self, kind: EnvironmentObjectType, chunk: &ChunkCoord, index: u32) -> EnvironmentObject {
+        let chunk_x = chunk.x as f32 * self.chunk_size;
+        let chunk_z = chunk.z as f32 * self.chunk_size;
+        let mut rng = self.object_rng(chunk, kind, index);
+        let mut object = match kind {
+            EnvironmentObjectType::Tree => self.generate_tree(&mut rng, chunk, index, chunk_x, chunk_z),
+            EnvironmentObjectType::Rock => self.generate_rock(&mut rng, chunk, index, chunk_x, chunk_z),
+            EnvironmentObjectType::Bush => self.generate_bush(&mut rng, chunk, index, chunk_x, chunk_z),
+            EnvironmentObjectType::Grass => self.generate_grass(&mut rng, chunk, index, chunk_x, chunk_z),
+        };
+        if let Some(resource_type) = self.resource_override(kind, chunk) {
+            object.resource_type = resource_type;
         }
+        object
+    }
 
-        // Bush clusters: 15-25 bushes, Sparse: 3-8 bushes
-        let bush_count = (3.0 + bush_density * 22.0) as u32;
-        for i in 0..bush_count {
-            let object = self.generate_bush(&mut rng, chunk_coord, i, chunk_x, chunk_z);
-            objects.push(object);
+    /// Biome-specific resource for an object type (from WorldConfig::resource_overrides)
+    fn resource_override(&self, kind: EnvironmentObjectType, chunk: &ChunkCoord) -> Option<ResourceType> {
+        if self.config.resource_overrides.is_empty() {
+            return None;
         }
+        self.config.resource_overrides.get(&self.classify_biome(chunk))?.get(&kind).copied()
+    }
 
-        // Grass is fairly uniform across all areas (10-30 per chunk)
-        let grass_count = rng.gen_range(10..=30);
-        for i in 0..grass_count {
-            let object = self.generate_grass(&mut rng, chunk_coord, i, chunk_x, chunk_z);
-            objects.push(object);
+    /// Minimum same-type spacing for an object type (0 = objects may overlap)
+    fn min_spacing(&self, kind: EnvironmentObjectType) -> f32 {
+        self.config.min_object_spacing.get(&kind).copied().unwrap_or(0.0).max(0.0)
+    }
+
+    /// Whether a position falls inside a configured exclusion zone
+    fn is_excluded(&self, position: &Position) -> bool {
+        self.config.exclusion_zones.iter().any(|zone| zone.contains(position))
+    }
+
+    /// The first `count` objects of a type in a chunk, minus candidates inside an exclusion zone
+    /// or within the minimum spacing of an earlier kept one (rejection sampling, so ids may have gaps)
+    fn spaced_objects(&self, kind: EnvironmentObjectType, chunk: &ChunkCoord, count: u32) -> Vec<EnvironmentObject> {
+        let spacing = self.min_spacing(kind);
+        let mut kept: Vec<EnvironmentObject> = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            let object = self.generate_object(kind, chunk, index);
+            if self.is_excluded(&object.position) {
+                continue;
+            }
+            if spacing > 0.0 && kept.iter().any(|other| other.position.distance_to(&object.position) < spacing) {
+                continue;
+            }
+            kept.push(object);
         }
+        kept
+    }
 
-        objects
+    /// Generate objects for a specific chunk
+    /// Uses deterministic per-object RNG based on seed + chunk coords for consistency
+    /// Uses noise for natural biome-like density variation
+    pub fn generate_chunk(&self, chunk_coord: &ChunkCoord) -> Vec<EnvironmentObject> {
+        let (tree_count, rock_count, bush_count, grass_count) = self.object_counts(chunk_coord);
+
+        [
+            (EnvironmentObjectType::Tree, tree_count),
+            (EnvironmentObjectType::Rock, rock_count),
+            (EnvironmentObjectType::Bush, bush_count),
+            (EnvironmentObjectType::Grass, grass_count),
+        ]
+            .into_iter()
+            .flat_map(|(kind, count)| self.spaced_objects(kind, chunk_coord, count))
+            .collect()
+    }
+
+    /// Deterministically rebuild a single generated object from its id
+    /// Returns None for ids the generator didn't produce (e.g. planted saplings)
+    pub fn regenerate_object(&self, object_id: &str) -> Option<EnvironmentObject> {
+        let (kind, chunk, index) = parse_object_id(object_id)?;
+        let (trees, rocks, bushes, grass) = self.object_counts(&chunk);
+        let count = match kind {
+            EnvironmentObjectType::Tree => trees,
+            EnvironmentObjectType::Rock => rocks,
+            EnvironmentObjectType::Bush => bushes,
+            EnvironmentObjectType::Grass => grass,
+        };
+        if index >= count {
+            return None;
+        }
+        if self.min_spacing(kind) > 0.0 {
+            // Whether this index survived spacing depends on the objects before it
+            return self
+                .spaced_objects(kind, &chunk, index + 1)
+                .pop()
+                .filter(|object| object.object_id == format_object_id(kind, &chunk, index));
+        }
+        Some(self.generate_object(kind, &chunk, index)).filter(|object| !self.is_excluded(&object.position))
     }
 
     fn generate_tree(&self, rng: &mut ChaCha8Rng, chunk: &ChunkCoord, index: u32, chunk_x: f32, chunk_z: f32) -> EnvironmentObject {
@@ -147,23 +413,27 @@ impl EnvironmentGenerator {
         let tree_type_value = self.tree_type_noise.get_noise_2d(position.x, position.z);
         let asset_name = if tree_type_value > 0.0 {
             // Pine forest (higher noise values)
-            if rng.gen_bool(0.5) { "Tree_Pine_01" } else { "Tree_Pine_02" }
+            self.pine_assets.pick(rng)
         } else {
             // Oak forest (lower noise values)
-            if rng.gen_bool(0.5) { "Tree_Oak_01" } else { "Tree_Oak_02" }
+            self.oak_assets.pick(rng)
         }.to_string();
 
+        // Draw order (rotation, then scale) is part of the deterministic layout
+        let rotation_y = rng.gen_range(0.0..360.0);
+        let scale = rng.gen_range(0.8..1.2);
+
         EnvironmentObject {
-            object_id: format!("tree_{}_{}_idx_{}", chunk.x, chunk.z, index),
+            object_id: format_object_id(EnvironmentObjectType::Tree, chunk, index),
             asset_name,
             position,
             rotation: Quaternion {
                 x: 0.0,
-                y: rng.gen_range(0.0..360.0),
+                y: rotation_y,
                 z: 0.0,
                 w: 1.0,
             },
-            scale: Scale::uniform(rng.gen_range(0.8..1.2)),
+            scale: Scale::uniform(scale),
             object_type: EnvironmentObjectType::Tree,
             resource_type: ResourceType::Wood,
             resource_amount: rng.gen_range(3..=8),
@@ -171,6 +441,9 @@ impl EnvironmentGenerator {
             is_harvested: false,
             harvested_at: None,
             respawn_time_seconds: Some(300), // 5 minutes
+            harvest_count: 0,
+            collision_radius: TREE_COLLISION_RADIUS * scale,
+            metadata: Some(render_hints(rng, Some(0.2..0.6))),
         }
     }
 
@@ -181,20 +454,23 @@ impl EnvironmentGenerator {
             z: chunk_z + rng.gen_range(0.0..self.chunk_size),
         };
 
-        let rock_variants = ["Rock_01", "Rock_02", "Rock_03"];
-        let asset_name = rock_variants[rng.gen_range(0..rock_variants.len())].to_string();
+        let asset_name = self.rock_assets.pick(rng).to_string();
+
+        // Draw order (rotation, then scale) is part of the deterministic layout
+        let rotation_y = rng.gen_range(0.0..360.0);
+        let scale = rng.gen_range(0.9..1.3);
 
         EnvironmentObject {
-            object_id: format!("rock_{}_{}_idx_{}", chunk.x, chunk.z, index),
+            object_id: format_object_id(EnvironmentObjectType::Rock, chunk, index),
             asset_name,
             position,
             rotation: Quaternion {
                 x: 0.0,
-                y: rng.gen_range(0.0..360.0),
+                y: rotation_y,
                 z: 0.0,
                 w: 1.0,
             },
-            scale: Scale::uniform(rng.gen_range(0.9..1.3)),
+            scale: Scale::uniform(scale),
             object_type: EnvironmentObjectType::Rock,
             resource_type: ResourceType::Stone,
             resource_amount: rng.gen_range(2..=6),
@@ -202,6 +478,9 @@ impl EnvironmentGenerator {
             is_harvested: false,
             harvested_at: None,
             respawn_time_seconds: Some(600), // 10 minutes
+            harvest_count: 0,
+            collision_radius: ROCK_COLLISION_RADIUS * scale,
+            metadata: Some(render_hints(rng, None)),
         }
     }
 
@@ -212,11 +491,10 @@ impl EnvironmentGenerator {
             z: chunk_z + rng.gen_range(0.0..self.chunk_size),
         };
 
-        let bush_variants = ["Bush_01", "Bush_02"];
-        let asset_name = bush_variants[rng.gen_range(0..bush_variants.len())].to_string();
+        let asset_name = self.bush_assets.pick(rng).to_string();
 
         EnvironmentObject {
-            object_id: format!("bush_{}_{}_idx_{}", chunk.x, chunk.z, index),
+            object_id: format_object_id(EnvironmentObjectType::Bush, chunk, index),
             asset_name,
             position,
             rotation: Quaternion {
@@ -233,6 +511,9 @@ impl EnvironmentGenerator {
             is_harvested: false,
             harvested_at: None,
             respawn_time_seconds: Some(180), // 3 minutes
+            harvest_count: 0,
+            collision_radius: 0.0, // Walk-through foliage
+            metadata: Some(render_hints(rng, Some(0.4..0.8))),
         }
     }
 
@@ -244,7 +525,7 @@ impl EnvironmentGenerator {
         };
 
         EnvironmentObject {
-            object_id: format!("grass_{}_{}_idx_{}", chunk.x, chunk.z, index),
+            object_id: format_object_id(EnvironmentObjectType::Grass, chunk, index),
             asset_name: "Grass_Patch_01".to_string(),
             position,
             rotation: Quaternion::default(),
@@ -256,6 +537,9 @@ impl EnvironmentGenerator {
             is_harvested: false,
             harvested_at: None,
             respawn_time_seconds: Some(120), // 2 minutes
+            harvest_count: 0,
+            collision_radius: 0.0,
+            metadata: Some(render_hints(rng, Some(0.8..1.0))),
         }
     }
 
@@ -278,6 +562,92 @@ impl EnvironmentGenerator {
     }
 }
 
+/// Progress of the background world boot (initial area generation)
+#[derive(Debug, Default)]
+pub struct GenerationProgress {
+    chunks_total: AtomicUsize,
+    chunks_done: AtomicUsize,
+    complete: AtomicBool,
+    /// Set by a reseed; the boot stops before its next chunk
+    cancelled: AtomicBool,
+    finished: tokio::sync::Notify,
+}
+
+/// Snapshot of `GenerationProgress` for readiness reporting
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct GenerationStatus {
+    pub complete: bool,
+    pub chunks_done: usize,
+    pub chunks_total: usize,
+}
+
+impl GenerationProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Progress for a world that needs no boot generation (e.g. tests)
+    pub fn completed() -> Self {
+        let progress = Self::default();
+        progress.complete.store(true, Ordering::Release);
+        progress
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete.load(Ordering::Acquire)
+    }
+
+    /// Stop the boot generation and wait until it has let go of the world
+    /// (returns at once if it already finished or never started)
+    pub async fn cancel_and_wait(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        let finished = self.finished.notified();
+        tokio::pin!(finished);
+        finished.as_mut().enable();
+        if !self.is_complete() {
+            finished.await;
+        }
+    }
+
+    pub fn status(&self) -> GenerationStatus {
+        GenerationStatus {
+            complete: self.is_complete(),
+            chunks_done: self.chunks_done.load(Ordering::Relaxed),
+            chunks_total: self.chunks_total.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Generate the area around `center` chunk by chunk without blocking the runtime
+/// Chunks nearest the center go first; chunks generated lazily in the meantime are skipped
+pub async fn generate_area_in_background(
+    generator: Arc<EnvironmentGenerator>,
+    environment_manager: Arc<EnvironmentManager>,
+    center: ChunkCoord,
+    radius: i32,
+    progress: Arc<GenerationProgress>,
+) {
+    let mut chunks = center.neighbors(radius);
+    chunks.sort_by_key(|c| (c.x - center.x).abs().max((c.z - center.z).abs()));
+    progress.chunks_total.store(chunks.len(), Ordering::Relaxed);
+    info!(chunks = chunks.len(), "Generating initial world area in background");
+
+    let mut objects = 0;
+    for chunk in chunks {
+        if progress.cancelled.load(Ordering::Acquire) {
+            info!(objects, "Initial world generation cancelled");
+            break;
+        }
+        objects += environment_manager.ensure_chunk_generated(&generator, &chunk);
+        progress.chunks_done.fetch_add(1, Ordering::Relaxed);
+        tokio::task::yield_now().await;
+    }
+
+    progress.complete.store(true, Ordering::Release);
+    progress.finished.notify_waiters();
+    info!("Generated {} initial environment objects", objects);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -306,4 +676,254 @@ mod tests {
 
         assert_ne!(objects1[0].object_id, objects2[0].object_id);
     }
+
+    #[test]
+    fn test_noise_grid_dimensions_and_range() {
+        let gen = EnvironmentGenerator::new(12345, 50.0);
+        for layer in [NoiseLayer::TreeDensity, NoiseLayer::TreeType, NoiseLayer::Rock, NoiseLayer::Bush] {
+            let grid = gen.sample_noise_grid(layer, -100.0, 40.0, 16, 9, 5.0);
+            assert_eq!(grid.len(), 9);
+            assert!(grid.iter().all(|row| row.len() == 16));
+            assert!(grid.iter().flatten().all(|v| (-1.0..=1.0).contains(v)));
+        }
+    }
+
+    #[test]
+    fn test_classify_biome_follows_density() {
+        let gen = EnvironmentGenerator::new(12345, 50.0);
+        let chunks = ChunkCoord { x: 0, z: 0 }.neighbors(10);
+        let count = |chunk: &ChunkCoord, kind| {
+            gen.generate_chunk(chunk).iter().filter(|o| o.object_type == kind).count()
+        };
+
+        // Densest rock field in the area is Rocky
+        let rockiest = chunks.iter().max_by_key(|c| count(c, EnvironmentObjectType::Rock)).unwrap();
+        assert!(count(rockiest, EnvironmentObjectType::Rock) >= 8);
+        assert_eq!(gen.classify_biome(rockiest), Biome::Rocky);
+
+        // Densest forest outside rocky ground is Forest
+        let forest = chunks
+            .iter()
+            .filter(|c| gen.classify_biome(c) != Biome::Rocky)
+            .max_by_key(|c| count(c, EnvironmentObjectType::Tree))
+            .unwrap();
+        assert!(count(forest, EnvironmentObjectType::Tree) >= 12);
+        assert_eq!(gen.classify_biome(forest), Biome::Forest);
+
+        let sparse = chunks
+            .iter()
+            .min_by_key(|c| count(c, EnvironmentObjectType::Tree) + count(c, EnvironmentObjectType::Rock))
+            .unwrap();
+        assert_eq!(gen.classify_biome(sparse), Biome::Plains);
+    }
+
+    #[test]
+    fn test_resource_overrides_per_biome() {
+        use std::collections::HashMap;
+        let config = WorldConfig {
+            resource_overrides: HashMap::from([(Biome::Rocky, HashMap::from([(EnvironmentObjectType::Bush, ResourceType::Herbs)]))]),
+            ..WorldConfig::default()
+        };
+        let gen = EnvironmentGenerator::with_config(12345, 50.0, &config);
+        let chunks = ChunkCoord { x: 0, z: 0 }.neighbors(10);
+        let bush_resources = |biome: Biome| {
+            let chunk = chunks.iter().find(|c| gen.classify_biome(c) == biome).expect("biome in test area");
+            gen.generate_chunk(chunk)
+                .into_iter()
+                .filter(|o| o.object_type == EnvironmentObjectType::Bush)
+                .map(|o| o.resource_type)
+                .collect::<Vec<_>>()
+        };
+
+        let forest = bush_resources(Biome::Forest);
+        assert!(!forest.is_empty() && forest.iter().all(|r| *r == ResourceType::Berries));
+        let rocky = bush_resources(Biome::Rocky);
+        assert!(!rocky.is_empty() && rocky.iter().all(|r| *r == ResourceType::Herbs));
+
+        // Regenerated objects get the same override
+        let rocky_chunk = chunks.iter().find(|c| gen.classify_biome(c) == Biome::Rocky).unwrap();
+        let bush = format_object_id(EnvironmentObjectType::Bush, rocky_chunk, 0);
+        assert_eq!(gen.regenerate_object(&bush).unwrap().resource_type, ResourceType::Herbs);
+    }
+
+    #[test]
+    fn test_weighted_assets_deterministic_for_seed() {
+        use crate::game::world_config::WeightedAsset;
+
+        let config = WorldConfig {
+            rock_variants: vec![
+                WeightedAsset::new("Rock_01", 0.7),
+                WeightedAsset::new("Rock_02", 0.3),
+            ],
+            ..WorldConfig::default()
+        };
+        let gen1 = EnvironmentGenerator::with_config(12345, 50.0, &config);
+        let gen2 = EnvironmentGenerator::with_config(12345, 50.0, &config);
+        let chunk = ChunkCoord { x: -2, z: 5 };
+
+        let names1: Vec<_> = gen1.generate_chunk(&chunk).into_iter().map(|o| o.asset_name).collect();
+        let names2: Vec<_> = gen2.generate_chunk(&chunk).into_iter().map(|o| o.asset_name).collect();
+
+        assert_eq!(names1, names2);
+        assert!(names1.iter().all(|n| n != "Rock_03"));
+    }
+
+    #[test]
+    fn test_regenerate_object_matches_generate_chunk() {
+        let gen = EnvironmentGenerator::new(12345, 50.0);
+        assert_eq!(gen.seed(), 12345);
+        assert_eq!(gen.chunk_size(), 50.0);
+
+        for chunk in [ChunkCoord { x: 0, z: 0 }, ChunkCoord { x: -3, z: 7 }] {
+            for object in gen.generate_chunk(&chunk) {
+                let rebuilt = gen.regenerate_object(&object.object_id).unwrap();
+                assert_eq!(rebuilt.asset_name, object.asset_name);
+                assert_eq!(rebuilt.position, object.position);
+                assert_eq!(rebuilt.rotation.y, object.rotation.y);
+                assert_eq!(rebuilt.resource_amount, object.resource_amount);
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_object_spacing_between_trees() {
+        use std::collections::HashMap;
+        let spacing = 8.0;
+        let config = WorldConfig {
+            min_object_spacing: HashMap::from([(EnvironmentObjectType::Tree, spacing)]),
+            ..WorldConfig::default()
+        };
+        let spaced = EnvironmentGenerator::with_config(12345, 50.0, &config);
+        let unspaced = EnvironmentGenerator::with_config(12345, 50.0, &WorldConfig {
+            min_object_spacing: HashMap::new(),
+            ..WorldConfig::default()
+        });
+        let trees = |gen: &EnvironmentGenerator, chunk: &ChunkCoord| -> Vec<EnvironmentObject> {
+            gen.generate_chunk(chunk).into_iter().filter(|o| o.object_type == EnvironmentObjectType::Tree).collect()
+        };
+
+        let origin = ChunkCoord { x: 0, z: 0 };
+        let mut dropped = 0;
+        for chunk in origin.neighbors(2) {
+            let kept = trees(&spaced, &chunk);
+            for (i, a) in kept.iter().enumerate() {
+                for b in &kept[i + 1..] {
+                    assert!(a.position.distance_to(&b.position) >= spacing, "{} and {} overlap", a.object_id, b.object_id);
+                }
+            }
+            for tree in trees(&unspaced, &chunk) {
+                let rebuilt = spaced.regenerate_object(&tree.object_id);
+                assert_eq!(rebuilt.is_some(), kept.iter().any(|k| k.object_id == tree.object_id));
+                if rebuilt.is_none() {
+                    dropped += 1;
+                }
+            }
+        }
+        assert!(dropped > 0, "spacing should reject some candidates at this density");
+    }
+
+    #[test]
+    fn test_exclusion_zone_keeps_area_clear() {
+        use crate::game::world_config::ExclusionZone;
+        let road = ExclusionZone::Rect { min_x: 0.0, min_z: 0.0, max_x: 25.0, max_z: 50.0 };
+        let config = WorldConfig { exclusion_zones: vec![road], ..WorldConfig::default() };
+        let gen = EnvironmentGenerator::with_config(12345, 50.0, &config);
+        let chunk = ChunkCoord { x: 0, z: 0 };
+
+        let objects = gen.generate_chunk(&chunk);
+        assert!(objects.iter().all(|o| !road.contains(&o.position)));
+        assert!(objects.iter().any(|o| o.position.x > 25.0), "the rest of the chunk is still populated");
+
+        // Respawns agree with generation: objects that would land in the zone don't exist
+        for object in EnvironmentGenerator::new(12345, 50.0).generate_chunk(&chunk) {
+            let rebuilt = gen.regenerate_object(&object.object_id);
+            if road.contains(&object.position) {
+                assert!(rebuilt.is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_regenerate_object_rejects_unknown_ids() {
+        let gen = EnvironmentGenerator::new(12345, 50.0);
+        assert!(gen.regenerate_object("sapling_01JABCDEF").is_none());
+        assert!(gen.regenerate_object("tree_0_0_idx_9999").is_none());
+        assert!(gen.regenerate_object("house_0_0_idx_0").is_none());
+    }
+
+    #[test]
+    fn test_generated_objects_carry_deterministic_metadata() {
+        let chunk = ChunkCoord { x: 2, z: -1 };
+        let a = EnvironmentGenerator::new(777, 50.0).generate_chunk(&chunk);
+        let b = EnvironmentGenerator::new(777, 50.0).generate_chunk(&chunk);
+
+        for (left, right) in a.iter().zip(&b) {
+            let metadata = left.metadata.as_ref().expect("generated objects have render hints");
+            assert!(metadata["tintSeed"].is_u64());
+            assert_eq!(metadata["windSway"].is_number(), left.object_type != EnvironmentObjectType::Rock);
+            assert_eq!(left.metadata, right.metadata);
+        }
+    }
+
+    #[test]
+    fn test_parse_object_id() {
+        assert_eq!(
+            parse_object_id("tree_-3_12_idx_7"),
+            Some((EnvironmentObjectType::Tree, ChunkCoord { x: -3, z: 12 }, 7))
+        );
+        let chunk = ChunkCoord { x: 4, z: -1 };
+        let id = format_object_id(EnvironmentObjectType::Grass, &chunk, 0);
+        assert_eq!(parse_object_id(&id), Some((EnvironmentObjectType::Grass, chunk, 0)));
+
+        let before = object_id_parse_failures();
+        for malformed in ["", "tree", "tree_1_2_idx", "tree_1_2_idx_-1", "tree_a_2_idx_0", "tree_1_2_ix_0", "house_0_0_idx_0", "tree_1_2_idx_0_extra"] {
+            assert!(parse_object_id(malformed).is_none(), "{malformed} should not parse");
+        }
+        // Counter is process-wide, other tests may bump it concurrently
+        assert!(object_id_parse_failures() >= before + 8);
+
+        assert!(parse_object_id("sapling_01JABCDEF").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_background_generation_skips_lazy_chunks() {
+        let gen = Arc::new(EnvironmentGenerator::new(12345, 50.0));
+        let manager = Arc::new(EnvironmentManager::new(50.0, 3, 10.0));
+        let progress = Arc::new(GenerationProgress::new());
+        let center = ChunkCoord { x: 0, z: 0 };
+
+        // A player arrived early and forced the spawn chunk
+        let lazy = manager.ensure_chunk_generated(&gen, &center);
+        assert_eq!(lazy, gen.generate_chunk(&center).len());
+        assert_eq!(manager.ensure_chunk_generated(&gen, &center), 0);
+
+        generate_area_in_background(gen.clone(), manager.clone(), center, 1, progress.clone()).await;
+        let status = progress.status();
+        assert!(status.complete);
+        assert_eq!((status.chunks_done, status.chunks_total), (9, 9));
+        let expected: usize = center.neighbors(1).iter().map(|c| gen.generate_chunk(c).len()).sum();
+        assert_eq!(manager.get_objects_in_chunks(&center.neighbors(1)).len(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_boot_stops_before_next_chunk() {
+        let gen = Arc::new(EnvironmentGenerator::new(12345, 50.0));
+        let manager = Arc::new(EnvironmentManager::new(50.0, 3, 10.0));
+        let progress = Arc::new(GenerationProgress::new());
+        let center = ChunkCoord { x: 0, z: 0 };
+
+        let boot = tokio::spawn(generate_area_in_background(gen, manager.clone(), center, 2, progress.clone()));
+        tokio::task::yield_now().await;
+        progress.cancel_and_wait().await;
+
+        let status = progress.status();
+        assert!(status.complete);
+        assert!(status.chunks_done < status.chunks_total);
+        let loaded = manager.get_stats().loaded_chunks;
+        boot.await.unwrap();
+        assert_eq!(manager.get_stats().loaded_chunks, loaded);
+
+        // Waiting on a finished boot returns at once
+        progress.cancel_and_wait().await;
+    }
 }
@@ -0,0 +1,77 @@
+// src/game/events.rs
+// In-process game event stream for observers (quests, achievements, analytics)
+//
+// Managers publish events after a change has been applied; subscribers get their own
+// receiver and see every event published after they subscribed. Publishing never blocks
+// and is a no-op with no subscribers. A subscriber that falls more than the channel
+// capacity behind skips the oldest events (RecvError::Lagged).
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::entity_state::{EntityType, Position};
+use super::environment::ResourceType;
+
+/// Default number of events buffered per subscriber
+pub const DEFAULT_EVENT_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// A player successfully harvested an environment object
+    Harvested {
+        player_id: String,
+        object_id: String,
+        resource_type: ResourceType,
+        amount: u32,
+    },
+    /// An entity's position changed (streamed to dashboards by /stream/entities)
+    EntityMoved {
+        entity_id: String,
+        entity_type: EntityType,
+        position: Position,
+    },
+    /// An entity moved into a watched area (`EntityStateManager::watch_area`)
+    EnteredArea {
+        entity_id: String,
+        area_id: String,
+    },
+    /// An entity moved out of (or was removed while inside) a watched area
+    LeftArea {
+        entity_id: String,
+        area_id: String,
+    },
+}
+
+/// Cloneable handle; clones publish to and subscribe from the same stream
+#[derive(Debug, Clone)]
+pub struct GameEventBus {
+    tx: broadcast::Sender<GameEvent>,
+}
+
+impl GameEventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Self { tx }
+    }
+
+    pub fn publish(&self, event: GameEvent) {
+        // Err only means nobody is subscribed
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<GameEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Live subscribers (receivers that haven't been dropped)
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+impl Default for GameEventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
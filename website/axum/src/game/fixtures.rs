@@ -0,0 +1,26 @@
+// src/game/fixtures.rs
+// Sample world objects shared by the unit tests here and in the server binary
+
+use super::entity_state::Position;
+use super::environment::{EnvironmentObject, EnvironmentObjectType, Quaternion, ResourceType, Scale};
+
+/// Harvestable oak at ground level: 5 wood, 3s harvest, 300s respawn, 0.5 collision radius
+pub fn test_object(object_id: &str, x: f32, z: f32) -> EnvironmentObject {
+    EnvironmentObject {
+        object_id: object_id.to_string(),
+        asset_name: "Tree_Oak_01".to_string(),
+        position: Position::new(x, 0.0, z),
+        rotation: Quaternion::default(),
+        scale: Scale::default(),
+        object_type: EnvironmentObjectType::Tree,
+        resource_type: ResourceType::Wood,
+        resource_amount: 5,
+        harvest_time: 3.0,
+        is_harvested: false,
+        harvested_at: None,
+        respawn_time_seconds: Some(300),
+        harvest_count: 0,
+        collision_radius: 0.5,
+        metadata: None,
+    }
+}
@@ -0,0 +1,127 @@
+// src/game/harvest_audit.rs
+// In-memory audit trail of harvest attempts, for investigating exploits
+//
+// EnvironmentManager records every harvest request (granted or rejected) with the
+// distance the player claimed to be at. The log is a fixed-size ring buffer: once full,
+// the oldest entries are dropped. Admins query it via /admin/audit/harvests.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use super::environment::{HarvestErrorCode, HarvestObjectResponse, ResourceType};
+
+/// Entries kept before the oldest are dropped
+pub const DEFAULT_HARVEST_AUDIT_CAPACITY: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HarvestAuditResult {
+    Granted,
+    Rejected,
+}
+
+/// One harvest attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct HarvestAuditEntry {
+    /// Unix timestamp (milliseconds)
+    pub timestamp: i64,
+    pub player_id: String,
+    pub object_id: String,
+    pub result: HarvestAuditResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<HarvestErrorCode>,
+    /// Distance from the claimed player position to the object (None if the object doesn't exist)
+    pub distance: Option<f32>,
+    pub resource_type: ResourceType,
+    pub amount: u32,
+}
+
+impl HarvestAuditEntry {
+    pub fn from_response(response: &HarvestObjectResponse, distance: Option<f32>) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            player_id: response.player_id.clone(),
+            object_id: response.object_id.clone(),
+            result: if response.success { HarvestAuditResult::Granted } else { HarvestAuditResult::Rejected },
+            error_code: response.error_code,
+            distance,
+            resource_type: response.resource_type,
+            amount: response.resource_amount,
+        }
+    }
+}
+
+/// Bounded, shared harvest audit log (cheap to clone)
+#[derive(Debug, Clone)]
+pub struct HarvestAuditLog {
+    entries: Arc<Mutex<VecDeque<HarvestAuditEntry>>>,
+    capacity: usize,
+}
+
+impl Default for HarvestAuditLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_HARVEST_AUDIT_CAPACITY)
+    }
+}
+
+impl HarvestAuditLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity.min(1024)))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn record(&self, entry: HarvestAuditEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Up to `limit` most recent entries, newest first, optionally for one player
+    pub fn recent(&self, player_id: Option<&str>, limit: usize) -> Vec<HarvestAuditEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .rev()
+            .filter(|e| player_id.is_none_or(|id| e.player_id == id))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(player_id: &str, object_id: &str) -> HarvestAuditEntry {
+        HarvestAuditEntry {
+            timestamp: 0,
+            player_id: player_id.to_string(),
+            object_id: object_id.to_string(),
+            result: HarvestAuditResult::Granted,
+            error_code: None,
+            distance: Some(1.0),
+            resource_type: ResourceType::Wood,
+            amount: 1,
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer_drops_oldest_and_filters_by_player() {
+        let log = HarvestAuditLog::new(3);
+        log.record(entry("a", "tree_1"));
+        log.record(entry("b", "tree_2"));
+        log.record(entry("a", "tree_3"));
+        log.record(entry("a", "tree_4"));
+
+        let all: Vec<_> = log.recent(None, 10).into_iter().map(|e| e.object_id).collect();
+        assert_eq!(all, ["tree_4", "tree_3", "tree_2"]);
+        let a: Vec<_> = log.recent(Some("a"), 1).into_iter().map(|e| e.object_id).collect();
+        assert_eq!(a, ["tree_4"]);
+    }
+}
@@ -0,0 +1,71 @@
+// src/game/harvest_policy.rs
+// Game-mode hook for harvest validation
+//
+// EnvironmentManager handles the structural checks (object exists, not already harvested)
+// and then asks a HarvestPolicy whether this player may harvest this object. The standard
+// policy enforces spawn protection, harvest range and configured tool requirements; other
+// modes (creative, hardcore) inject their own rules with `EnvironmentManager::with_harvest_policy`.
+
+use std::collections::HashMap;
+use tracing::warn;
+
+use super::entity_state::Position;
+use super::environment::{EnvironmentObject, EnvironmentObjectType, HarvestErrorCode, SpawnProtection};
+use super::tools::tool_spec;
+
+/// Everything a policy may consult besides the player and object
+#[derive(Debug, Clone, Copy)]
+pub struct HarvestContext<'a> {
+    pub player_position: &'a Position,
+    pub tool: Option<&'a str>,
+    /// Harvest range for this object's type (override or the global max)
+    pub max_range: f32,
+    pub spawn_protection: Option<SpawnProtection>,
+}
+
+/// Decides whether a harvest may go ahead
+pub trait HarvestPolicy: Send + Sync {
+    fn validate(&self, player_id: &str, object: &EnvironmentObject, context: &HarvestContext) -> Result<(), HarvestErrorCode>;
+}
+
+/// Default rules: no harvesting inside spawn protection, only within harvest range, and
+/// only with a good enough tool for object types that require one
+#[derive(Debug, Default, Clone)]
+pub struct StandardHarvestPolicy {
+    /// Minimum tool tier per object type (absent types can be harvested bare-handed)
+    required_tool_tiers: HashMap<EnvironmentObjectType, u8>,
+}
+
+impl StandardHarvestPolicy {
+    pub fn with_required_tools(required_tool_tiers: HashMap<EnvironmentObjectType, u8>) -> Self {
+        Self { required_tool_tiers }
+    }
+}
+
+impl HarvestPolicy for StandardHarvestPolicy {
+    fn validate(&self, player_id: &str, object: &EnvironmentObject, context: &HarvestContext) -> Result<(), HarvestErrorCode> {
+        if context.spawn_protection.is_some_and(|p| p.contains(&object.position)) {
+            return Err(HarvestErrorCode::Protected);
+        }
+
+        // Validate range (anti-cheat)
+        let distance = object.position.distance_to(context.player_position);
+        if distance > context.max_range {
+            warn!("Player {} attempted to harvest from too far: {} > {}",
+                  player_id, distance, context.max_range);
+            return Err(HarvestErrorCode::TooFar);
+        }
+
+        if let Some(&min_tier) = self.required_tool_tiers.get(&object.object_type) {
+            let tier = context
+                .tool
+                .and_then(tool_spec)
+                .filter(|spec| spec.harvests == object.object_type)
+                .map_or(0, |spec| spec.tier);
+            if tier < min_tier {
+                return Err(HarvestErrorCode::MissingTool);
+            }
+        }
+        Ok(())
+    }
+}
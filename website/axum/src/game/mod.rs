@@ -1,20 +1,34 @@
 // src/game/mod.rs
 // Game state management for all entities (players, NPCs, etc.) and environment
 
+pub mod anticheat;
+pub mod crafting;
 pub mod entity_state;
+pub mod entity_store;
 pub mod environment;
 pub mod environment_gen;
+pub mod events;
+pub mod fixtures;
+pub mod harvest_audit;
+pub mod harvest_policy;
+pub mod player_store;
+pub mod spawn;
+pub mod tick;
+pub mod tools;
+pub mod world_config;
 
 pub use entity_state::{
-    EntityState, EntityStateManager, EntityType, Position, Rotation,
-    Inventory, InventoryItem, GameMessage, ServerMessage
+    EntityState, EntityStateManager, EntityType, Position, PublicEntityState, Rotation,
+    Inventory, InventoryItem, GameMessage, GameMessageError, MoveResult, ServerMessage
 };
 
 pub use environment::{
     EnvironmentManager, EnvironmentObject, EnvironmentObjectType, ResourceType,
     EnvironmentObjectData, EnvironmentObjectsSpawnMessage, EnvironmentObjectsDespawnMessage,
-    HarvestObjectRequest, HarvestObjectResponse, EnvironmentObjectRespawnMessage,
+    HarvestObjectRequest, HarvestObjectResponse, HarvestErrorCode, InteractAction, SpawnProtection, HarvestEffect, HarvestYieldRange, EnvironmentObjectRespawnMessage,
     ChunkCoord, EnvironmentStats
 };
 
-pub use environment_gen::EnvironmentGenerator;
+pub use environment_gen::{EnvironmentGenerator, GenerationProgress, NoiseLayer};
+pub use tick::TickBatcher;
+pub use world_config::WorldConfig;
@@ -0,0 +1,128 @@
+// src/game/player_store.rs
+// Durable storage for player progress (position and inventory)
+//
+// EntityStateManager saves a player's record when they leave and restores it when they
+// rejoin. With checkpointing enabled it also writes every player changed since the last
+// checkpoint on a fixed interval, so a crash loses at most one interval of progress.
+
+use anyhow::Result;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::entity_state::{EntityState, Inventory, Position};
+
+/// Persisted slice of a player's state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerRecord {
+    pub user_id: String,
+    pub position: Position,
+    pub inventory: Inventory,
+    /// Unix timestamp (seconds) of the save
+    pub saved_at: i64,
+}
+
+impl PlayerRecord {
+    pub fn from_entity(entity: &EntityState) -> Self {
+        Self {
+            user_id: entity.entity_id.clone(),
+            position: entity.position,
+            inventory: entity.inventory.clone(),
+            saved_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// Player progress backend
+pub trait PlayerStore: Send + Sync {
+    /// Last saved record for a player (None if they have never been saved)
+    fn load(&self, user_id: &str) -> Result<Option<PlayerRecord>>;
+
+    /// Insert or replace a player's record
+    fn save(&self, record: &PlayerRecord) -> Result<()>;
+}
+
+/// Process-local store (tests, single-instance development)
+#[derive(Default)]
+pub struct InMemoryPlayerStore {
+    records: DashMap<String, PlayerRecord>,
+}
+
+impl InMemoryPlayerStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PlayerStore for InMemoryPlayerStore {
+    fn load(&self, user_id: &str) -> Result<Option<PlayerRecord>> {
+        Ok(self.records.get(user_id).map(|r| r.clone()))
+    }
+
+    fn save(&self, record: &PlayerRecord) -> Result<()> {
+        self.records.insert(record.user_id.clone(), record.clone());
+        Ok(())
+    }
+}
+
+/// One JSON file per player in a directory
+/// Files are written to a temporary name and renamed, so a crash mid-write keeps the old record
+pub struct JsonDirPlayerStore {
+    dir: PathBuf,
+}
+
+impl JsonDirPlayerStore {
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(Self { dir: dir.as_ref().to_path_buf() })
+    }
+
+    fn path_for(&self, user_id: &str) -> PathBuf {
+        // User ids are UUIDs; anything else is reduced to a safe file name
+        let name: String = user_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{name}.json"))
+    }
+}
+
+impl PlayerStore for JsonDirPlayerStore {
+    fn load(&self, user_id: &str) -> Result<Option<PlayerRecord>> {
+        match std::fs::read(self.path_for(user_id)) {
+            Ok(raw) => Ok(Some(serde_json::from_slice(&raw)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, record: &PlayerRecord) -> Result<()> {
+        let path = self.path_for(&record.user_id);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(record)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_dir_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("bugwars-players-{}", ulid::Ulid::new()));
+        let store = JsonDirPlayerStore::new(&dir).unwrap();
+        assert!(store.load("user-1").unwrap().is_none());
+
+        let mut entity = EntityState::new_player("user-1".to_string(), "one".to_string());
+        entity.position = Position::new(4.0, 0.0, -2.0);
+        entity.inventory.add_item("wood".to_string(), 3);
+        store.save(&PlayerRecord::from_entity(&entity)).unwrap();
+
+        let loaded = store.load("user-1").unwrap().unwrap();
+        assert_eq!(loaded.position, entity.position);
+        assert_eq!(loaded.inventory.get_item_quantity("wood"), 3);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,128 @@
+// src/game/spawn.rs
+// Where new and respawning players are placed
+//
+// The world config lists one or more spawn points; SpawnManager hands them out either
+// round-robin (even spread, weights ignored) or at random in proportion to each point's
+// weight. With no points configured everyone spawns at the origin.
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::warn;
+
+use super::entity_state::Position;
+
+/// A spawn location and its relative selection weight (random selection only)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpawnPoint {
+    pub position: Position,
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+impl SpawnPoint {
+    pub fn new(position: Position) -> Self {
+        Self { position, weight: 1.0 }
+    }
+}
+
+/// How the next spawn point is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpawnSelection {
+    #[default]
+    RoundRobin,
+    Random,
+}
+
+#[derive(Debug)]
+pub struct SpawnManager {
+    points: Vec<SpawnPoint>,
+    selection: SpawnSelection,
+    next: AtomicUsize,
+    /// Weighted table for random selection (None = uniform, e.g. invalid weights)
+    weights: Option<WeightedIndex<f32>>,
+}
+
+impl Default for SpawnManager {
+    fn default() -> Self {
+        Self::new(Vec::new(), SpawnSelection::default())
+    }
+}
+
+impl SpawnManager {
+    pub fn new(points: Vec<SpawnPoint>, selection: SpawnSelection) -> Self {
+        let weights = match (selection, points.is_empty()) {
+            (SpawnSelection::Random, false) => WeightedIndex::new(points.iter().map(|p| p.weight))
+                .map_err(|e| warn!(error = %e, "Invalid spawn point weights, picking uniformly"))
+                .ok(),
+            _ => None,
+        };
+        Self { points, selection, next: AtomicUsize::new(0), weights }
+    }
+
+    /// Every player spawns at `position`
+    pub fn single(position: Position) -> Self {
+        Self::new(vec![SpawnPoint::new(position)], SpawnSelection::RoundRobin)
+    }
+
+    pub fn points(&self) -> &[SpawnPoint] {
+        &self.points
+    }
+
+    /// Position for the next spawning player (origin if no points are configured)
+    pub fn pick(&self) -> Position {
+        if self.points.is_empty() {
+            return Position::default();
+        }
+        let index = match self.selection {
+            SpawnSelection::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % self.points.len(),
+            SpawnSelection::Random => {
+                let mut rng = rand::thread_rng();
+                match &self.weights {
+                    Some(weights) => weights.sample(&mut rng),
+                    None => rng.gen_range(0..self.points.len()),
+                }
+            }
+        };
+        self.points[index].position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_robin_cycles_points() {
+        let a = Position::new(10.0, 0.0, 0.0);
+        let b = Position::new(-10.0, 0.0, 5.0);
+        let spawns = SpawnManager::new(vec![SpawnPoint::new(a), SpawnPoint::new(b)], SpawnSelection::RoundRobin);
+        let picks: Vec<_> = (0..4).map(|_| spawns.pick()).collect();
+        assert_eq!(picks, [a, b, a, b]);
+    }
+
+    #[test]
+    fn test_random_selection_follows_weights() {
+        let a = Position::new(10.0, 0.0, 0.0);
+        let b = Position::new(-10.0, 0.0, 5.0);
+        let never = SpawnPoint { position: Position::new(99.0, 0.0, 99.0), weight: 0.0 };
+        let spawns = SpawnManager::new(vec![SpawnPoint::new(a), never, SpawnPoint { position: b, weight: 3.0 }], SpawnSelection::Random);
+        let picks: Vec<_> = (0..400).map(|_| spawns.pick()).collect();
+        assert!(picks.iter().all(|p| *p == a || *p == b));
+        let at_b = picks.iter().filter(|p| **p == b).count();
+        assert!(at_b > 200, "weighted point picked {at_b}/400 times");
+    }
+
+    #[test]
+    fn test_empty_config_spawns_at_origin() {
+        for selection in [SpawnSelection::RoundRobin, SpawnSelection::Random] {
+            assert_eq!(SpawnManager::new(Vec::new(), selection).pick(), Position::default());
+        }
+    }
+}
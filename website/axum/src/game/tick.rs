@@ -0,0 +1,333 @@
+// src/game/tick.rs
+// Fixed-rate server tick that batches entity changes into one delta per tick
+//
+// Handlers record changes (moves, health, leaves) as they arrive; the tick loop drains
+// the accumulated state at a fixed rate and hands a single `GameStateDelta` to the
+// transport layer, which fans it out to interested players. Rapid updates from the
+// same entity within one tick collapse into its latest state.
+//
+// Entity types can be given a sync interval (in ticks) so low-priority entities such as
+// NPCs go out less often; their changes are held back and merged until the interval
+// since their last broadcast has passed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time;
+use tracing::{debug, info};
+
+use super::entity_state::{EntityType, Position, Rotation, ServerMessage};
+
+/// Default broadcast rate
+pub const DEFAULT_TICK_RATE_HZ: u32 = 20;
+
+/// Latest changed fields of one entity within a tick (unchanged fields are omitted)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityDelta {
+    pub entity_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<Rotation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_alive: Option<bool>,
+}
+
+impl EntityDelta {
+    pub fn new(entity_id: &str) -> Self {
+        Self {
+            entity_id: entity_id.to_string(),
+            position: None,
+            rotation: None,
+            health: None,
+            is_alive: None,
+        }
+    }
+
+    /// Overwrite fields set in `newer`, keep the rest
+    fn merge(&mut self, newer: EntityDelta) {
+        self.position = newer.position.or(self.position);
+        self.rotation = newer.rotation.or(self.rotation);
+        self.health = newer.health.or(self.health);
+        self.is_alive = newer.is_alive.or(self.is_alive);
+    }
+}
+
+#[derive(Default)]
+struct PendingTick {
+    entities: HashMap<String, (EntityType, EntityDelta)>,
+    removed: HashSet<String>,
+}
+
+impl PendingTick {
+    fn remove(&mut self, entity_id: &str) {
+        self.entities.remove(entity_id);
+        self.removed.insert(entity_id.to_string());
+    }
+
+    fn update(&mut self, entity_type: EntityType, update: EntityDelta) {
+        self.removed.remove(&update.entity_id);
+        match self.entities.get_mut(&update.entity_id) {
+            Some((_, delta)) => delta.merge(update),
+            None => {
+                self.entities.insert(update.entity_id.clone(), (entity_type, update));
+            }
+        }
+    }
+}
+
+/// Accumulates entity changes between ticks
+#[derive(Default)]
+pub struct TickBatcher {
+    pending: Mutex<PendingTick>,
+    tick: AtomicU64,
+    /// Ticks between broadcasts per entity type (absent = every tick)
+    sync_intervals: HashMap<EntityType, u64>,
+    /// Tick each entity was last included in a delta
+    last_broadcast: Mutex<HashMap<String, u64>>,
+}
+
+impl TickBatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Broadcast entities of `entity_type` at most once every `ticks` ticks
+    pub fn with_sync_interval(mut self, entity_type: EntityType, ticks: u64) -> Self {
+        self.sync_intervals.insert(entity_type, ticks.max(1));
+        self
+    }
+
+    /// Record the broadcast-worthy part of a handler response
+    /// (PlayerMoved, PlayerHealthChanged, PlayerLeft; other messages are ignored)
+    pub fn record(&self, msg: &ServerMessage) {
+        let mut pending = self.pending.lock().unwrap();
+        match msg {
+            ServerMessage::PlayerMoved { user_id, position, rotation } => {
+                let mut delta = EntityDelta::new(user_id);
+                delta.position = Some(*position);
+                delta.rotation = Some(*rotation);
+                pending.update(EntityType::Player, delta);
+            }
+            ServerMessage::PlayerHealthChanged { user_id, health, is_alive } => {
+                let mut delta = EntityDelta::new(user_id);
+                delta.health = Some(*health);
+                delta.is_alive = Some(*is_alive);
+                pending.update(EntityType::Player, delta);
+            }
+            ServerMessage::PlayerLeft { user_id } => pending.remove(user_id),
+            ServerMessage::Batch { messages } => {
+                drop(pending);
+                messages.iter().for_each(|message| self.record(message));
+            }
+            _ => {}
+        }
+    }
+
+    /// Record a change to a server-driven entity (NPCs, enemies, bosses)
+    pub fn record_update(&self, entity_type: EntityType, update: EntityDelta) {
+        self.pending.lock().unwrap().update(entity_type, update);
+    }
+
+    /// Record that an entity left the world (e.g. socket closed)
+    pub fn record_removed(&self, entity_id: &str) {
+        self.pending.lock().unwrap().remove(entity_id);
+    }
+
+    /// Advance the tick and take everything recorded since the last one
+    /// Returns None when nothing changed (no empty deltas go over the wire)
+    pub fn drain(&self) -> Option<ServerMessage> {
+        let tick = self.tick.fetch_add(1, Ordering::Relaxed) + 1;
+        let (entities, removed) = {
+            let mut guard = self.pending.lock().unwrap();
+            let pending = std::mem::take(&mut *guard);
+            let mut last_broadcast = self.last_broadcast.lock().unwrap();
+            for entity_id in &pending.removed {
+                last_broadcast.remove(entity_id);
+            }
+
+            // Entities still inside their sync interval wait for a later tick
+            let mut entities = Vec::with_capacity(pending.entities.len());
+            for (entity_id, (entity_type, delta)) in pending.entities {
+                let interval = self.sync_intervals.get(&entity_type).copied().unwrap_or(1);
+                let due = last_broadcast.get(&entity_id).is_none_or(|last| tick - last >= interval);
+                if due {
+                    last_broadcast.insert(entity_id, tick);
+                    entities.push(delta);
+                } else {
+                    guard.entities.insert(entity_id, (entity_type, delta));
+                }
+            }
+            (entities, pending.removed)
+        };
+        if entities.is_empty() && removed.is_empty() {
+            return None;
+        }
+        Some(ServerMessage::GameStateDelta {
+            tick,
+            entities,
+            removed: removed.into_iter().collect(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        })
+    }
+
+    /// Number of ticks elapsed
+    pub fn current_tick(&self) -> u64 {
+        self.tick.load(Ordering::Relaxed)
+    }
+}
+
+/// Coalesce messages bound for one recipient into a single frame
+/// Returns None for an empty queue and the message itself when there is only one;
+/// nested batches are flattened so clients never see a batch inside a batch
+pub fn coalesce(queue: Vec<ServerMessage>) -> Option<ServerMessage> {
+    let mut messages = Vec::with_capacity(queue.len());
+    for msg in queue {
+        match msg {
+            ServerMessage::Batch { messages: inner } => messages.extend(inner),
+            other => messages.push(other),
+        }
+    }
+    match messages.len() {
+        0 => None,
+        1 => messages.pop(),
+        _ => Some(ServerMessage::Batch { messages }),
+    }
+}
+
+/// Drive the batcher at `tick_rate_hz`, passing each non-empty delta to `flush`
+pub async fn run_tick_loop<F>(batcher: std::sync::Arc<TickBatcher>, tick_rate_hz: u32, mut flush: F)
+where
+    F: FnMut(ServerMessage),
+{
+    let period = Duration::from_secs_f64(1.0 / tick_rate_hz.max(1) as f64);
+    let mut interval = time::interval(period);
+    interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+    info!(tick_rate_hz, "Starting server tick loop");
+
+    loop {
+        interval.tick().await;
+        if let Some(delta) = batcher.drain() {
+            if let ServerMessage::GameStateDelta { tick, entities, removed, .. } = &delta {
+                debug!(tick, entities = entities.len(), removed = removed.len(), "Flushing tick delta");
+            }
+            flush(delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moved(user_id: &str, x: f32) -> ServerMessage {
+        ServerMessage::PlayerMoved {
+            user_id: user_id.to_string(),
+            position: Position::new(x, 0.0, 0.0),
+            rotation: Rotation::default(),
+        }
+    }
+
+    #[test]
+    fn test_rapid_updates_batch_into_single_delta() {
+        let batcher = TickBatcher::new();
+        for i in 0..100 {
+            batcher.record(&moved("player-a", i as f32));
+        }
+        batcher.record(&moved("player-b", 7.0));
+
+        let Some(ServerMessage::GameStateDelta { tick, entities, removed, .. }) = batcher.drain() else {
+            panic!("expected a delta");
+        };
+        assert_eq!(tick, 1);
+        assert_eq!(entities.len(), 2);
+        assert!(removed.is_empty());
+        let a = entities.iter().find(|e| e.entity_id == "player-a").unwrap();
+        assert_eq!(a.position.unwrap().x, 99.0);
+
+        // Nothing new since the last tick
+        assert!(batcher.drain().is_none());
+        assert_eq!(batcher.current_tick(), 2);
+    }
+
+    #[test]
+    fn test_sync_interval_throttles_low_priority_entities() {
+        let batcher = TickBatcher::new().with_sync_interval(EntityType::Npc, 5);
+        let (mut player_updates, mut npc_updates) = (0, 0);
+        for i in 0..10 {
+            batcher.record(&moved("player-a", i as f32));
+            let mut npc = EntityDelta::new("npc-00000001");
+            npc.position = Some(Position::new(i as f32, 0.0, 0.0));
+            batcher.record_update(EntityType::Npc, npc);
+
+            let Some(ServerMessage::GameStateDelta { entities, .. }) = batcher.drain() else {
+                panic!("expected a delta");
+            };
+            player_updates += entities.iter().filter(|e| e.entity_id == "player-a").count();
+            npc_updates += entities.iter().filter(|e| e.entity_id == "npc-00000001").count();
+        }
+        assert_eq!(player_updates, 10);
+        assert_eq!(npc_updates, 2);
+
+        // Held-back changes are merged, so the next NPC broadcast carries its latest position
+        let Some(ServerMessage::GameStateDelta { entities, .. }) = (0..5).find_map(|_| batcher.drain()) else {
+            panic!("expected the deferred NPC delta");
+        };
+        assert_eq!(entities[0].position.unwrap().x, 9.0);
+    }
+
+    #[test]
+    fn test_leave_replaces_pending_moves() {
+        let batcher = TickBatcher::new();
+        batcher.record(&moved("player-a", 1.0));
+        batcher.record_removed("player-a");
+
+        let Some(ServerMessage::GameStateDelta { entities, removed, .. }) = batcher.drain() else {
+            panic!("expected a delta");
+        };
+        assert!(entities.is_empty());
+        assert_eq!(removed, vec!["player-a".to_string()]);
+    }
+
+    #[test]
+    fn test_batch_round_trip() {
+        let batch = coalesce(vec![
+            moved("player-a", 3.0),
+            ServerMessage::ObjectRespawned {
+                object_id: "tree-1".to_string(),
+                object_data: serde_json::json!({ "id": "tree-1" }),
+            },
+        ])
+        .unwrap();
+
+        let json = serde_json::to_value(&batch).unwrap();
+        assert_eq!(json["type"], "batch");
+        assert_eq!(json["messages"][0]["type"], "player_moved");
+        assert_eq!(json["messages"][1]["type"], "object_respawned");
+
+        let ServerMessage::Batch { messages } = serde_json::from_value(json).unwrap() else {
+            panic!("expected a batch");
+        };
+        assert!(matches!(&messages[0], ServerMessage::PlayerMoved { user_id, position, .. } if user_id == "player-a" && position.x == 3.0));
+        assert!(matches!(&messages[1], ServerMessage::ObjectRespawned { object_id, .. } if object_id == "tree-1"));
+    }
+
+    #[test]
+    fn test_coalesce_single_and_nested() {
+        assert!(coalesce(Vec::new()).is_none());
+        assert!(matches!(coalesce(vec![moved("player-a", 1.0)]), Some(ServerMessage::PlayerMoved { .. })));
+
+        let nested = coalesce(vec![
+            ServerMessage::Batch { messages: vec![moved("player-a", 1.0), moved("player-b", 2.0)] },
+            moved("player-c", 3.0),
+        ]);
+        let Some(ServerMessage::Batch { messages }) = nested else {
+            panic!("expected a batch");
+        };
+        assert_eq!(messages.len(), 3);
+    }
+}
@@ -0,0 +1,41 @@
+// src/game/tools.rs
+// Harvesting tools and their tier yield multipliers
+//
+// Tools are plain inventory items; this table maps item ids to the object type they
+// work on and how much they multiply the harvested resource amount.
+
+use super::environment::EnvironmentObjectType;
+
+/// Static tool definition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToolSpec {
+    pub item_id: &'static str,
+    pub tier: u8,
+    /// Multiplier applied to `resource_amount` when harvesting a matching object
+    pub yield_multiplier: f32,
+    pub harvests: EnvironmentObjectType,
+}
+
+/// Known tools (item id lookup table)
+pub const TOOLS: &[ToolSpec] = &[
+    ToolSpec { item_id: "stone_axe", tier: 1, yield_multiplier: 1.0, harvests: EnvironmentObjectType::Tree },
+    ToolSpec { item_id: "iron_axe", tier: 2, yield_multiplier: 2.0, harvests: EnvironmentObjectType::Tree },
+    ToolSpec { item_id: "steel_axe", tier: 3, yield_multiplier: 3.0, harvests: EnvironmentObjectType::Tree },
+    ToolSpec { item_id: "stone_pickaxe", tier: 1, yield_multiplier: 1.0, harvests: EnvironmentObjectType::Rock },
+    ToolSpec { item_id: "iron_pickaxe", tier: 2, yield_multiplier: 2.0, harvests: EnvironmentObjectType::Rock },
+    ToolSpec { item_id: "steel_pickaxe", tier: 3, yield_multiplier: 3.0, harvests: EnvironmentObjectType::Rock },
+];
+
+/// Look up a tool by item id
+pub fn tool_spec(item_id: &str) -> Option<&'static ToolSpec> {
+    TOOLS.iter().find(|t| t.item_id == item_id)
+}
+
+/// Yield multiplier for harvesting `object_type` with an (optional) equipped tool
+/// Bare hands and tools for other object types yield 1x
+pub fn yield_multiplier(tool: Option<&str>, object_type: EnvironmentObjectType) -> f32 {
+    tool.and_then(tool_spec)
+        .filter(|spec| spec.harvests == object_type)
+        .map(|spec| spec.yield_multiplier)
+        .unwrap_or(1.0)
+}
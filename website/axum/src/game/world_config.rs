@@ -0,0 +1,235 @@
+// src/game/world_config.rs
+// Designer-tunable world settings consumed by the environment generator
+//
+// Loaded from a JSON file (WORLD_CONFIG_PATH) when present, otherwise defaults
+// matching the original hardcoded generation behavior are used.
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::warn;
+
+use super::entity_state::{InventoryItem, Position, ProximityArea, PvpZone, DEFAULT_INVENTORY_SLOTS};
+use super::environment_gen::Biome;
+use super::environment::{EnvironmentObjectType, HarvestEffect, HarvestYieldRange, ResourceType, SpawnProtection};
+use super::spawn::{SpawnPoint, SpawnSelection};
+
+/// A single asset variant and its relative selection weight
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightedAsset {
+    pub asset_name: String,
+    pub weight: f32,
+}
+
+impl WeightedAsset {
+    pub fn new(asset_name: &str, weight: f32) -> Self {
+        Self {
+            asset_name: asset_name.to_string(),
+            weight,
+        }
+    }
+}
+
+/// Area kept clear of generated objects (roads, structures), on the x/z plane
+/// `{"shape": "circle", "center": {"x": 0, "y": 0, "z": 0}, "radius": 12}` or
+/// `{"shape": "rect", "min_x": -5, "min_z": 0, "max_x": 5, "max_z": 200}`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum ExclusionZone {
+    Circle { center: Position, radius: f32 },
+    Rect { min_x: f32, min_z: f32, max_x: f32, max_z: f32 },
+}
+
+impl ExclusionZone {
+    pub fn contains(&self, position: &Position) -> bool {
+        match *self {
+            ExclusionZone::Circle { center, radius } => {
+                let dx = position.x - center.x;
+                let dz = position.z - center.z;
+                dx * dx + dz * dz <= radius * radius
+            }
+            ExclusionZone::Rect { min_x, min_z, max_x, max_z } => {
+                (min_x..=max_x).contains(&position.x) && (min_z..=max_z).contains(&position.z)
+            }
+        }
+    }
+}
+
+/// World generation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldConfig {
+    /// Variants used in oak biomes (tree type noise <= 0)
+    pub oak_variants: Vec<WeightedAsset>,
+    /// Variants used in pine biomes (tree type noise > 0)
+    pub pine_variants: Vec<WeightedAsset>,
+    pub rock_variants: Vec<WeightedAsset>,
+    pub bush_variants: Vec<WeightedAsset>,
+    /// Harvest-free safe zone around spawn (null disables it)
+    pub spawn_protection: Option<SpawnProtection>,
+    /// Client effect/sound ids sent with successful harvests, keyed by resource type
+    pub harvest_effects: HashMap<ResourceType, HarvestEffect>,
+    /// Harvest range overrides per object type (others use the global max range)
+    pub harvest_ranges: HashMap<EnvironmentObjectType, f32>,
+    /// Minimum equipped tool tier to harvest an object type (`{"Tree": 1}`); absent types
+    /// can be harvested bare-handed
+    pub required_tool_tiers: HashMap<EnvironmentObjectType, u8>,
+    /// Chunks around an object's chunk that its harvest/respawn broadcasts reach (`{"Tree": 1}`);
+    /// absent types only reach players watching the object's own chunk
+    pub broadcast_radii: HashMap<EnvironmentObjectType, i32>,
+    /// Resource an object type yields in a given biome, overriding its default
+    /// (`{"rocky": {"Bush": "Herbs"}}`); applied during generation
+    pub resource_overrides: HashMap<Biome, HashMap<EnvironmentObjectType, ResourceType>>,
+    /// Minimum distance between generated objects of the same type within a chunk
+    /// (`{"Tree": 2.0}`); candidates closer than this to an earlier object are dropped.
+    /// Empty by default, so the default world matches what a seed generated before
+    pub min_object_spacing: HashMap<EnvironmentObjectType, f32>,
+    /// Areas where no objects are generated (see `ExclusionZone`)
+    pub exclusion_zones: Vec<ExclusionZone>,
+    /// Randomized yield ranges per resource type (`{"Wood": {"min": 2, "max": 6}}`);
+    /// absent types always yield the object's fixed amount
+    pub harvest_yields: HashMap<ResourceType, HarvestYieldRange>,
+    /// Items every new player starts with (`[{"item_id": "seed", "quantity": 3}]`)
+    pub starter_items: Vec<InventoryItem>,
+    /// Inventory slots for players without a per-player override (0 = unlimited);
+    /// `app_metadata.inventory_slots` in a player's token overrides it
+    pub inventory_slots: u32,
+    /// Whether players can damage each other outside any PvP zone
+    pub pvp_enabled: bool,
+    /// Chunk regions with their own PvP rule, e.g. a PvE-only town (see `PvpZone`);
+    /// later zones win where they overlap
+    pub pvp_zones: Vec<PvpZone>,
+    /// Trigger areas by id (`{"boss_lair": {"center": {...}, "radius": 10.0}}`); entities
+    /// moving in or out publish `EnteredArea` / `LeftArea` events
+    pub watched_areas: HashMap<String, ProximityArea>,
+    /// Where new and respawning players appear (`[{"position": {"x": 0, "y": 0, "z": 0}, "weight": 2}]`);
+    /// empty falls back to the spawn protection center, or the origin
+    pub spawn_points: Vec<SpawnPoint>,
+    /// "round_robin" (even spread) or "random" (weighted)
+    pub spawn_selection: SpawnSelection,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            oak_variants: vec![
+                WeightedAsset::new("Tree_Oak_01", 1.0),
+                WeightedAsset::new("Tree_Oak_02", 1.0),
+            ],
+            pine_variants: vec![
+                WeightedAsset::new("Tree_Pine_01", 1.0),
+                WeightedAsset::new("Tree_Pine_02", 1.0),
+            ],
+            rock_variants: vec![
+                WeightedAsset::new("Rock_01", 1.0),
+                WeightedAsset::new("Rock_02", 1.0),
+                WeightedAsset::new("Rock_03", 1.0),
+            ],
+            bush_variants: vec![
+                WeightedAsset::new("Bush_01", 1.0),
+                WeightedAsset::new("Bush_02", 1.0),
+            ],
+            spawn_protection: Some(SpawnProtection {
+                center: Position::new(0.0, 0.0, 0.0),
+                radius: 15.0,
+            }),
+            harvest_effects: HashMap::from([
+                (ResourceType::Wood, HarvestEffect::new("fx_wood_chips", "sfx_chop_wood")),
+                (ResourceType::Stone, HarvestEffect::new("fx_stone_debris", "sfx_mine_stone")),
+                (ResourceType::Berries, HarvestEffect::new("fx_leaf_rustle", "sfx_pick_berries")),
+                (ResourceType::Herbs, HarvestEffect::new("fx_leaf_rustle", "sfx_pick_herbs")),
+            ]),
+            harvest_ranges: HashMap::new(),
+            required_tool_tiers: HashMap::new(),
+            broadcast_radii: HashMap::new(),
+            resource_overrides: HashMap::new(),
+            min_object_spacing: HashMap::new(),
+            exclusion_zones: Vec::new(),
+            harvest_yields: HashMap::new(),
+            starter_items: Vec::new(),
+            inventory_slots: DEFAULT_INVENTORY_SLOTS,
+            pvp_enabled: true,
+            pvp_zones: Vec::new(),
+            watched_areas: HashMap::new(),
+            spawn_points: Vec::new(),
+            spawn_selection: SpawnSelection::default(),
+        }
+    }
+}
+
+impl WorldConfig {
+    /// Load configuration from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(path.as_ref())?;
+        Ok(serde_json::from_str(&raw)?)
+    }
+}
+
+/// Precomputed weighted table for picking an asset variant with a seeded RNG
+#[derive(Debug, Clone)]
+pub struct AssetTable {
+    names: Vec<String>,
+    dist: WeightedIndex<f32>,
+}
+
+impl AssetTable {
+    /// Build a table from weighted variants
+    /// Falls back to `fallback` (uniform) if the weights are empty, negative or all zero
+    pub fn new(variants: &[WeightedAsset], fallback: &[WeightedAsset]) -> Self {
+        match WeightedIndex::new(variants.iter().map(|v| v.weight)) {
+            Ok(dist) => Self {
+                names: variants.iter().map(|v| v.asset_name.clone()).collect(),
+                dist,
+            },
+            Err(e) => {
+                warn!(error = %e, "Invalid asset weights in world config, using defaults");
+                Self {
+                    names: fallback.iter().map(|v| v.asset_name.clone()).collect(),
+                    dist: WeightedIndex::new(fallback.iter().map(|v| v.weight))
+                        .expect("default asset weights must be valid"),
+                }
+            }
+        }
+    }
+
+    /// Pick a variant (consumes RNG state, so it stays deterministic per seed)
+    pub fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> &str {
+        &self.names[self.dist.sample(rng)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_weighted_pick_matches_configured_distribution() {
+        let variants = vec![
+            WeightedAsset::new("Tree_Oak_01", 0.6),
+            WeightedAsset::new("Tree_Oak_02", 0.4),
+        ];
+        let table = AssetTable::new(&variants, &WorldConfig::default().oak_variants);
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+        let samples = 20_000;
+        let oak_01 = (0..samples)
+            .filter(|_| table.pick(&mut rng) == "Tree_Oak_01")
+            .count();
+        let ratio = oak_01 as f32 / samples as f32;
+
+        assert!((ratio - 0.6).abs() < 0.02, "observed ratio {ratio}");
+    }
+
+    #[test]
+    fn test_invalid_weights_fall_back_to_defaults() {
+        let defaults = WorldConfig::default().rock_variants;
+        let table = AssetTable::new(&[WeightedAsset::new("Rock_99", 0.0)], &defaults);
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+
+        assert!(table.pick(&mut rng).starts_with("Rock_0"));
+    }
+}
@@ -0,0 +1,4 @@
+// src/lib.rs
+// Game simulation shared by the server binary and the benches
+
+pub mod game;
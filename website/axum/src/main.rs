@@ -1,16 +1,23 @@
 mod core;
 mod astro;
 mod auth;
-mod game;
+mod config;
 
 mod transports {
     pub mod https;
+    pub mod close;
+    pub mod codec;
+    pub mod format;
+    pub mod sessions;
+    pub mod recorder;
     pub mod tcp;
     pub mod graph;
+    pub mod openapi;
+    pub mod sequence;
 }
 
 use std::sync::Arc;
-use std::time::Duration;
+use kbve_bugwars::game;
 use core::{new_bus, run_app};
 use axum::{
     response::IntoResponse,
@@ -47,24 +54,35 @@ async fn main() -> anyhow::Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    // Bus
-    let (bus, rx) = new_bus(1024);
-    tokio::spawn(run_app(rx));
-
-    // JWT Cache - uses Supabase URL and anon key from environment
-    let supabase_url = std::env::var("SUPABASE_URL")
-        .unwrap_or_else(|_| {
-            warn!("SUPABASE_URL not set, using local default (for development only)");
-            "http://localhost:8000".to_string()
-        });
-    let supabase_anon_key = std::env::var("SUPABASE_ANON_KEY")
-        .expect("SUPABASE_ANON_KEY must be set in environment");
-
-    let jwt_cache = auth::jwt_cache::JwtCache::new(supabase_url, supabase_anon_key);
+    #[cfg(all(feature = "jemalloc", target_env = "msvc"))]
+    warn!("jemalloc feature enabled but unsupported on this target, using the system allocator");
+
+    // Config - every env setting is validated up front, problems reported together
+    let config = match config::Config::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{e}");
+            std::process::exit(1);
+        }
+    };
+    config.log_summary();
+    if config.supabase_url_defaulted {
+        warn!("SUPABASE_URL not set, using local default (for development only)");
+    }
+
+    // JWT Cache - uses Supabase URL and anon key from config
+    let mut jwt_cache = auth::jwt_cache::JwtCache::new(config.supabase_url.clone(), config.supabase_anon_key.clone());
+    if let Some(grace) = config.token_expiry_warning_secs {
+        jwt_cache = jwt_cache.with_grace_period(grace);
+    }
+    // Extra tenants: {"<tenant>": {"url": "...", "anon_key": "..."}}
+    for (tenant, upstream) in config.supabase_tenants.clone() {
+        jwt_cache = jwt_cache.with_tenant(tenant, upstream);
+    }
     info!("JWT cache initialized with Supabase verification");
 
     // Service role key initialization - validate at startup (kills app if invalid)
-    if let Ok(service_key) = std::env::var("SUPABASE_SERVICE_ROLE_KEY") {
+    if let Some(service_key) = config.supabase_service_role_key.clone() {
         auth::jwt_cache::init_service_role_key(service_key)
             .expect("CRITICAL: Failed to initialize service role key - this should never happen (key was already set). Terminating application for safety.");
         info!("Service role key initialized successfully - admin operations enabled (bypasses RLS)");
@@ -72,33 +90,119 @@ async fn main() -> anyhow::Result<()> {
         warn!("SUPABASE_SERVICE_ROLE_KEY not configured - admin operations will be disabled");
     }
 
+    // Game events (harvests, area triggers) for quest/achievement subscribers
+    let game_events = game::events::GameEventBus::default();
+
+    // Movement/harvest validation, shared so /admin/anticheat can flip shadow mode at runtime
+    let anticheat = game::anticheat::AntiCheat::new(config.anticheat_mode, config.anticheat_tolerance);
+
     // Entity state manager for Unity game clients (players, NPCs, enemies, bosses)
-    let entity_state = game::EntityStateManager::new(120); // 2 minute stale timeout
+    let mut entity_state = game::EntityStateManager::new(120) // 2 minute stale timeout
+        .with_event_bus(game_events.clone())
+        .with_anticheat(anticheat.clone());
+    if let Some(max_entities) = config.max_entities {
+        entity_state = entity_state.with_max_entities(max_entities);
+    }
+    if let Some(max_players) = config.max_players {
+        entity_state = entity_state.with_max_players(max_players);
+    }
+    if let Some(ops_per_sec) = config.inventory_ops_per_sec {
+        entity_state = entity_state.with_inventory_rate_limit(ops_per_sec);
+    }
+    if let Some(max_speed) = config.max_move_speed {
+        entity_state = entity_state.with_max_move_speed(max_speed);
+    }
+    if let Some(dir) = &config.player_store_dir {
+        let store = game::player_store::JsonDirPlayerStore::new(dir)?;
+        entity_state = entity_state.with_player_store(Arc::new(store));
+        info!(dir = %dir.display(), "Player progress persistence enabled");
+    }
     info!("Entity state manager initialized for Unity clients");
 
+    // World config (asset weights etc.) - optional JSON file, loaded with the rest of the config
+    let world_config = &config.world;
+    if !world_config.starter_items.is_empty() {
+        info!(items = world_config.starter_items.len(), "Starter inventory configured");
+    }
+    // Configured spawn points; without any, players (re)spawn at the protected spawn area's
+    // center, or the origin when spawn protection is off
+    let spawns = if world_config.spawn_points.is_empty() {
+        let center = world_config.spawn_protection.map(|protection| protection.center).unwrap_or_default();
+        game::spawn::SpawnManager::single(center)
+    } else {
+        game::spawn::SpawnManager::new(world_config.spawn_points.clone(), world_config.spawn_selection)
+    };
+    info!(points = spawns.points().len(), selection = ?world_config.spawn_selection, "Spawn points configured");
+    let entity_state = entity_state
+        .with_starter_items(world_config.starter_items.clone())
+        .with_chunk_size(config.chunk_size)
+        .with_inventory_slots(world_config.inventory_slots)
+        .with_pvp_zones(world_config.pvp_enabled, world_config.pvp_zones.clone())
+        .with_respawn_cooldown(config.player_respawn_cooldown)
+        .with_disconnect_linger(config.disconnect_linger)
+        .with_health_regen(config.health_regen_per_sec, config.health_regen_delay)
+        .with_spawns(spawns);
+    for (area_id, area) in &world_config.watched_areas {
+        entity_state.watch_area(area_id.clone(), *area);
+    }
+    if !world_config.watched_areas.is_empty() {
+        info!(areas = world_config.watched_areas.len(), "Proximity trigger areas configured");
+    }
+
     // Environment manager for server-authoritative environment objects (trees, rocks, bushes)
-    let environment_manager = Arc::new(game::EnvironmentManager::new(
-        50.0,  // chunk_size (matches Unity terrain chunks)
-        3,     // view_distance_chunks (3 = 7x7 grid)
-        10.0,  // max_harvest_range (anti-cheat validation)
-    ));
+    let mut environment_manager = game::EnvironmentManager::new(
+        config.chunk_size,           // matches Unity terrain chunks
+        config.view_distance_chunks, // 3 = 7x7 grid
+        config.max_harvest_range,    // anti-cheat validation
+    );
+    if let Some(protection) = world_config.spawn_protection {
+        environment_manager = environment_manager.with_spawn_protection(protection);
+        info!(radius = protection.radius, "Spawn protection enabled");
+    }
+    if let Some(water_level) = config.water_level {
+        environment_manager = environment_manager.with_water_level(water_level);
+        info!(water_level, "Planting below the water level disabled");
+    }
+    if let Some(player_radius) = config.collision_player_radius {
+        environment_manager = environment_manager.with_collision_checks(player_radius);
+        info!(player_radius, "Object collision checks enabled");
+    }
+    environment_manager = environment_manager
+        .with_respawn_timing(config.respawn_check_interval, config.respawn_jitter_secs)
+        .with_harvest_effects(world_config.harvest_effects.clone())
+        .with_harvest_ranges(world_config.harvest_ranges.clone())
+        .with_harvest_policy(Arc::new(game::harvest_policy::StandardHarvestPolicy::with_required_tools(
+            world_config.required_tool_tiers.clone(),
+        )))
+        .with_harvest_all_limit(config.harvest_all_limit)
+        .with_harvest_audit_capacity(config.harvest_audit_capacity)
+        .with_grouped_despawns(config.grouped_despawns)
+        .with_chunk_diffing(config.chunk_diffing)
+        .with_interaction_range(config.interaction_range)
+        .with_broadcast_radii(world_config.broadcast_radii.clone())
+        .with_harvest_yields(world_config.harvest_yields.clone(), config.world_seed)
+        .with_event_bus(game_events.clone())
+        .with_anticheat(anticheat.clone());
+    let environment_manager = Arc::new(environment_manager);
     info!("Environment manager initialized");
 
     // Generate initial world environment objects
-    let generator = game::EnvironmentGenerator::new(
-        12345, // world seed (deterministic generation)
-        50.0,  // chunk_size (must match environment_manager)
-    );
-
-    // Generate starting area around spawn (0, 0)
-    let spawn_chunk = game::ChunkCoord { x: 0, z: 0 };
-    let initial_objects = generator.generate_area(&spawn_chunk, 5); // 11x11 chunks
-    info!("Generated {} initial environment objects", initial_objects.len());
+    let generator = Arc::new(game::EnvironmentGenerator::with_config(
+        config.world_seed, // deterministic generation
+        config.chunk_size, // must match environment_manager
+        world_config,
+    ));
 
-    // Add objects to manager
-    for object in initial_objects {
-        environment_manager.add_object(object);
-    }
+    // Generate starting area around spawn (0, 0) in the background so startup isn't blocked
+    // (/ready reports "generating" until done; early joiners generate spawn chunks lazily)
+    let world_gen = Arc::new(game::GenerationProgress::new());
+    tokio::spawn(game::environment_gen::generate_area_in_background(
+        generator.clone(),
+        environment_manager.clone(),
+        game::ChunkCoord { x: 0, z: 0 },
+        game::environment_gen::INITIAL_AREA_RADIUS,
+        world_gen.clone(),
+    ));
 
     // Start respawn background task
     let env_manager_clone = environment_manager.clone();
@@ -107,6 +211,15 @@ async fn main() -> anyhow::Result<()> {
         env_manager_clone.start_respawn_task().await;
     });
 
+    // Bus
+    let (bus, rx) = new_bus(1024);
+    tokio::spawn(run_app(
+        rx,
+        jwt_cache.clone(),
+        entity_state.clone(),
+        environment_manager.clone(),
+    ));
+
     // Spawn cache manager task
     let cache_manager = {
         let cache = jwt_cache.clone();
@@ -123,13 +236,41 @@ async fn main() -> anyhow::Result<()> {
         })
     };
 
+    // Periodic save of changed players so a crash loses at most one interval of progress
+    tokio::spawn(entity_state.clone().run_checkpoint_task(config.checkpoint_interval));
+
+    let app_state = transports::https::AppState {
+        bus: bus.clone(),
+        jwt_cache: jwt_cache.clone(),
+        entity_state: entity_state.clone(),
+        environment_manager: environment_manager.clone(),
+        sessions: transports::sessions::SessionRegistry::new()
+            .with_duplicate_policy(config.duplicate_session_policy),
+        generator: Arc::new(std::sync::RwLock::new(generator.clone())),
+        tick: Arc::new(
+            config
+                .entity_sync_intervals()
+                .fold(game::TickBatcher::new(), |tick, (entity_type, ticks)| tick.with_sync_interval(entity_type, ticks)),
+        ),
+        recording: config.recording.clone(),
+        ws_idle_timeout: config.ws_idle_timeout,
+        ws_ping_interval: config.ws_ping_interval,
+        ws_pong_timeout: config.ws_pong_timeout,
+        world_gen,
+        recipes: config.recipes.clone(),
+        events: game_events.clone(),
+        anticheat,
+    };
+
+    // Fixed-rate tick: batches entity changes into one delta per tick per player
+    tokio::spawn(transports::https::run_broadcast_tick(app_state.clone(), config.tick_rate_hz));
+
+    // Drop players whose reconnect window ran out
+    tokio::spawn(transports::https::run_disconnect_sweep(app_state.clone()));
+    tokio::spawn(transports::https::run_health_regen(app_state.clone()));
+
     // Tokio
-    let http = tokio::spawn(transports::https::serve(
-        bus.clone(),
-        jwt_cache.clone(),
-        entity_state.clone(),
-        environment_manager.clone(),
-    ));
+    let http = tokio::spawn(transports::https::serve(app_state, config.http_addr));
 
     // Print
     info!("BugWars v{}", env!("CARGO_PKG_VERSION"));
@@ -144,11 +285,16 @@ async fn main() -> anyhow::Result<()> {
         _ = entity_cleanup => {
             error!("Entity state cleanup task terminated unexpectedly");
         },
-        _ = tokio::signal::ctrl_c() => {
+        _ = transports::https::shutdown_requested() => {
             tracing::info!("shutdown signal received");
         }
     }
 
+    // Graceful shutdown can finish the server task before the signal branch runs,
+    // so the final save happens on every way out
+    let saved = entity_state.checkpoint_blocking().await;
+    info!(saved, "Saved player progress before exit");
+
     Ok(())
 
 }
\ No newline at end of file
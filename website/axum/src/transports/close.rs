@@ -0,0 +1,112 @@
+// src/transports/close.rs
+// Close codes for server-initiated WebSocket closes
+//
+// Every close the server starts carries one of these application codes (4000-4999 is
+// reserved for applications by RFC 6455) so clients can decide whether to reconnect,
+// re-authenticate or give up without parsing the reason text:
+//
+//   4001 auth_failed       token expired or revoked; refresh it, then reconnect
+//   4003 kicked            removed by an admin; don't reconnect automatically
+//   4004 shutdown          server is stopping; reconnect with backoff
+//   4005 protocol_error    malformed frames or transport error
+//   4006 session_replaced  the same user connected elsewhere (or already was)
+//   4007 server_full       entity cap reached; retry after `retry_after_ms`
+//   4008 timeout           idle or keepalive timeout; reconnect when active again
+//
+// 4002 is reserved for a future message rate limit.
+//
+// Close frames are control frames capped at 125 payload bytes, two of which hold the
+// code, so long reasons are cut short in the frame; the JSON notice sent just before
+// the close always carries the full text.
+
+/// Largest close-frame reason in bytes (125-byte control payload minus the code)
+pub const MAX_CLOSE_REASON_BYTES: usize = 123;
+
+/// Cut `reason` to fit a close frame without splitting a UTF-8 character
+pub fn truncate_reason(reason: &str) -> &str {
+    if reason.len() <= MAX_CLOSE_REASON_BYTES {
+        return reason;
+    }
+    let mut end = MAX_CLOSE_REASON_BYTES;
+    while !reason.is_char_boundary(end) {
+        end -= 1;
+    }
+    &reason[..end]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseCode {
+    AuthFailed,
+    Kicked,
+    Shutdown,
+    ProtocolError,
+    SessionReplaced,
+    ServerFull,
+    Timeout,
+}
+
+impl CloseCode {
+    #[cfg(test)]
+    pub const ALL: [CloseCode; 7] = [
+        Self::AuthFailed,
+        Self::Kicked,
+        Self::Shutdown,
+        Self::ProtocolError,
+        Self::SessionReplaced,
+        Self::ServerFull,
+        Self::Timeout,
+    ];
+
+    /// Numeric code sent in the close frame
+    pub fn code(self) -> u16 {
+        match self {
+            Self::AuthFailed => 4001,
+            Self::Kicked => 4003,
+            Self::Shutdown => 4004,
+            Self::ProtocolError => 4005,
+            Self::SessionReplaced => 4006,
+            Self::ServerFull => 4007,
+            Self::Timeout => 4008,
+        }
+    }
+
+    /// Default reason text (callers may pass a more specific one, e.g. a kick reason)
+    pub fn reason(self) -> &'static str {
+        match self {
+            Self::AuthFailed => "Token expired",
+            Self::Kicked => "Kicked",
+            Self::Shutdown => "Server shutting down",
+            Self::ProtocolError => "Protocol error",
+            Self::SessionReplaced => "Connected from another location",
+            Self::ServerFull => "Server full",
+            Self::Timeout => "Idle timeout",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_unique_application_codes() {
+        let mut seen = std::collections::HashSet::new();
+        for close in CloseCode::ALL {
+            assert!((4000..5000).contains(&close.code()), "{close:?}");
+            assert!(seen.insert(close.code()), "duplicate code for {close:?}");
+        }
+    }
+
+    #[test]
+    fn test_truncate_reason_keeps_char_boundaries() {
+        assert_eq!(truncate_reason("Kicked"), "Kicked");
+
+        // 41 three-byte characters: 123 bytes fit exactly, a 42nd does not
+        let exact = "禁".repeat(41);
+        assert_eq!(truncate_reason(&exact), exact);
+        let long = format!("a{exact}");
+        let cut = truncate_reason(&long);
+        assert_eq!(cut.len(), 121);
+        assert!(long.starts_with(cut));
+    }
+}
@@ -0,0 +1,266 @@
+// src/transports/codec.rs
+// Wire codecs for large server payloads (initial environment snapshots)
+//
+// JSON text frames remain the default. Clients that negotiate `?codec=zstd` on the
+// WebSocket upgrade receive the initial snapshot as a single binary frame:
+// bincode-serialized EnvironmentObjectsSpawnMessage, compressed with zstd.
+// Object metadata is carried as JSON text, since bincode can't encode a
+// serde_json::Value or a skipped optional field.
+//
+// Independently, a session may opt into frame compression (`?compress_above=<bytes>`,
+// `&compress=zstd|deflate`): any outbound JSON frame larger than the threshold is sent
+// as a binary frame holding a one-byte algorithm tag followed by the compressed JSON.
+
+use anyhow::{anyhow, Result};
+use axum::extract::ws::Message;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::game::{
+    EnvironmentObjectData, EnvironmentObjectType, EnvironmentObjectsSpawnMessage, Position, ResourceType,
+};
+use crate::game::environment::{Quaternion, Scale};
+
+const ZSTD_LEVEL: i32 = 3; // Fast compression, good ratio for repetitive object data
+
+/// Codec used for the initial object snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SnapshotCodec {
+    #[default]
+    Json,
+    ZstdBincode,
+}
+
+impl SnapshotCodec {
+    /// Resolve the codec requested by the client (unknown values fall back to JSON)
+    pub fn negotiate(requested: Option<&str>) -> Self {
+        match requested.map(|s| s.to_ascii_lowercase()) {
+            Some(codec) if codec == "zstd" => SnapshotCodec::ZstdBincode,
+            _ => SnapshotCodec::Json,
+        }
+    }
+}
+
+/// Algorithm for compressed frames; the discriminant is the frame's tag byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum FrameAlgorithm {
+    #[default]
+    Zstd = 1,
+    Deflate = 2,
+}
+
+impl FrameAlgorithm {
+    /// Resolve the algorithm requested by the client (unknown values fall back to zstd)
+    pub fn negotiate(requested: Option<&str>) -> Self {
+        match requested.map(|s| s.to_ascii_lowercase()) {
+            Some(algorithm) if algorithm == "deflate" => FrameAlgorithm::Deflate,
+            _ => FrameAlgorithm::Zstd,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(FrameAlgorithm::Zstd),
+            2 => Some(FrameAlgorithm::Deflate),
+            _ => None,
+        }
+    }
+
+    fn compress(self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut out = vec![self as u8];
+        match self {
+            FrameAlgorithm::Zstd => out.extend(zstd::encode_all(data, ZSTD_LEVEL)?),
+            FrameAlgorithm::Deflate => {
+                let mut encoder = DeflateEncoder::new(out, Compression::fast());
+                encoder.write_all(data)?;
+                out = encoder.finish()?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Per-session outbound frame compression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FramePolicy {
+    /// JSON frames larger than this go out as compressed binary (None = always text)
+    pub compress_above_bytes: Option<usize>,
+    pub algorithm: FrameAlgorithm,
+}
+
+impl FramePolicy {
+    pub fn negotiate(compress_above_bytes: Option<usize>, algorithm: Option<&str>) -> Self {
+        Self {
+            compress_above_bytes,
+            algorithm: FrameAlgorithm::negotiate(algorithm),
+        }
+    }
+
+    /// Frame a serialized JSON message, compressing it if it exceeds the threshold
+    /// Falls back to a text frame if compression fails
+    pub fn encode(&self, json: String) -> Message {
+        match self.compress_above_bytes {
+            Some(threshold) if json.len() > threshold => match self.algorithm.compress(json.as_bytes()) {
+                Ok(bytes) => {
+                    debug!(raw_bytes = json.len(), compressed_bytes = bytes.len(), algorithm = ?self.algorithm, "Compressed outbound frame");
+                    Message::Binary(bytes.into())
+                }
+                Err(e) => {
+                    warn!(error = %e, "Frame compression failed, sending text");
+                    Message::Text(json.into())
+                }
+            },
+            _ => Message::Text(json.into()),
+        }
+    }
+}
+
+/// Decode a tagged compressed frame back to its JSON text (mirror of the client decoder)
+pub fn decode_frame(bytes: &[u8]) -> Result<String> {
+    let (&tag, payload) = bytes.split_first().ok_or_else(|| anyhow!("empty frame"))?;
+    let raw = match FrameAlgorithm::from_tag(tag).ok_or_else(|| anyhow!("unknown frame tag {tag}"))? {
+        FrameAlgorithm::Zstd => zstd::decode_all(payload)?,
+        FrameAlgorithm::Deflate => {
+            let mut raw = Vec::new();
+            DeflateDecoder::new(payload).read_to_end(&mut raw)?;
+            raw
+        }
+    };
+    Ok(String::from_utf8(raw)?)
+}
+
+/// Binary wire form of EnvironmentObjectData (same field order, metadata as JSON text)
+#[derive(Serialize, Deserialize)]
+struct WireObject {
+    object_id: String,
+    asset_name: String,
+    position: Position,
+    rotation: Quaternion,
+    scale: Scale,
+    object_type: EnvironmentObjectType,
+    resource_type: ResourceType,
+    resource_amount: u32,
+    harvest_time: f32,
+    collision_radius: f32,
+    metadata: Option<String>,
+}
+
+impl From<&EnvironmentObjectData> for WireObject {
+    fn from(data: &EnvironmentObjectData) -> Self {
+        Self {
+            object_id: data.object_id.clone(),
+            asset_name: data.asset_name.clone(),
+            position: data.position,
+            rotation: data.rotation,
+            scale: data.scale,
+            object_type: data.object_type,
+            resource_type: data.resource_type,
+            resource_amount: data.resource_amount,
+            harvest_time: data.harvest_time,
+            collision_radius: data.collision_radius,
+            metadata: data.metadata.as_ref().map(|m| m.to_string()),
+        }
+    }
+}
+
+impl TryFrom<WireObject> for EnvironmentObjectData {
+    type Error = serde_json::Error;
+
+    fn try_from(wire: WireObject) -> Result<Self, Self::Error> {
+        Ok(Self {
+            object_id: wire.object_id,
+            asset_name: wire.asset_name,
+            position: wire.position,
+            rotation: wire.rotation,
+            scale: wire.scale,
+            object_type: wire.object_type,
+            resource_type: wire.resource_type,
+            resource_amount: wire.resource_amount,
+            harvest_time: wire.harvest_time,
+            collision_radius: wire.collision_radius,
+            metadata: wire.metadata.as_deref().map(serde_json::from_str).transpose()?,
+        })
+    }
+}
+
+/// Encode a spawn snapshot as zstd-compressed bincode
+pub fn encode_snapshot(msg: &EnvironmentObjectsSpawnMessage) -> Result<Vec<u8>> {
+    let wire: Vec<WireObject> = msg.objects.iter().map(|data| WireObject::from(data.as_ref())).collect();
+    let raw = bincode::serialize(&wire)?;
+    let compressed = zstd::encode_all(raw.as_slice(), ZSTD_LEVEL)?;
+    debug!(
+        objects = msg.objects.len(),
+        raw_bytes = raw.len(),
+        compressed_bytes = compressed.len(),
+        "Encoded compressed environment snapshot"
+    );
+    Ok(compressed)
+}
+
+/// Decode a zstd-compressed bincode spawn snapshot (mirror of the client decoder)
+pub fn decode_snapshot(bytes: &[u8]) -> Result<EnvironmentObjectsSpawnMessage> {
+    let raw = zstd::decode_all(bytes)?;
+    let wire: Vec<WireObject> = bincode::deserialize(&raw)?;
+    let objects = wire
+        .into_iter()
+        .map(|wire| EnvironmentObjectData::try_from(wire).map(Arc::new))
+        .collect::<Result<_, _>>()?;
+    Ok(EnvironmentObjectsSpawnMessage { objects })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{ChunkCoord, EnvironmentGenerator};
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let generator = EnvironmentGenerator::new(12345, 50.0);
+        let objects = generator
+            .generate_area(&ChunkCoord { x: 0, z: 0 }, 3)
+            .iter()
+            .map(|o| Arc::new(o.to_network_data()))
+            .collect::<Vec<_>>();
+        assert!(objects.len() > 1000);
+
+        let msg = EnvironmentObjectsSpawnMessage { objects };
+        let encoded = encode_snapshot(&msg).unwrap();
+        let decoded = decode_snapshot(&encoded).unwrap();
+
+        assert_eq!(decoded, msg);
+        assert!(encoded.len() < serde_json::to_vec(&msg).unwrap().len());
+    }
+
+    #[test]
+    fn test_small_frames_stay_text_large_frames_compress() {
+        for algorithm in [FrameAlgorithm::Zstd, FrameAlgorithm::Deflate] {
+            let policy = FramePolicy { compress_above_bytes: Some(256), algorithm };
+
+            let small = r#"{"type":"pong","timestamp":1}"#.to_string();
+            assert!(matches!(policy.encode(small.clone()), Message::Text(text) if text.as_str() == small));
+
+            let large = serde_json::json!({ "type": "environment_objects", "objects": vec!["tree"; 200] }).to_string();
+            let Message::Binary(bytes) = policy.encode(large.clone()) else {
+                panic!("large frame should be compressed binary");
+            };
+            assert_eq!(bytes[0], algorithm as u8);
+            assert!(bytes.len() < large.len());
+            assert_eq!(decode_frame(&bytes).unwrap(), large);
+        }
+
+        let disabled = FramePolicy::default().encode("x".repeat(10_000));
+        assert!(matches!(disabled, Message::Text(_)));
+        assert!(decode_frame(&[9, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_negotiate_codec() {
+        assert_eq!(SnapshotCodec::negotiate(None), SnapshotCodec::Json);
+        assert_eq!(SnapshotCodec::negotiate(Some("ZSTD")), SnapshotCodec::ZstdBincode);
+        assert_eq!(SnapshotCodec::negotiate(Some("brotli")), SnapshotCodec::Json);
+    }
+}
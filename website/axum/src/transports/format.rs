@@ -0,0 +1,80 @@
+// src/transports/format.rs
+// Response format negotiation for HTTP API routes
+//
+// Handlers return `Negotiated(format, value)`; the format is taken from the request's
+// Accept header. `application/msgpack` (or `application/x-msgpack`) gets a MessagePack
+// body with named fields, anything else gets JSON. Request bodies stay JSON.
+
+use axum::extract::FromRequestParts;
+use axum::http::{header, request::Parts, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use std::convert::Infallible;
+use tracing::warn;
+
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Body format chosen from the Accept header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    MsgPack,
+}
+
+impl ResponseFormat {
+    pub fn from_accept(accept: Option<&str>) -> Self {
+        let wants_msgpack = accept.is_some_and(|accept| {
+            accept.split(',').any(|media| {
+                let media = media.split(';').next().unwrap_or("").trim();
+                media.eq_ignore_ascii_case(MSGPACK_CONTENT_TYPE) || media.eq_ignore_ascii_case("application/x-msgpack")
+            })
+        });
+        if wants_msgpack { ResponseFormat::MsgPack } else { ResponseFormat::Json }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ResponseFormat {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let accept = parts.headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+        Ok(Self::from_accept(accept))
+    }
+}
+
+/// A response body serialized in the negotiated format
+pub struct Negotiated<T>(pub ResponseFormat, pub T);
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        match self.0 {
+            ResponseFormat::Json => Json(self.1).into_response(),
+            ResponseFormat::MsgPack => match rmp_serde::to_vec_named(&self.1) {
+                Ok(bytes) => ([(header::CONTENT_TYPE, HeaderValue::from_static(MSGPACK_CONTENT_TYPE))], bytes).into_response(),
+                Err(e) => {
+                    warn!(error = %e, "MessagePack serialization failed");
+                    (StatusCode::INTERNAL_SERVER_ERROR, "serialization failed").into_response()
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_negotiation() {
+        assert_eq!(ResponseFormat::from_accept(None), ResponseFormat::Json);
+        assert_eq!(ResponseFormat::from_accept(Some("application/json")), ResponseFormat::Json);
+        assert_eq!(ResponseFormat::from_accept(Some("*/*")), ResponseFormat::Json);
+        assert_eq!(ResponseFormat::from_accept(Some("application/msgpack")), ResponseFormat::MsgPack);
+        assert_eq!(
+            ResponseFormat::from_accept(Some("text/html, application/x-msgpack;q=0.9")),
+            ResponseFormat::MsgPack
+        );
+    }
+}
@@ -4,14 +4,14 @@ use std::{net::SocketAddr, time::Duration};
 use axum::{
     extract::{
         Query, State,
-        ws::{Message, WebSocket, WebSocketUpgrade},
+        ws::{CloseFrame, Message, WebSocketUpgrade},
     },
     http::{Request, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
-use futures_util::StreamExt;
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
@@ -26,32 +26,78 @@ use tracing::{debug, error, info, warn, Level};
 use std::sync::Arc;
 use crate::core::{AppBus, AppCmd};
 use crate::auth::{extract_auth_user_from_parts, AuthUser, jwt_cache::JwtCache};
-use crate::game::{EntityStateManager, GameMessage, ServerMessage, EnvironmentManager};
+use crate::game::{
+    EntityStateManager, GameMessage, GameMessageError, MoveResult, ServerMessage, EnvironmentManager, HarvestObjectRequest, HarvestObjectResponse, HarvestErrorCode,
+    EnvironmentObjectData, EnvironmentObjectsSpawnMessage, EnvironmentGenerator, GenerationProgress, NoiseLayer, InteractAction,
+    EntityState, EntityType, Position, PublicEntityState, Rotation, TickBatcher,
+};
+use crate::game::anticheat::{AntiCheat, AntiCheatMode};
+use crate::game::crafting::RecipeRegistry;
+use crate::game::entity_state::PositionUpdateOutcome;
+use crate::game::environment::SEED_ITEM_ID;
+use crate::game::environment_gen::INITIAL_AREA_RADIUS;
+use crate::game::events::{GameEvent, GameEventBus};
+use crate::game::tick::EntityDelta;
+use crate::transports::close::{truncate_reason, CloseCode};
+use crate::transports::codec::{FramePolicy, SnapshotCodec, encode_snapshot};
+use crate::transports::format::{Negotiated, ResponseFormat};
+use crate::transports::recorder::{RecordingConfig, SessionLog, SessionRecorder};
+use crate::transports::sequence::OutboundSequence;
+use crate::transports::sessions::{DuplicateSessionPolicy, SessionCommand, SessionRegistry};
 
-/* ------------------------------- serve() -------------------------------- */
+/* ------------------------------- AppState ------------------------------- */
+
+/// Shared state for all HTTP/WS handlers
+#[derive(Clone)]
+pub struct AppState {
+    pub bus: AppBus,
+    pub jwt_cache: JwtCache,
+    pub entity_state: EntityStateManager,
+    pub environment_manager: Arc<EnvironmentManager>,
+    pub sessions: SessionRegistry,
+    /// Swapped out by /admin/world/reseed; use `generator()` for the current one
+    pub generator: Arc<std::sync::RwLock<Arc<EnvironmentGenerator>>>,
+    pub tick: Arc<TickBatcher>,
+    /// Per-session message recording (SESSION_RECORDING), None when disabled
+    pub recording: Option<RecordingConfig>,
+    /// Close WebSocket sessions that send nothing for this long
+    pub ws_idle_timeout: Duration,
+    /// Server-initiated ping cadence; a session that sends nothing within
+    /// `ws_pong_timeout` of a ping is treated as dead (half-open TCP)
+    pub ws_ping_interval: Duration,
+    pub ws_pong_timeout: Duration,
+    /// Background world boot; sessions generate their spawn chunks lazily until it completes
+    pub world_gen: Arc<GenerationProgress>,
+    /// Crafting recipes (reloadable via /admin/recipes/reload)
+    pub recipes: RecipeRegistry,
+    /// Game events shared with the managers (entity moves feed /stream/entities)
+    pub events: GameEventBus,
+    /// Anti-cheat mode shared with the managers (toggled via /admin/anticheat)
+    pub anticheat: AntiCheat,
+}
 
-pub async fn serve(
-    bus: AppBus,
-    jwt_cache: JwtCache,
-    entity_state: EntityStateManager,
-    environment_manager: Arc<EnvironmentManager>,
-) -> Result<()> {
-    // Env-configurable bind
-    let host = std::env::var("HTTP_HOST").unwrap_or_else(|_| "0.0.0.0".into());
-    let port: u16 = std::env::var("HTTP_PORT").ok().and_then(|s| s.parse().ok()).unwrap_or(4321);
-    let addr: SocketAddr = format!("{host}:{port}").parse()?;
+impl AppState {
+    /// Current world generator
+    pub fn generator(&self) -> Arc<EnvironmentGenerator> {
+        self.generator.read().unwrap().clone()
+    }
+}
+
+/* ------------------------------- serve() -------------------------------- */
 
+pub async fn serve(state: AppState, addr: SocketAddr) -> Result<()> {
     // Socket tuning (nodelay, keepalive, reuseaddr)
     let listener = tuned_listener(addr)?;
 
     info!("HTTP/WS listening on http://{addr}");
 
     // Build app
-    let app = router(bus, jwt_cache, entity_state, environment_manager);
+    let sessions = state.sessions.clone();
+    let app = router(state);
 
     // Axum/Hyper tuning
     axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+        .with_graceful_shutdown(shutdown_signal(sessions))
         .await?;
 
     Ok(())
@@ -60,15 +106,12 @@ pub async fn serve(
 
 /* ------------------------------- router() ------------------------------- */
 
-fn router(
-    bus: crate::core::AppBus,
-    jwt_cache: JwtCache,
-    entity_state: EntityStateManager,
-    environment_manager: Arc<EnvironmentManager>,
-) -> axum::Router {
+fn router(state: AppState) -> axum::Router {
     // bring trait for .and() on compression predicates
     use tower_http::compression::Predicate as _;
 
+    STARTED_AT.get_or_init(std::time::Instant::now);
+
     let max_inflight: usize = (num_cpus::get().max(1) * 1024) as usize;
 
     // Static asset configuration
@@ -76,9 +119,11 @@ fn router(
 
     // Compress only when default rules allow AND body > 1 KiB
     // NOTE: Static assets are pre-compressed, so this only applies to dynamic routes
+    // NDJSON streams are left alone so each line reaches the client as it's written
     let compression = tower_http::compression::CompressionLayer::new().compress_when(
         tower_http::compression::predicate::DefaultPredicate::new()
-            .and(tower_http::compression::predicate::SizeAbove::new(1024)),
+            .and(tower_http::compression::predicate::SizeAbove::new(1024))
+            .and(tower_http::compression::predicate::NotForContentType::const_new(NDJSON_CONTENT_TYPE)),
     );
 
     // Build middleware stack with HandleErrorLayer for fallible services
@@ -94,18 +139,9 @@ fn router(
         // Compression layer (skipped for precompressed static assets)
         .layer(compression)
         // Handle errors from fallible middleware BEFORE applying them
-        .layer(axum::error_handling::HandleErrorLayer::new(
-            |err: tower::BoxError| async move {
-                if err.is::<tower::timeout::error::Elapsed>() {
-                    (axum::http::StatusCode::REQUEST_TIMEOUT, "request timed out")
-                } else if err.is::<tower::load_shed::error::Overloaded>() {
-                    (axum::http::StatusCode::SERVICE_UNAVAILABLE, "service overloaded")
-                } else {
-                    tracing::warn!(error = %err, "middleware error");
-                    (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "internal server error")
-                }
-            },
-        ))
+        // Plain-text 413s (body limit layer, body extractors) become JSON errors
+        .layer(axum::middleware::map_response(json_payload_too_large))
+        .layer(axum::error_handling::HandleErrorLayer::new(handle_middleware_error))
         // Fallible middleware layers (innermost)
         .timeout(std::time::Duration::from_secs(10))
         .concurrency_limit(max_inflight)
@@ -123,12 +159,34 @@ fn router(
     // Note: "/" is handled by static index.html from Astro
     let dynamic_router = axum::Router::new()
         .route("/health", axum::routing::get(health))
+        .route("/ready", axum::routing::get(ready))
         .route("/echo", axum::routing::post(echo))
+        .route("/stats", axum::routing::get(stats))
+        .route("/harvest", axum::routing::post(harvest))  // REST harvest for clients without a WebSocket
+        .route("/recipes", axum::routing::get(list_recipes))
+        .route("/openapi.json", axum::routing::get(openapi))
         .route("/ws", axum::routing::get(ws_upgrade))  // WebSocket for both browser and Unity clients
         // Optional: Add dynamic Askama routes
         // .route("/dashboard", axum::routing::get(crate::astro::askama::private_dashboard))
         // .route("/page/*path", axum::routing::get(crate::astro::askama::dynamic_page_handler))
-        .with_state((bus, jwt_cache, entity_state, environment_manager));
+        // Admin routes: need SUPABASE_SERVICE_ROLE_KEY as Bearer token, 503 when it isn't configured
+        .route("/admin/kick", axum::routing::post(admin_kick))
+        .route("/admin/runtime", axum::routing::get(admin_runtime))
+        .route("/admin/world/reseed", axum::routing::post(admin_reseed))
+        .route("/admin/env/respawn", axum::routing::post(admin_respawn_object))
+        .route("/admin/recipes/reload", axum::routing::post(admin_reload_recipes))
+        .route("/admin/audit/harvests", axum::routing::get(admin_harvest_audit))
+        .route("/admin/entities", axum::routing::get(admin_entities))
+        .route("/admin/entities/move", axum::routing::post(admin_move_entity))
+        .route("/admin/entities/damage", axum::routing::post(admin_damage_entity))
+        .route("/admin/entities/team", axum::routing::post(admin_set_entity_team))
+        .route("/admin/anticheat", axum::routing::get(admin_anticheat_status).post(admin_set_anticheat_mode))
+        .route("/admin/acks", axum::routing::get(admin_ack_stats))
+        .route("/admin/disconnect_all", axum::routing::post(admin_disconnect_all))
+        .route("/admin/recordings/replay", axum::routing::post(admin_replay_recording))
+        .route("/stream/entities", axum::routing::get(stream_entities))
+        .route("/debug/noise", axum::routing::get(debug_noise))
+        .with_state(state);
 
     // Merge static and dynamic routers, then apply middleware
     static_router
@@ -140,6 +198,46 @@ fn router(
 
 
 
+/* ---------------------------- Error responses --------------------------- */
+
+/// Structured error body for middleware failures: `{ "error": "...", "code": "..." }`
+#[derive(Serialize)]
+struct ApiError {
+    error: &'static str,
+    code: &'static str,
+}
+
+fn json_error(status: StatusCode, code: &'static str, error: &'static str) -> Response {
+    (status, Json(ApiError { error, code })).into_response()
+}
+
+/// Map errors from fallible middleware (timeout, load shed, body limit) to JSON responses
+async fn handle_middleware_error(err: tower::BoxError) -> Response {
+    if err.is::<tower::timeout::error::Elapsed>() {
+        json_error(StatusCode::REQUEST_TIMEOUT, "timeout", "request timed out")
+    } else if err.is::<tower::load_shed::error::Overloaded>() {
+        json_error(StatusCode::SERVICE_UNAVAILABLE, "overloaded", "service overloaded")
+    } else if err.is::<http_body_util::LengthLimitError>() {
+        json_error(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", "request body too large")
+    } else {
+        tracing::warn!(error = %err, "middleware error");
+        json_error(StatusCode::INTERNAL_SERVER_ERROR, "internal", "internal server error")
+    }
+}
+
+/// RequestBodyLimitLayer and the body extractors reject oversized bodies with plain-text 413s
+async fn json_payload_too_large(response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .is_some_and(|ct| ct.as_bytes().starts_with(b"application/json"));
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        json_error(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", "request body too large")
+    } else {
+        response
+    }
+}
+
 /* ------------------------------- Handlers ------------------------------- */
 
 async fn root() -> impl IntoResponse {
@@ -150,6 +248,18 @@ async fn health() -> impl IntoResponse {
     "OK"
 }
 
+/// Readiness: 503 with generation progress until the initial world area is generated
+async fn ready(State(state): State<AppState>) -> impl IntoResponse {
+    let status = state.world_gen.status();
+    let code = if status.complete { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let label = if status.complete { "ready" } else { "generating" };
+    (code, Json(serde_json::json!({
+        "status": label,
+        "chunks_done": status.chunks_done,
+        "chunks_total": status.chunks_total,
+    })))
+}
+
 #[derive(Deserialize)]
 struct EchoIn {
     name: String,
@@ -160,12 +270,667 @@ struct EchoOut {
     message: String,
 }
 
-async fn echo(State((bus, _, _, _)): State<(AppBus, JwtCache, EntityStateManager, Arc<EnvironmentManager>)>, Json(input): Json<EchoIn>) -> impl IntoResponse {
+async fn echo(State(state): State<AppState>, format: ResponseFormat, Json(input): Json<EchoIn>) -> impl IntoResponse {
     use tokio::sync::oneshot;
     let (tx, rx) = oneshot::channel();
-    let _ = bus.tx.send(AppCmd::Hello { name: input.name, reply: tx }).await;
+    let _ = state.bus.tx.send(AppCmd::Hello { name: input.name, reply: tx }).await;
     let message = rx.await.unwrap_or_else(|_| "unavailable".into());
-    Json(EchoOut { message })
+    Negotiated(format, EchoOut { message })
+}
+
+async fn stats(State(state): State<AppState>, format: ResponseFormat) -> impl IntoResponse {
+    use tokio::sync::oneshot;
+    let (tx, rx) = oneshot::channel();
+    if state.bus.tx.send(AppCmd::Stats { reply: tx }).await.is_err() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "unavailable").into_response();
+    }
+    match rx.await {
+        Ok(snapshot) => Negotiated(format, snapshot).into_response(),
+        Err(_) => (StatusCode::SERVICE_UNAVAILABLE, "unavailable").into_response(),
+    }
+}
+
+/// All crafting recipes currently loaded
+async fn list_recipes(State(state): State<AppState>, format: ResponseFormat) -> impl IntoResponse {
+    Negotiated(format, state.recipes.list())
+}
+
+/// Machine-readable description of the HTTP API (see `openapi.rs`)
+async fn openapi() -> impl IntoResponse {
+    Json(crate::transports::openapi::document())
+}
+
+/// Harvest an environment object over plain HTTP (bots/tooling)
+/// Same validation as the WebSocket `harvest_object` message
+async fn harvest(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Json(request): Json<HarvestObjectRequest>,
+) -> impl IntoResponse {
+    let auth_user = match authenticate_request(&state.jwt_cache, &headers).await {
+        Ok(user) => user,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    if let Some(refused) = check_harvest_reward(auth_user.user_id(), &request.object_id, &state.entity_state, &state.environment_manager) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(refused)).into_response();
+    }
+    let tool = state.entity_state.get_equipped_tool(auth_user.user_id());
+    let response = state.environment_manager.handle_harvest_request(auth_user.user_id(), request, tool.as_deref());
+    if response.success {
+        grant_harvest_reward(auth_user.user_id(), &response, &state.entity_state);
+        broadcast_object_harvested(&response.object_id, auth_user.user_id(), &state.environment_manager, &state.sessions);
+    }
+    let status = if response.success { StatusCode::OK } else { StatusCode::UNPROCESSABLE_ENTITY };
+    (status, Json(response)).into_response()
+}
+
+/* ------------------------------ Admin routes ----------------------------- */
+
+/// Admin routes are only available with a service role key configured
+/// (/admin/kick, /admin/runtime, /admin/world/reseed, /admin/env/respawn, /admin/recipes/reload,
+/// /admin/audit/harvests, /admin/entities, /admin/entities/move, /admin/anticheat, /admin/acks, /admin/disconnect_all, /stream/entities)
+fn admin_enabled() -> bool {
+    crate::auth::jwt_cache::has_service_role()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AdminRejection {
+    /// No service role key configured: 503 `{ "error": "admin disabled" }`
+    Disabled,
+    /// Missing or wrong credentials: 403
+    Forbidden,
+}
+
+impl IntoResponse for AdminRejection {
+    fn into_response(self) -> Response {
+        match self {
+            AdminRejection::Disabled => json_error(StatusCode::SERVICE_UNAVAILABLE, "admin_disabled", "admin disabled"),
+            AdminRejection::Forbidden => (StatusCode::FORBIDDEN, "forbidden").into_response(),
+        }
+    }
+}
+
+/// Require the service role key as Bearer token for admin routes
+fn require_admin(headers: &http::HeaderMap) -> Result<(), AdminRejection> {
+    authorize_admin(admin_enabled(), headers)
+}
+
+fn authorize_admin(enabled: bool, headers: &http::HeaderMap) -> Result<(), AdminRejection> {
+    if !enabled {
+        warn!("Admin route called but service role key is not configured");
+        return Err(AdminRejection::Disabled);
+    }
+    match extract_token_from_header(headers) {
+        Ok(token) if crate::auth::jwt_cache::is_service_role_key(&token) => Ok(()),
+        _ => {
+            warn!("Admin route rejected: invalid service role credentials");
+            Err(AdminRejection::Forbidden)
+        }
+    }
+}
+
+/// Process start, recorded when the router is first built (for uptime reporting)
+static STARTED_AT: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+
+#[derive(Serialize)]
+struct RuntimeInfo {
+    version: &'static str,
+    /// Global allocator actually in use ("jemalloc" or "system")
+    allocator: &'static str,
+    /// The jemalloc feature is on but the target can't use it, so the system allocator is active
+    jemalloc_fallback: bool,
+    /// Cargo features compiled into this build
+    features: Vec<&'static str>,
+    uptime_secs: u64,
+    tokio_workers: usize,
+    /// Environment object ids that failed to parse since startup
+    object_id_parse_failures: u64,
+}
+
+impl RuntimeInfo {
+    fn current() -> Self {
+        let jemalloc_requested = cfg!(feature = "jemalloc");
+        let jemalloc_active = jemalloc_requested && cfg!(not(target_env = "msvc"));
+        let mut features = Vec::new();
+        if jemalloc_requested {
+            features.push("jemalloc");
+        }
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            allocator: if jemalloc_active { "jemalloc" } else { "system" },
+            jemalloc_fallback: jemalloc_requested && !jemalloc_active,
+            features,
+            uptime_secs: STARTED_AT.get().map(|t| t.elapsed().as_secs()).unwrap_or(0),
+            tokio_workers: tokio::runtime::Handle::current().metrics().num_workers(),
+            object_id_parse_failures: crate::game::environment_gen::object_id_parse_failures(),
+        }
+    }
+}
+
+/// Build/runtime diagnostics (allocator, features, version, uptime, worker count)
+async fn admin_runtime(headers: http::HeaderMap) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    Json(RuntimeInfo::current()).into_response()
+}
+
+#[derive(Deserialize)]
+struct ReseedRequest {
+    seed: u64,
+}
+
+#[derive(Serialize)]
+struct ReseedResponse {
+    seed: u64,
+    objects: usize,
+}
+
+/// Regenerate the world with a new seed and tell every connected client to reload
+async fn admin_reseed(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Json(request): Json<ReseedRequest>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+
+    // A boot still filling the old world would mix its chunks into the new one
+    state.world_gen.cancel_and_wait().await;
+
+    let generator = Arc::new(state.generator().with_seed(request.seed));
+    *state.generator.write().unwrap() = generator.clone();
+
+    // Clearing first makes any harvest still targeting the old world fail with NotFound
+    let environment_manager = state.environment_manager.clone();
+    let generated = tokio::task::spawn_blocking(move || {
+        environment_manager.clear_objects();
+        crate::game::ChunkCoord { x: 0, z: 0 }
+            .neighbors(INITIAL_AREA_RADIUS)
+            .iter()
+            .map(|chunk| environment_manager.ensure_chunk_generated(&generator, chunk))
+            .sum::<usize>()
+    })
+    .await;
+    let objects = match generated {
+        Ok(objects) => objects,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    for user_id in state.sessions.connected_user_ids() {
+        state.sessions.send_to(&user_id, ServerMessage::WorldReset { seed: request.seed });
+    }
+
+    warn!(seed = request.seed, objects, "World reseeded by an administrator");
+    Json(ReseedResponse { seed: request.seed, objects }).into_response()
+}
+
+#[derive(Deserialize)]
+struct RespawnRequest {
+    object_id: String,
+}
+
+#[derive(Serialize)]
+struct RespawnResponse {
+    object_id: String,
+    notified: usize,
+}
+
+/// Force an object to respawn now (events, testing) and show it to players watching its chunk
+async fn admin_respawn_object(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Json(request): Json<RespawnRequest>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+
+    let respawn = match state.environment_manager.respawn_object(&request.object_id) {
+        Ok(respawn) => respawn,
+        Err(e) => return (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+    };
+    let notified = broadcast_object_respawned(&respawn.object_data, &state.environment_manager, &state.sessions);
+
+    info!(object_id = %request.object_id, notified, "Object force-respawned by an administrator");
+    Json(RespawnResponse { object_id: request.object_id, notified }).into_response()
+}
+
+#[derive(Serialize)]
+struct ReloadRecipesResponse {
+    recipes: usize,
+}
+
+/// Re-read the recipe file without a restart (the old set stays if the file is invalid)
+async fn admin_reload_recipes(State(state): State<AppState>, headers: http::HeaderMap) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    match state.recipes.reload() {
+        Ok(recipes) => {
+            info!(recipes, "Crafting recipes reloaded by an administrator");
+            Json(ReloadRecipesResponse { recipes }).into_response()
+        }
+        Err(e) => {
+            warn!(error = %e, "Recipe reload failed, keeping current recipes");
+            (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response()
+        }
+    }
+}
+
+/// Default and max entries returned by /admin/audit/harvests
+const DEFAULT_AUDIT_LIMIT: usize = 100;
+const MAX_AUDIT_LIMIT: usize = 1000;
+
+#[derive(Deserialize)]
+struct HarvestAuditQuery {
+    player: Option<String>,
+    limit: Option<usize>,
+}
+
+/// Recent harvest attempts, newest first (optionally for one player)
+async fn admin_harvest_audit(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Query(query): Query<HarvestAuditQuery>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT).min(MAX_AUDIT_LIMIT);
+    Json(state.environment_manager.harvest_audit().recent(query.player.as_deref(), limit)).into_response()
+}
+
+/// Default and max entities per /admin/entities page
+const DEFAULT_ENTITY_PAGE_LIMIT: usize = 100;
+const MAX_ENTITY_PAGE_LIMIT: usize = 1000;
+
+#[derive(Deserialize)]
+struct EntityPageQuery {
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct EntityPage {
+    entities: Vec<EntityState>,
+    total: usize,
+    offset: usize,
+    limit: usize,
+}
+
+/// Tracked entities one page at a time, ordered by entity id
+async fn admin_entities(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Query(query): Query<EntityPageQuery>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_ENTITY_PAGE_LIMIT).min(MAX_ENTITY_PAGE_LIMIT);
+    let (entities, total) = state.entity_state.get_all_entities_paginated(query.offset, limit);
+    Json(EntityPage { entities, total, offset: query.offset, limit }).into_response()
+}
+
+#[derive(Deserialize)]
+struct MoveEntityRequest {
+    entity_id: String,
+    position: Position,
+    rotation: Option<Rotation>,
+}
+
+/// Move a server-driven entity (NPC, enemy, boss), e.g. from scripted events or tooling
+/// Players are refused: their position comes from their own session.
+/// The move reaches clients at the entity type's sync rate (ENTITY_SYNC_HZ)
+async fn admin_move_entity(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Json(request): Json<MoveEntityRequest>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    match state.entity_state.get_entity(&request.entity_id) {
+        None => return (StatusCode::NOT_FOUND, format!("Entity {} not found", request.entity_id)).into_response(),
+        Some(entity) if entity.entity_type == EntityType::Player => {
+            return (StatusCode::UNPROCESSABLE_ENTITY, "Players can only be moved by their own session").into_response();
+        }
+        Some(_) => {}
+    }
+    state.entity_state.update_position(&request.entity_id, request.position, request.rotation);
+    let Some(moved) = state.entity_state.get_entity(&request.entity_id) else {
+        return (StatusCode::NOT_FOUND, format!("Entity {} not found", request.entity_id)).into_response();
+    };
+
+    let mut delta = EntityDelta::new(&moved.entity_id);
+    delta.position = Some(moved.position);
+    delta.rotation = Some(moved.rotation);
+    state.tick.record_update(moved.entity_type, delta);
+    debug!(entity_id = %moved.entity_id, entity_type = ?moved.entity_type, position = ?moved.position, "Entity moved by an administrator");
+    Json(moved.public()).into_response()
+}
+
+#[derive(Deserialize)]
+struct DamageEntityRequest {
+    attacker_id: String,
+    target_id: String,
+    amount: f32,
+}
+
+/// Deal damage from one entity to another (NPC AI, scripted events, tooling)
+/// Goes through `apply_damage`, so team, PvP zone and liveness rules apply; refused
+/// hits return 422 with the reason. The health change reaches clients on the next tick
+async fn admin_damage_entity(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Json(request): Json<DamageEntityRequest>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    let target = match state.entity_state.apply_damage(&request.attacker_id, &request.target_id, request.amount) {
+        Ok(target) => target,
+        Err(message) => return (StatusCode::UNPROCESSABLE_ENTITY, message).into_response(),
+    };
+
+    let mut delta = EntityDelta::new(&target.entity_id);
+    delta.health = Some(target.health);
+    delta.is_alive = Some(target.is_alive);
+    state.tick.record_update(target.entity_type, delta);
+    debug!(attacker_id = %request.attacker_id, target_id = %target.entity_id, amount = request.amount, health = target.health, "Damage applied by an administrator");
+    Json(target.public()).into_response()
+}
+
+#[derive(Deserialize)]
+struct EntityTeamRequest {
+    entity_id: String,
+    /// None takes the entity off its team
+    team: Option<String>,
+}
+
+/// Put an entity on a team (teammates can't damage each other)
+async fn admin_set_entity_team(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Json(request): Json<EntityTeamRequest>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    if !state.entity_state.set_team(&request.entity_id, request.team.clone()) {
+        return (StatusCode::NOT_FOUND, format!("Entity {} not found", request.entity_id)).into_response();
+    }
+    info!(entity_id = %request.entity_id, team = ?request.team, "Entity team set by an administrator");
+    match state.entity_state.get_entity(&request.entity_id) {
+        Some(entity) => Json(entity.public()).into_response(),
+        None => (StatusCode::NOT_FOUND, format!("Entity {} not found", request.entity_id)).into_response(),
+    }
+}
+
+/// Anti-cheat mode, tolerance and violation counts
+async fn admin_anticheat_status(State(state): State<AppState>, headers: http::HeaderMap) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    Json(state.anticheat.status()).into_response()
+}
+
+/// Client ack counters and latency across all WebSocket sessions
+async fn admin_ack_stats(State(state): State<AppState>, headers: http::HeaderMap) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    Json(state.sessions.ack_metrics().stats()).into_response()
+}
+
+#[derive(Deserialize)]
+struct AntiCheatModeRequest {
+    mode: AntiCheatMode,
+}
+
+/// Switch between enforcing and shadowing (log-only) anti-cheat validation
+async fn admin_set_anticheat_mode(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Json(request): Json<AntiCheatModeRequest>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    state.anticheat.set_mode(request.mode);
+    info!(mode = ?request.mode, "Anti-cheat mode changed by admin");
+    Json(state.anticheat.status()).into_response()
+}
+
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Live entity moves as newline-delimited JSON, for dashboards without a WebSocket
+/// The stream ends when the client disconnects (the body, and its subscription, is dropped)
+async fn stream_entities(State(state): State<AppState>, headers: http::HeaderMap) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    let events = state.events.subscribe();
+    // Every subscriber holds a copy of each event until it reads it, so watch the count
+    info!(subscribers = state.events.subscriber_count(), "Entity stream opened");
+    let updates = futures_util::stream::unfold(events, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event @ GameEvent::EntityMoved { .. }) => {
+                    let mut line = serde_json::to_vec(&event).unwrap_or_default();
+                    line.push(b'\n');
+                    return Some((Ok::<_, std::convert::Infallible>(line), rx));
+                }
+                Ok(_) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "Entity stream fell behind, dropping updates");
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    (
+        [(http::header::CONTENT_TYPE, NDJSON_CONTENT_TYPE)],
+        axum::body::Body::from_stream(updates),
+    )
+        .into_response()
+}
+
+#[derive(Deserialize)]
+struct KickRequest {
+    user_id: String,
+    reason: Option<String>,
+    #[serde(default)]
+    ban: bool,
+}
+
+#[derive(Serialize)]
+struct KickResponse {
+    user_id: String,
+    kicked: bool,
+    banned: bool,
+}
+
+/// Kick a connected player (and optionally ban them from reconnecting)
+async fn admin_kick(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Json(request): Json<KickRequest>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+
+    let reason = request.reason.unwrap_or_else(|| "Kicked by an administrator".to_string());
+    if request.ban {
+        state.sessions.ban(&request.user_id, &reason);
+    }
+    let kicked = state.sessions.kick(&request.user_id, &reason);
+
+    if !kicked && !request.ban {
+        return (StatusCode::NOT_FOUND, "user not connected").into_response();
+    }
+
+    info!(user_id = %request.user_id, kicked, banned = request.ban, "Admin kick processed");
+    Json(KickResponse {
+        user_id: request.user_id,
+        kicked,
+        banned: request.ban,
+    }).into_response()
+}
+
+#[derive(Deserialize)]
+struct DisconnectAllRequest {
+    reason: Option<String>,
+    /// Seconds between the warning and the disconnect (default: immediately, capped
+    /// at MAX_DISCONNECT_GRACE_SECS)
+    #[serde(default)]
+    grace_seconds: u64,
+}
+
+/// Longest grace period /admin/disconnect_all will wait before closing sessions
+const MAX_DISCONNECT_GRACE_SECS: u64 = 600;
+
+#[derive(Serialize)]
+struct DisconnectAllResponse {
+    /// Sessions warned (those still connected when the grace period ends are closed)
+    notified: usize,
+    /// Effective grace period after capping
+    grace_seconds: u64,
+}
+
+/// Disconnect everyone for a maintenance window
+/// Warns every session with `ShuttingDown`, then after the grace period saves player
+/// progress and closes all sessions with the shutdown close code and the given reason
+async fn admin_disconnect_all(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Json(request): Json<DisconnectAllRequest>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+
+    let reason = request.reason.unwrap_or_else(|| CloseCode::Shutdown.reason().to_string());
+    let grace_seconds = request.grace_seconds.min(MAX_DISCONNECT_GRACE_SECS);
+    let notified = state.sessions.broadcast(&ServerMessage::ShuttingDown { reason: reason.clone(), grace_seconds });
+    warn!(notified, grace_seconds, reason = %reason, "Admin disconnect of all sessions scheduled");
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(grace_seconds)).await;
+        let saved = state.entity_state.checkpoint_blocking().await;
+        let closed = state.sessions.close_all_with_reason(CloseCode::Shutdown, &reason);
+        info!(saved, closed, "Disconnected all sessions for maintenance");
+    });
+
+    (StatusCode::ACCEPTED, Json(DisconnectAllResponse { notified, grace_seconds })).into_response()
+}
+
+#[derive(Deserialize)]
+struct ReplayRecordingRequest {
+    /// File name inside SESSION_RECORDING_DIR, e.g. `<user_id>-<session_id>.json`
+    file: String,
+}
+
+#[derive(Serialize)]
+struct ReplayRecordingResponse {
+    user_id: String,
+    /// Handler replies to the recorded inbound messages, in order
+    responses: Vec<ServerMessage>,
+}
+
+/// Replay a recorded session against an empty copy of the world
+/// The live world and players are untouched; the replies show how the session plays out
+async fn admin_replay_recording(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Json(request): Json<ReplayRecordingRequest>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+    let Some(dir) = state.recording.as_ref().and_then(|c| c.dir.clone()) else {
+        return (StatusCode::NOT_FOUND, "session recording directory not configured").into_response();
+    };
+    // Only plain file names, so the request can't read outside the recording directory
+    if std::path::Path::new(&request.file).file_name() != Some(std::ffi::OsStr::new(&request.file)) {
+        return (StatusCode::BAD_REQUEST, "file must be a file name inside the recording directory").into_response();
+    }
+
+    let path = dir.join(&request.file);
+    let log = match tokio::task::spawn_blocking(move || SessionLog::load(path)).await {
+        Ok(Ok(log)) => log,
+        Ok(Err(e)) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) => {
+            return (StatusCode::NOT_FOUND, "recording not found").into_response();
+        }
+        Ok(Err(e)) => {
+            warn!(file = %request.file, error = %e, "Failed to load session recording");
+            return (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response();
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let entity_state = EntityStateManager::new(120);
+    let environment_manager = Arc::new(state.environment_manager.empty_copy());
+    let responses = crate::transports::recorder::replay(&log, &entity_state, &environment_manager).await;
+    info!(file = %request.file, user_id = %log.user_id, responses = responses.len(), "Replayed session recording");
+    Json(ReplayRecordingResponse { user_id: log.user_id, responses }).into_response()
+}
+
+/// Max samples per axis for /debug/noise (keeps responses bounded)
+const MAX_NOISE_GRID: usize = 256;
+
+#[derive(Deserialize)]
+struct NoiseQuery {
+    x0: f32,
+    z0: f32,
+    w: usize,
+    h: usize,
+    layer: NoiseLayer,
+    /// World units between samples (default 1.0)
+    step: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct NoiseGrid {
+    layer: NoiseLayer,
+    x0: f32,
+    z0: f32,
+    w: usize,
+    h: usize,
+    step: f32,
+    values: Vec<Vec<f32>>,
+}
+
+/// Sample a generator noise layer over a world rectangle (for tuning noise parameters)
+async fn debug_noise(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    Query(query): Query<NoiseQuery>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_admin(&headers) {
+        return rejection.into_response();
+    }
+
+    let step = query.step.unwrap_or(1.0);
+    if query.w == 0 || query.h == 0 || query.w > MAX_NOISE_GRID || query.h > MAX_NOISE_GRID || step <= 0.0 {
+        return (StatusCode::BAD_REQUEST, "w/h must be 1..=256 and step > 0").into_response();
+    }
+
+    let values = state.generator().sample_noise_grid(query.layer, query.x0, query.z0, query.w, query.h, step);
+    Json(NoiseGrid {
+        layer: query.layer,
+        x0: query.x0,
+        z0: query.z0,
+        w: query.w,
+        h: query.h,
+        step,
+        values,
+    }).into_response()
 }
 
 /* ---------------------------- WebSocket path ---------------------------- */
@@ -175,11 +940,35 @@ async fn echo(State((bus, _, _, _)): State<(AppBus, JwtCache, EntityStateManager
 #[derive(Deserialize)]
 struct WsQuery {
     token: Option<String>,
+    /// Supabase tenant to verify the token against (defaults to the token's issuer)
+    tenant: Option<String>,
+    /// Optional snapshot codec ("zstd" for compressed binary initial objects)
+    codec: Option<String>,
+    /// Watch without joining: no entity is created and mutating messages are rejected
+    #[serde(default)]
+    spectator: bool,
+    /// Spectator region center (world coordinates, defaults to spawn)
+    x: Option<f32>,
+    z: Option<f32>,
+    /// Requested view distance in chunks (clamped to the server's; players only)
+    view_distance: Option<i32>,
+    /// Send JSON frames larger than this many bytes as compressed binary
+    compress_above: Option<usize>,
+    /// Frame compression algorithm ("zstd" or "deflate", default zstd)
+    compress: Option<String>,
 }
 
+/// WebSocket upgrade for browser and Unity clients
+///
+/// Compression: axum 0.8 (tungstenite 0.28) doesn't implement `permessage-deflate`, so an
+/// offered extension is declined - the response carries no `Sec-WebSocket-Extensions` and,
+/// per RFC 7692, the client sends uncompressed frames. Large payloads use `?codec=zstd`
+/// and `?compress_above=<bytes>` instead (see `codec.rs`). Supporting deflate needs a custom upgrade: answer the handshake
+/// by hand via `hyper::upgrade::on`, wrap the upgraded IO in a WebSocket implementation with
+/// the extension, and drive `ws_loop` over it (it only needs a Sink/Stream of `Message`).
 async fn ws_upgrade(
     ws: WebSocketUpgrade,
-    State((bus, jwt_cache, entity_state, environment_manager)): State<(AppBus, JwtCache, EntityStateManager, Arc<EnvironmentManager>)>,
+    State(state): State<AppState>,
     Query(query): Query<WsQuery>,
     req: Request<axum::body::Body>,
 ) -> impl IntoResponse {
@@ -194,31 +983,22 @@ async fn ws_upgrade(
         "WebSocket upgrade request received"
     );
 
-    // Extract JWT token from Authorization header OR query parameter
-    let token = match extract_token_from_header(&parts.headers) {
-        Ok(t) => {
-            debug!(token_len = t.len(), "JWT token extracted from Authorization header");
-            t
-        }
-        Err(header_err) => {
-            // Fallback: Try to extract from query parameter (for browser WebSocket API)
-            if let Some(t) = query.token {
-                debug!(token_len = t.len(), "JWT token extracted from query parameter");
-                t
-            } else {
-                warn!(
-                    header_error = %header_err,
-                    "WebSocket connection rejected: no valid auth token in header or query"
-                );
-                return (StatusCode::UNAUTHORIZED, "Missing or invalid auth token").into_response();
-            }
-        }
+    // Extract JWT token from Authorization header OR query parameter (header wins)
+    let header_token = extract_token_from_header(&parts.headers);
+    let header_error = header_token.as_ref().err().cloned();
+    let Some((token, source)) = select_ws_token(header_token.ok(), query.token) else {
+        warn!(
+            header_error = ?header_error,
+            "WebSocket connection rejected: no valid auth token in header or query"
+        );
+        return (StatusCode::UNAUTHORIZED, "Missing or invalid auth token").into_response();
     };
+    debug!(token_len = token.len(), source = ?source, "JWT token extracted");
 
     // Verify JWT using cache (fast path) or Supabase API (slow path)
     debug!("Starting JWT verification for WebSocket connection");
     let verification_start = std::time::Instant::now();
-    let token_info = match jwt_cache.verify_and_cache(&token).await {
+    let token_info = match state.jwt_cache.verify_and_cache_for(&token, query.tenant.as_deref()).await {
         Ok(info) => {
             let verification_duration = verification_start.elapsed();
             info!(
@@ -261,21 +1041,44 @@ async fn ws_upgrade(
         return (StatusCode::UNAUTHORIZED, "Token expired").into_response();
     }
 
-    // Create AuthUser from token info
-    let auth_user = AuthUser {
-        claims: crate::auth::Claims {
-            sub: token_info.user_id.clone(),
-            iat: 0, // Not needed for WebSocket session
-            exp: token_info.expires_at,
-            iss: "supabase".to_string(),
-            role: token_info.role.clone(),
-            email: token_info.email.clone(),
-            phone: None,
-            app_metadata: None,
-            user_metadata: None,
-        },
-        token: token.clone(),
-    };
+    // Duplicate connections: refuse early when the policy keeps the existing session
+    if state.sessions.duplicate_policy() == DuplicateSessionPolicy::RejectNew
+        && state.sessions.is_connected(&token_info.user_id)
+    {
+        warn!(user_id = %token_info.user_id, "WebSocket connection rejected: already connected");
+        return (StatusCode::CONFLICT, "Already connected").into_response();
+    }
+
+    // Reject banned users before upgrading
+    if let Some(reason) = state.sessions.ban_reason(&token_info.user_id) {
+        warn!(
+            user_id = %token_info.user_id,
+            reason = %reason,
+            "WebSocket connection rejected: user is banned"
+        );
+        return (StatusCode::FORBIDDEN, format!("Banned: {}", reason)).into_response();
+    }
+
+    // Player cap: spectators and admins still get in so a full server can be observed/managed
+    if !query.spectator && token_info.role != ADMIN_ROLE {
+        if let Some((current, max)) = state.entity_state.player_capacity_exceeded(&token_info.user_id) {
+            warn!(
+                user_id = %token_info.user_id,
+                current,
+                max,
+                "WebSocket connection rejected: server full"
+            );
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(http::header::RETRY_AFTER, SERVER_FULL_RETRY_AFTER.as_secs().to_string())],
+                Json(ServerMessage::ServerFull { current, max }),
+            )
+                .into_response();
+        }
+    }
+
+    // Create AuthUser from token info
+    let auth_user = auth_user_from_token_info(&token_info, token.clone());
 
     info!(
         user_id = %auth_user.user_id(),
@@ -284,15 +1087,102 @@ async fn ws_upgrade(
         "WebSocket upgrade successful, starting connection loop"
     );
 
+    let options = SessionOptions {
+        codec: SnapshotCodec::negotiate(query.codec.as_deref()),
+        frames: FramePolicy::negotiate(query.compress_above, query.compress.as_deref()),
+        spectate: query.spectator
+            .then(|| Position::new(query.x.unwrap_or(0.0), 0.0, query.z.unwrap_or(0.0))),
+        view_distance: query.view_distance,
+    };
+
     // Set sizes to defend allocations; tune to your needs
     ws.max_message_size(1 << 20) // 1 MiB per message
         .max_frame_size(1 << 20)
         .on_upgrade(move |socket| {
             debug!(user_id = %auth_user.user_id(), "WebSocket connection upgraded, entering message loop");
-            ws_loop(socket, bus, auth_user, entity_state, environment_manager)
+            let (sink, stream) = socket.split();
+            ws_loop(sink, stream, state, auth_user, options)
         })
 }
 
+/// Build an AuthUser from cached/verified token info
+fn auth_user_from_token_info(token_info: &crate::auth::jwt_cache::TokenInfo, token: String) -> AuthUser {
+    AuthUser {
+        claims: crate::auth::Claims {
+            sub: token_info.user_id.clone(),
+            iat: 0, // Not needed for server-side sessions
+            exp: token_info.expires_at,
+            iss: "supabase".to_string(),
+            role: token_info.role.clone(),
+            email: token_info.email.clone(),
+            phone: None,
+            app_metadata: token_info.app_metadata.clone(),
+            user_metadata: None,
+        },
+        token,
+    }
+}
+
+/// Authenticate a plain HTTP request using the Bearer token and the JWT cache
+async fn authenticate_request(jwt_cache: &JwtCache, headers: &http::HeaderMap) -> Result<AuthUser, (StatusCode, String)> {
+    use crate::auth::jwt_cache::AuthCacheError;
+
+    let token = extract_token_from_header(headers)
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e))?;
+
+    let token_info = match jwt_cache.verify_and_cache(&token).await {
+        Ok(info) => info,
+        Err(AuthCacheError::InvalidToken(msg)) => {
+            warn!(error = %msg, "HTTP request rejected: invalid token");
+            return Err((StatusCode::UNAUTHORIZED, format!("Invalid token: {}", msg)));
+        }
+        Err(e) => {
+            error!(error = %e, "HTTP JWT verification failed: internal error");
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Authentication service error".to_string()));
+        }
+    };
+
+    if token_info.is_expired() {
+        return Err((StatusCode::UNAUTHORIZED, "Token expired".to_string()));
+    }
+
+    Ok(auth_user_from_token_info(&token_info, token))
+}
+
+/// How often a live session re-checks its token expiry
+const TOKEN_EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Warning message if a token expiring at `expires_at` is within the grace period
+fn token_expiry_warning(expires_at: i64, grace_period_secs: i64) -> Option<ServerMessage> {
+    let remaining = expires_at - chrono::Utc::now().timestamp();
+    (remaining <= grace_period_secs).then_some(ServerMessage::TokenExpiringSoon { expires_at })
+}
+
+/// Where the WebSocket auth token was taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenSource {
+    Header,
+    Query,
+    /// Both were sent with different tokens; the header's was used
+    HeaderOverridingQuery,
+}
+
+/// Pick the WebSocket auth token
+/// A valid `Authorization: Bearer` header always takes precedence over `?token=` (the query
+/// parameter exists for browsers, which can't set headers on WebSocket upgrades). Two
+/// different tokens on one request is suspicious (token confusion), so it is logged.
+fn select_ws_token(header: Option<String>, query: Option<String>) -> Option<(String, TokenSource)> {
+    match (header, query) {
+        (Some(header), Some(query)) if header != query => {
+            warn!("WebSocket upgrade sent different tokens in header and query; using the header token");
+            Some((header, TokenSource::HeaderOverridingQuery))
+        }
+        (Some(header), _) => Some((header, TokenSource::Header)),
+        (None, Some(query)) => Some((query, TokenSource::Query)),
+        (None, None) => None,
+    }
+}
+
 fn extract_token_from_header(headers: &http::HeaderMap) -> Result<String, String> {
     let auth_header = headers
         .get(http::header::AUTHORIZATION)
@@ -309,66 +1199,285 @@ fn extract_token_from_header(headers: &http::HeaderMap) -> Result<String, String
     Ok(auth_str[7..].to_string())
 }
 
-async fn ws_loop(
-    mut socket: WebSocket,
-    _bus: AppBus,
+/// Token role exempt from the player cap (Supabase service-role JWTs)
+const ADMIN_ROLE: &str = "service_role";
+
+/// How long clients refused for a full server should wait before reconnecting
+const SERVER_FULL_RETRY_AFTER: Duration = Duration::from_secs(15);
+
+/// Send a `Disconnecting` notice (with an optional backoff hint), then the close frame
+/// Send errors are ignored: the connection is going away either way
+async fn close_session<Tx>(
+    socket: &mut Tx,
+    frames: &FramePolicy,
+    sequence: &mut OutboundSequence,
+    code: CloseCode,
+    reason: &str,
+    retry_after_ms: Option<u64>,
+)
+where
+    Tx: Sink<Message> + Unpin,
+{
+    let notice = ServerMessage::Disconnecting { reason: reason.to_string(), retry_after_ms };
+    if let Ok(json) = serde_json::to_string(&notice) {
+        let _ = socket.send(frames.encode(sequence.stamp(json))).await;
+    }
+    let frame = CloseFrame { code: code.code(), reason: truncate_reason(reason).to_string().into() };
+    let _ = socket.send(Message::Close(Some(frame))).await;
+}
+
+/// Per-connection options negotiated from the upgrade query
+#[derive(Debug, Clone, Copy, Default)]
+struct SessionOptions {
+    codec: SnapshotCodec,
+    frames: FramePolicy,
+    /// Makes this a spectator session watching the region around that position
+    spectate: Option<Position>,
+    /// Requested view distance in chunks (players only)
+    view_distance: Option<i32>,
+}
+
+/// Per-connection message loop
+/// Generic over the socket halves so sessions can be driven by mock sockets in tests
+async fn ws_loop<Tx, Rx>(
+    mut socket: Tx,
+    mut stream: Rx,
+    state: AppState,
     auth_user: AuthUser,
-    entity_state: EntityStateManager,
-    environment_manager: Arc<EnvironmentManager>,
-) {
-    use tokio::sync::oneshot;
+    options: SessionOptions,
+)
+where
+    Tx: Sink<Message> + Unpin,
+    Tx::Error: std::fmt::Display,
+    Rx: Stream<Item = Result<Message, axum::Error>> + Unpin,
+{
+    let AppState { jwt_cache, entity_state, environment_manager, sessions, generator, tick, recording, ws_idle_timeout, ws_ping_interval, ws_pong_timeout, world_gen, .. } = state;
+    let SessionOptions { codec, frames, spectate, view_distance } = options;
 
     let user_id = auth_user.user_id();
     let user_email = auth_user.email().map(|s| s.to_string());
-    info!(user_id = %user_id, "WebSocket session starting, sending welcome message");
+    info!(user_id = %user_id, "WebSocket session starting");
 
-    // Send welcome message with user info
-    let welcome_msg = format!(
-        "{{\"type\":\"connected\",\"user_id\":\"{}\",\"role\":\"{}\"}}",
-        user_id,
-        auth_user.role()
-    );
-    if let Err(e) = socket.send(Message::Text(welcome_msg.into())).await {
-        error!(user_id = %user_id, error = %e, "Failed to send welcome message");
-        return;
+    // Every JSON frame this session sends carries the next `seq` (see sequence.rs)
+    let mut sequence = OutboundSequence::new();
+
+    // Register first so a refused duplicate never touches the existing session's entity
+    let (session_id, mut commands) = match sessions.register(user_id) {
+        Ok(registered) => registered,
+        Err(e) => {
+            warn!(user_id = %user_id, "WebSocket session refused: {}", e);
+            close_session(&mut socket, &frames, &mut sequence, CloseCode::SessionReplaced, "Already connected", None).await;
+            return;
+        }
+    };
+
+    // Players are spawned on connect; spectators watch the requested region
+    if spectate.is_none() {
+        entity_state.set_inventory_slots(user_id, auth_user.inventory_slots());
+        if let Some(requested) = view_distance {
+            environment_manager.set_player_view_distance(user_id, requested);
+        }
     }
+    let self_entity = match spectate {
+        Some(_) => None,
+        None => match spawn_player(user_id, &user_email, &entity_state, &tick) {
+            Some(entity) => Some(entity),
+            None => {
+                // Overload is transient: tell the client when to come back instead of letting it hammer reconnects
+                warn!(user_id = %user_id, "Server full, closing new session with a retry hint");
+                sessions.unregister(user_id, session_id);
+                let retry_after_ms = SERVER_FULL_RETRY_AFTER.as_millis() as u64;
+                close_session(&mut socket, &frames, &mut sequence, CloseCode::ServerFull, CloseCode::ServerFull.reason(), Some(retry_after_ms)).await;
+                return;
+            }
+        },
+    };
+    let spawn_position = spectate
+        .or_else(|| self_entity.as_ref().map(|e| e.position))
+        .unwrap_or_default();
 
-    // Send initial environment objects (spawn area: chunk 0,0 + surrounding chunks)
-    let spawn_chunk = crate::game::ChunkCoord { x: 0, z: 0 };
+    // Visible chunks around spawn (players use their own view distance)
+    let chunks = match spectate {
+        Some(_) => environment_manager.get_nearby_chunks(&spawn_position),
+        None => environment_manager.get_nearby_chunks_for_player(user_id, &spawn_position),
+    };
 
-    // Generate chunk list (7x7 grid around spawn)
-    let mut chunks = Vec::new();
-    for dx in -3..=3 {
-        for dz in -3..=3 {
-            chunks.push(crate::game::ChunkCoord {
-                x: spawn_chunk.x + dx,
-                z: spawn_chunk.z + dz,
-            });
+    // World boot still running: generate whatever spawn chunks aren't ready yet
+    if !world_gen.is_complete() {
+        let generator = generator.read().unwrap().clone();
+        for chunk in &chunks {
+            environment_manager.ensure_chunk_generated(&generator, chunk);
         }
     }
 
-    let initial_objects = environment_manager.get_objects_in_chunks(&chunks);
+    // Track the visible chunks: chunk refreshes, object broadcasts and tick deltas
+    // are all scoped by them (spectators only watch their fixed region)
+    let initial_objects = match spectate {
+        Some(_) => {
+            environment_manager.set_player_chunks(user_id, &chunks);
+            environment_manager.get_objects_in_chunks_network(&chunks)
+        }
+        None => environment_manager.send_initial_objects(user_id, &spawn_position).objects,
+    };
 
-    let objects_json: Vec<serde_json::Value> = initial_objects.iter()
-        .filter_map(|obj| serde_json::to_value(obj).ok())
+    // Welcome: identity, spawn, nearby players and (for JSON clients) the object snapshot in one frame
+    let nearby_players: Vec<PublicEntityState> = entity_state
+        .get_all_players()
+        .iter()
+        .filter(|player| chunks.contains(&environment_manager.chunk_for_position(&player.position)))
+        .map(EntityState::public)
         .collect();
-
-    let env_msg = ServerMessage::EnvironmentObjects {
-        objects: objects_json,
+    let nearby_objects: Vec<serde_json::Value> = match codec {
+        SnapshotCodec::Json => initial_objects
+            .iter()
+            .filter_map(|obj| serde_json::to_value(obj).ok())
+            .collect(),
+        SnapshotCodec::ZstdBincode => Vec::new(),
+    };
+    let (player_count, object_count) = (nearby_players.len(), nearby_objects.len());
+    let welcome = ServerMessage::Welcome {
+        user_id: user_id.to_string(),
+        role: auth_user.role().to_string(),
+        spectator: spectate.is_some(),
+        spawn_position,
+        biome: environment_manager.chunk_biome(&environment_manager.chunk_for_position(&spawn_position)),
+        nearby_players,
+        nearby_objects,
+        server_tick: tick.current_tick(),
+    };
+    let welcome_sent = match serde_json::to_string(&welcome) {
+        Ok(json) => socket.send(frames.encode(sequence.stamp(json))).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
     };
+    if let Err(e) = welcome_sent {
+        error!(user_id = %user_id, error = %e, "Failed to send welcome message");
+        if sessions.unregister(user_id, session_id) {
+            environment_manager.remove_player(user_id);
+            if spectate.is_none() {
+                entity_state.remove_entity(user_id);
+            }
+        }
+        return;
+    }
+    info!(user_id = %user_id, player_count, object_count, "Sent welcome to client");
 
-    if let Ok(env_json) = serde_json::to_string(&env_msg) {
-        if let Err(e) = socket.send(Message::Text(env_json.into())).await {
-            error!(user_id = %user_id, error = %e, "Failed to send initial environment objects");
-        } else {
-            info!(user_id = %user_id, object_count = initial_objects.len(), "Sent initial environment objects to player");
+    if codec == SnapshotCodec::ZstdBincode {
+        // Single compressed binary frame (bincode + zstd) for dense worlds
+        let snapshot = EnvironmentObjectsSpawnMessage { objects: initial_objects };
+        match encode_snapshot(&snapshot) {
+            Ok(bytes) => {
+                let compressed_len = bytes.len();
+                if let Err(e) = socket.send(Message::Binary(bytes.into())).await {
+                    error!(user_id = %user_id, error = %e, "Failed to send compressed environment snapshot");
+                } else {
+                    info!(
+                        user_id = %user_id,
+                        object_count = snapshot.objects.len(),
+                        compressed_bytes = compressed_len,
+                        "Sent compressed environment snapshot to player"
+                    );
+                }
+            }
+            Err(e) => {
+                error!(user_id = %user_id, error = %e, "Failed to encode compressed environment snapshot");
+            }
         }
     }
 
     info!(user_id = %user_id, "WebSocket session active, listening for messages");
 
+    let mut recorder = recording
+        .as_ref()
+        .map(|config| SessionRecorder::new(user_id, user_email.clone(), config.capacity));
+
+    // First tick fires immediately, so near-expiry tokens are warned at session start
+    let mut expiry_check = tokio::time::interval(TOKEN_EXPIRY_CHECK_INTERVAL);
+    let mut expiry_warned = false;
+
+    // Reset on every inbound frame (including pings)
+    let idle = tokio::time::sleep(ws_idle_timeout);
+    tokio::pin!(idle);
+
+    // Keepalive: ping on an interval; any inbound frame counts as the answer
+    let mut keepalive = tokio::time::interval_at(tokio::time::Instant::now() + ws_ping_interval, ws_ping_interval);
+    let pong_deadline = tokio::time::sleep(ws_pong_timeout);
+    tokio::pin!(pong_deadline);
+    let mut awaiting_pong = false;
+
     let mut message_count = 0u64;
-    while let Some(result) = socket.next().await {
+    loop {
+        let result = tokio::select! {
+            _ = &mut idle => {
+                info!(user_id = %user_id, idle_secs = ws_idle_timeout.as_secs(), "Closing idle WebSocket session");
+                close_session(&mut socket, &frames, &mut sequence, CloseCode::Timeout, "Idle timeout", None).await;
+                break;
+            }
+            _ = &mut pong_deadline, if awaiting_pong => {
+                warn!(user_id = %user_id, timeout_ms = ws_pong_timeout.as_millis() as u64, "No answer to keepalive ping, closing dead WebSocket session");
+                close_session(&mut socket, &frames, &mut sequence, CloseCode::Timeout, "Keepalive timeout", None).await;
+                break;
+            }
+            _ = keepalive.tick(), if !awaiting_pong => {
+                if let Err(e) = socket.send(Message::Ping(Vec::new().into())).await {
+                    error!(user_id = %user_id, error = %e, "Failed to send keepalive ping");
+                    break;
+                }
+                awaiting_pong = true;
+                pong_deadline.as_mut().reset(tokio::time::Instant::now() + ws_pong_timeout);
+                continue;
+            }
+            _ = expiry_check.tick() => {
+                if auth_user.claims.exp <= chrono::Utc::now().timestamp() {
+                    info!(user_id = %user_id, expires_at = auth_user.claims.exp, "Token expired, closing WebSocket session");
+                    close_session(&mut socket, &frames, &mut sequence, CloseCode::AuthFailed, CloseCode::AuthFailed.reason(), None).await;
+                    break;
+                }
+                if expiry_warned {
+                    continue;
+                }
+                if let Some(warning) = token_expiry_warning(auth_user.claims.exp, jwt_cache.grace_period_secs()) {
+                    info!(user_id = %user_id, expires_at = auth_user.claims.exp, "Token near expiry, warning client");
+                    expiry_warned = true;
+                    let json = serde_json::to_string(&warning).unwrap_or_default();
+                    if let Err(e) = socket.send(frames.encode(sequence.stamp(json))).await {
+                        error!(user_id = %user_id, error = %e, "Failed to send token expiry warning");
+                        break;
+                    }
+                }
+                continue;
+            }
+            command = commands.recv() => {
+                match command {
+                    Some(SessionCommand::Send(msg)) => {
+                        if let Some(recorder) = recorder.as_mut() {
+                            recorder.record_outbound(&msg);
+                        }
+                        let json = serde_json::to_string(&msg)
+                            .unwrap_or_else(|_| "{\"type\":\"error\",\"message\":\"serialization failed\"}".to_string());
+                        if let Err(e) = socket.send(frames.encode(sequence.stamp(json))).await {
+                            error!(user_id = %user_id, error = %e, "Failed to send queued message");
+                            break;
+                        }
+                        continue;
+                    }
+                    Some(SessionCommand::Close { code, reason, retry_after_ms }) => {
+                        info!(user_id = %user_id, close_code = code.code(), reason = %reason, ?retry_after_ms, "Server closing WebSocket session");
+                        close_session(&mut socket, &frames, &mut sequence, code, &reason, retry_after_ms).await;
+                        break;
+                    }
+                    None => break,
+                }
+            }
+            next = stream.next() => match next {
+                Some(result) => {
+                    idle.as_mut().reset(tokio::time::Instant::now() + ws_idle_timeout);
+                    awaiting_pong = false;
+                    result
+                }
+                None => break,
+            },
+        };
+
         match result {
             Ok(msg) => {
                 message_count += 1;
@@ -384,19 +1493,69 @@ async fn ws_loop(
                         );
 
                         // Try to parse as game message
-                        match serde_json::from_str::<GameMessage>(&text_str) {
+                        match GameMessage::parse(&text_str) {
+                            Ok(GameMessage::Ack { seq }) => {
+                                // Acks only feed metrics; nothing is sent back
+                                match sequence.ack(seq) {
+                                    Ok(record) => {
+                                        if record.skipped > 0 {
+                                            debug!(user_id = %user_id, seq, skipped = record.skipped, "Client skipped acks");
+                                        }
+                                        sessions.ack_metrics().record(&record);
+                                    }
+                                    Err(reason) => {
+                                        debug!(user_id = %user_id, seq, last_sent = sequence.last_sent(), ?reason, "Ignored client ack");
+                                        sessions.ack_metrics().record_rejected();
+                                    }
+                                }
+                            }
                             Ok(game_msg) => {
-                                // Handle game-specific messages
-                                let response = handle_game_message(game_msg, &user_id, &user_email, &entity_state, &environment_manager).await;
+                                if let Some(recorder) = recorder.as_mut() {
+                                    recorder.record_inbound(&game_msg);
+                                }
+                                // Handle game-specific messages (spectators may only read)
+                                let response = if spectate.is_some() && !game_msg.is_read_only() {
+                                    debug!(user_id = %user_id, "Rejected mutating message from spectator");
+                                    ServerMessage::Error {
+                                        message: "Spectators can't modify game state".to_string(),
+                                    }
+                                } else {
+                                    handle_game_message(game_msg, &user_id, &user_email, &entity_state, &environment_manager, &sessions, &tick).await
+                                };
+                                if let Some(recorder) = recorder.as_mut() {
+                                    recorder.record_outbound(&response);
+                                }
+                                // Other players see this change in the next batched tick delta
+                                tick.record(&response);
+                                match &response {
+                                    ServerMessage::HarvestResult { object_id, success: true, .. } => {
+                                        broadcast_object_harvested(object_id, user_id, &environment_manager, &sessions);
+                                    }
+                                    ServerMessage::HarvestAllResult { results } => {
+                                        for harvested in results {
+                                            broadcast_object_harvested(&harvested.object_id, user_id, &environment_manager, &sessions);
+                                        }
+                                    }
+                                    _ => {}
+                                }
                                 let response_json = serde_json::to_string(&response)
                                     .unwrap_or_else(|_| "{\"type\":\"error\",\"message\":\"serialization failed\"}".to_string());
 
-                                if let Err(e) = socket.send(Message::Text(response_json.into())).await {
+                                if let Err(e) = socket.send(frames.encode(sequence.stamp(response_json))).await {
+                                    error!(user_id = %user_id, error = %e, "Failed to send game response");
+                                    break;
+                                }
+                            }
+                            Err(e @ (GameMessageError::TooLarge { .. } | GameMessageError::TooDeep { .. })) => {
+                                warn!(user_id = %user_id, error = %e, "Rejected oversized game message");
+                                let response = serde_json::to_string(&ServerMessage::Error { message: e.to_string() })
+                                    .unwrap_or_else(|_| "{\"type\":\"error\",\"message\":\"serialization failed\"}".to_string());
+                                if let Err(e) = socket.send(frames.encode(sequence.stamp(response))).await {
                                     error!(user_id = %user_id, error = %e, "Failed to send game response");
                                     break;
                                 }
                             }
-                            Err(_) => {
+                            Err(GameMessageError::Invalid(_)) => {
                                 // Not a game message, echo back for compatibility
                                 debug!(user_id = %user_id, "Received non-game message, echoing back");
                                 let response = format!(
@@ -405,7 +1564,7 @@ async fn ws_loop(
                                     serde_json::to_string(&text_str).unwrap_or_else(|_| "\"invalid\"".to_string())
                                 );
 
-                                if let Err(e) = socket.send(Message::Text(response.into())).await {
+                                if let Err(e) = socket.send(Message::Text(sequence.stamp(response).into())).await {
                                     error!(user_id = %user_id, error = %e, "Failed to send echo response");
                                     break;
                                 }
@@ -463,13 +1622,37 @@ async fn ws_loop(
                     messages_exchanged = message_count,
                     "WebSocket error, closing connection"
                 );
+                close_session(&mut socket, &frames, &mut sequence, CloseCode::ProtocolError, CloseCode::ProtocolError.reason(), None).await;
                 break;
             }
         }
     }
 
-    // Clean up entity state when connection ends
-    if let Some(removed_entity) = entity_state.remove_entity(&user_id) {
+    let was_current = sessions.unregister(user_id, session_id);
+
+    if let (Some(recorder), Some(dir)) = (&recorder, recording.as_ref().and_then(|c| c.dir.as_ref())) {
+        if let Err(e) = recorder.write_to_dir(dir, session_id) {
+            warn!(user_id = %user_id, error = %e, "Failed to write session recording");
+        }
+    }
+
+    // A newer session for this user now owns the entity
+    if !was_current {
+        info!(user_id = %user_id, session_id, total_messages = message_count, "WebSocket session replaced by a newer connection");
+        return;
+    }
+
+    if spectate.is_some() {
+        environment_manager.remove_player(user_id);
+        info!(user_id = %user_id, total_messages = message_count, "Spectator session ended");
+        return;
+    }
+
+    // Clean up per-player environment state when connection ends; the entity itself
+    // lingers for a reconnect if a disconnect linger window is configured
+    environment_manager.remove_player(user_id);
+    if let Some(removed_entity) = entity_state.disconnect_player(user_id) {
+        tick.record_removed(user_id);
         info!(
             user_id = %user_id,
             total_messages = message_count,
@@ -485,13 +1668,67 @@ async fn ws_loop(
     }
 }
 
+/// The player's entity, spawning it if it isn't in the world yet (None if the world is full)
+/// Stale NPCs evicted to make room are queued as removals for the next tick
+fn spawn_player(user_id: &str, user_email: &Option<String>, entity_state: &EntityStateManager, tick: &TickBatcher) -> Option<EntityState> {
+    if let Some(existing) = entity_state.get_entity(user_id).filter(|e| e.entity_type == EntityType::Player) {
+        entity_state.reconnect_player(user_id);
+        return Some(existing);
+    }
+    // Generate display name from email (use part before @, or full user_id if no email)
+    let display_name = user_email
+        .as_ref()
+        .and_then(|email| email.split('@').next())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!("Player_{}", &user_id[..8]));
+    let entity = entity_state.add_player(user_id.to_string(), display_name);
+    for evicted_id in entity_state.take_evicted() {
+        tick.record_removed(&evicted_id);
+    }
+    entity
+}
+
+/// Chunk biome plus object spawns/despawns for a player who just entered a new chunk
+fn chunk_entry_messages(user_id: &str, outcome: &PositionUpdateOutcome, environment_manager: &EnvironmentManager) -> Vec<ServerMessage> {
+    let mut messages = Vec::new();
+    if let Some(biome) = environment_manager.chunk_biome(&outcome.new_chunk) {
+        messages.push(ServerMessage::ChunkInfo {
+            chunk_x: outcome.new_chunk.x,
+            chunk_z: outcome.new_chunk.z,
+            biome,
+        });
+    }
+    messages.extend(chunk_refresh_messages(user_id, &outcome.position, environment_manager));
+    messages
+}
+
+/// Object spawns/despawns that bring a player's visible chunks in line with their
+/// position and view distance
+fn chunk_refresh_messages(user_id: &str, position: &Position, environment_manager: &EnvironmentManager) -> Vec<ServerMessage> {
+    let (spawn, despawn) = environment_manager.update_player_chunks(user_id, position);
+    let mut messages = Vec::new();
+    if let Some(spawn) = spawn {
+        messages.push(ServerMessage::EnvironmentObjects {
+            objects: spawn.objects.iter()
+                .filter_map(|obj| serde_json::to_value(obj).ok())
+                .collect(),
+        });
+    }
+    if let Some(despawn) = despawn {
+        messages.push(ServerMessage::ObjectsDespawned { object_ids: despawn.object_ids, chunks: despawn.chunks });
+    }
+    messages
+}
+
 /// Handle game-specific messages from Unity clients
-async fn handle_game_message(
+pub(crate) async fn handle_game_message(
     msg: GameMessage,
     user_id: &str,
     user_email: &Option<String>,
     entity_state: &EntityStateManager,
     environment_manager: &Arc<EnvironmentManager>,
+    sessions: &SessionRegistry,
+    tick: &TickBatcher,
 ) -> ServerMessage {
     match msg {
         GameMessage::Ping => {
@@ -500,41 +1737,90 @@ async fn handle_game_message(
                 timestamp: chrono::Utc::now().timestamp(),
             }
         }
-        GameMessage::Join { position } => {
-            // Generate display name from email (use part before @, or full user_id if no email)
-            let display_name = user_email
-                .as_ref()
-                .and_then(|email| email.split('@').next())
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| format!("Player_{}", &user_id[..8]));
-
-            let mut entity = entity_state.add_player(user_id.to_string(), display_name);
+        GameMessage::Ack { .. } => ServerMessage::Error {
+            message: "Acks are only tracked on a WebSocket session".to_string(),
+        },
+        GameMessage::Join { position, view_distance } => {
+            let Some(mut entity) = spawn_player(user_id, user_email, entity_state, tick) else {
+                return ServerMessage::Error {
+                    message: "Server full".to_string(),
+                };
+            };
             if let Some(pos) = position {
                 entity_state.update_position(user_id, pos, None);
                 entity.position = pos;
             }
+            if let Some(requested) = view_distance {
+                environment_manager.set_player_view_distance(user_id, requested);
+            }
+            // A new position or view distance changes which chunks the player sees
+            if (position.is_some() || view_distance.is_some()) && environment_manager.is_tracking_player(user_id) {
+                for msg in chunk_refresh_messages(user_id, &entity.position, environment_manager) {
+                    sessions.send_to(user_id, msg);
+                }
+            }
             info!(
                 user_id = %user_id,
                 entity_type = ?entity.entity_type,
                 position = ?entity.position,
+                view_distance = environment_manager.get_player_view_distance(user_id),
                 "Player entity joined game"
             );
+            // Full inventory sync follows the reply; later changes go out as InventoryDelta
+            sessions.send_to(user_id, ServerMessage::InventoryUpdated {
+                user_id: user_id.to_string(),
+                inventory: entity.inventory,
+            });
             ServerMessage::Joined {
                 user_id: user_id.to_string(),
                 position: entity.position,
             }
         }
         GameMessage::UpdatePosition { position, rotation } => {
-            if let Some(updated_entity) = entity_state.update_position(user_id, position, rotation) {
-                ServerMessage::PlayerMoved {
-                    user_id: user_id.to_string(),
-                    position: updated_entity.position,
-                    rotation: updated_entity.rotation,
-                }
-            } else {
-                warn!(user_id = %user_id, "Received position update for non-existent entity");
-                ServerMessage::Error {
+            // Authority: the entity id always comes from the authenticated session, never the payload
+            if !entity_state.is_owned_by(user_id, user_id) {
+                warn!(user_id = %user_id, "Received position update for entity not owned by session");
+                return ServerMessage::Error {
                     message: "Player not in game. Send 'join' first.".to_string(),
+                };
+            }
+            if let Some(object_id) = environment_manager.blocking_object_at(&position) {
+                debug!(user_id = %user_id, object_id = %object_id, "Rejected move into a solid object");
+                if let Some(current) = entity_state.get_entity(user_id) {
+                    return ServerMessage::PositionCorrected {
+                        position: current.position,
+                        rotation: current.rotation,
+                        smooth: true,
+                    };
+                }
+            }
+            match entity_state.move_entity(user_id, position, rotation) {
+                Some(MoveResult::Moved(outcome)) => {
+                    let moved = ServerMessage::PlayerMoved {
+                        user_id: user_id.to_string(),
+                        position: outcome.position,
+                        rotation: outcome.rotation,
+                    };
+                    // Visible chunks only change on a chunk boundary, and only for tracked players;
+                    // the refresh is queued so it follows the move reply
+                    if outcome.moved && outcome.crossed_chunk && environment_manager.is_tracking_player(user_id) {
+                        debug!(user_id = %user_id, chunk_x = outcome.new_chunk.x, chunk_z = outcome.new_chunk.z, "Player crossed into a new chunk");
+                        for msg in chunk_entry_messages(user_id, &outcome, environment_manager) {
+                            sessions.send_to(user_id, msg);
+                        }
+                    }
+                    moved
+                }
+                Some(MoveResult::Rejected(last_valid)) => ServerMessage::PositionCorrected {
+                    position: last_valid.position,
+                    rotation: last_valid.rotation,
+                    smooth: true,
+                },
+                None => {
+                    warn!(user_id = %user_id, "Received position update for non-existent entity");
+                    ServerMessage::Error {
+                        message: "Player not in game. Send 'join' first.".to_string(),
+                    }
                 }
             }
         }
@@ -552,22 +1838,49 @@ async fn handle_game_message(
                 }
             }
         }
-        GameMessage::AddItem { item_id, quantity } => {
-            if let Some((success, inventory)) = entity_state.add_item(user_id, item_id.clone(), quantity) {
-                ServerMessage::ItemAdded {
-                    item_id,
-                    quantity,
-                    success,
-                }
-            } else {
-                warn!(user_id = %user_id, "Received add_item for non-existent entity");
-                ServerMessage::Error {
-                    message: "Player not in game. Send 'join' first.".to_string(),
+        GameMessage::Respawn => match entity_state.respawn_player(user_id) {
+            Ok((entity, outcome)) => {
+                let healed = ServerMessage::PlayerHealthChanged {
+                    user_id: user_id.to_string(),
+                    health: entity.health,
+                    is_alive: entity.is_alive,
+                };
+                let moved = ServerMessage::PlayerMoved {
+                    user_id: user_id.to_string(),
+                    position: outcome.position,
+                    rotation: outcome.rotation,
+                };
+                // Other players see the reset and the move in the next tick delta (as with regen)
+                tick.record(&healed);
+                tick.record(&moved);
+                // The respawning player gets the health reset as the reply, then the move
+                // and any chunk refresh
+                sessions.send_to(user_id, moved);
+                if outcome.crossed_chunk && environment_manager.is_tracking_player(user_id) {
+                    for msg in chunk_entry_messages(user_id, &outcome, environment_manager) {
+                        sessions.send_to(user_id, msg);
+                    }
                 }
+                healed
+            }
+            Err(message) => {
+                debug!(user_id = %user_id, reason = %message, "Respawn rejected");
+                ServerMessage::Error { message }
             }
+        },
+        GameMessage::AddItem { item_id, quantity } => {
+            // Items only enter an inventory through server-side grants (harvest rewards, refunds)
+            warn!(user_id = %user_id, item_id = %item_id, quantity = quantity, "Rejected unsolicited add_item from client");
+            ServerMessage::ItemAdded { item_id, quantity, success: false }
         }
         GameMessage::RemoveItem { item_id, quantity } => {
-            if let Some((success, inventory)) = entity_state.remove_item(user_id, &item_id, quantity) {
+            if let Some((success, _)) = entity_state.remove_item(user_id, &item_id, quantity) {
+                if success {
+                    sessions.send_to(user_id, ServerMessage::InventoryDelta {
+                        added: Vec::new(),
+                        removed: vec![(item_id.clone(), quantity)],
+                    });
+                }
                 ServerMessage::ItemRemoved {
                     item_id,
                     quantity,
@@ -598,8 +1911,19 @@ async fn handle_game_message(
                 }
             }
         }
+        GameMessage::EquipTool { item_id } => {
+            match entity_state.equip_tool(user_id, item_id.clone()) {
+                Some(true) => ServerMessage::ToolEquipped { item_id },
+                Some(false) => ServerMessage::Error {
+                    message: "Tool not in inventory".to_string(),
+                },
+                None => ServerMessage::Error {
+                    message: "Player not in game. Send 'join' first.".to_string(),
+                },
+            }
+        }
         GameMessage::GetState => {
-            let players = entity_state.get_all_players();
+            let players: Vec<PublicEntityState> = entity_state.get_all_players().iter().map(EntityState::public).collect();
             info!(
                 user_id = %user_id,
                 player_count = players.len(),
@@ -618,75 +1942,343 @@ async fn handle_game_message(
             }
         }
         GameMessage::HarvestObject { object_id, player_position } => {
-            use crate::game::{HarvestObjectRequest, Position as EnvPosition};
+            harvest_object(user_id, object_id, player_position, entity_state, environment_manager)
+        }
+        GameMessage::HarvestAllInRange { player_position } => {
+            harvest_all_in_range(user_id, player_position, entity_state, environment_manager)
+        }
+        GameMessage::InteractObject { object_id, action, player_position, target_position } => match action {
+            InteractAction::Examine => match environment_manager.examine_object(user_id, &object_id, &player_position) {
+                Ok(object) => ServerMessage::ObjectExamined {
+                    object_data: serde_json::to_value(object.to_network_data()).unwrap_or_default(),
+                    is_harvested: object.is_harvested,
+                    harvested_at: object.harvested_at,
+                },
+                Err(message) => ServerMessage::Error { message },
+            },
+            InteractAction::Harvest => harvest_object(user_id, object_id, player_position, entity_state, environment_manager),
+            InteractAction::Plant => {
+                // Range is checked from the server's position, not the one the client reports
+                let Some(planter) = entity_state.get_entity(user_id) else {
+                    return ServerMessage::Error {
+                        message: "Player not in game. Send 'join' first.".to_string(),
+                    };
+                };
+                // Validate before taking the seed so a rejected plant never needs a refund
+                let target = target_position.unwrap_or(planter.position);
+                if let Err(message) = environment_manager.check_plant(user_id, &planter.position, &target) {
+                    warn!(user_id = %user_id, error = %message, "Plant rejected");
+                    return ServerMessage::Error { message };
+                }
+                match entity_state.remove_item(user_id, SEED_ITEM_ID, 1) {
+                    Some((true, _)) => {}
+                    Some((false, _)) => {
+                        return ServerMessage::Error {
+                            message: "No seed in inventory".to_string(),
+                        };
+                    }
+                    None => {
+                        return ServerMessage::Error {
+                            message: "Player not in game. Send 'join' first.".to_string(),
+                        };
+                    }
+                }
+
+                match environment_manager.plant_sapling(user_id, &planter.position, target) {
+                    Ok(object_data) => ServerMessage::ObjectPlanted {
+                        object_data: serde_json::to_value(object_data).unwrap_or_default(),
+                    },
+                    Err(message) => {
+                        // Only reachable if something was planted in the way since the check
+                        if !matches!(entity_state.add_item(user_id, SEED_ITEM_ID.to_string(), 1), Some((true, _))) {
+                            error!(user_id = %user_id, "Plant failed and the seed could not be refunded");
+                        }
+                        warn!(user_id = %user_id, error = %message, "Plant failed");
+                        ServerMessage::Error { message }
+                    }
+                }
+            }
+        },
+    }
+}
 
-            // Create harvest request
+/// Validate and apply a harvest for the session's player
+fn harvest_object(
+    user_id: &str,
+    object_id: String,
+    player_position: Position,
+    entity_state: &EntityStateManager,
+    environment_manager: &EnvironmentManager,
+) -> ServerMessage {
+    // Don't consume the object if its reward can't be granted
+    let response = match check_harvest_reward(user_id, &object_id, entity_state, environment_manager) {
+        Some(refused) => refused,
+        None => {
             let request = HarvestObjectRequest {
                 object_id: object_id.clone(),
-                player_position: EnvPosition {
-                    x: player_position.x,
-                    y: player_position.y,
-                    z: player_position.z,
-                },
+                player_position,
             };
+            let tool = entity_state.get_equipped_tool(user_id);
+            environment_manager.handle_harvest_request(user_id, request, tool.as_deref())
+        }
+    };
 
-            // Handle harvest request
-            let response = environment_manager.handle_harvest_request(user_id, request);
-
-            if response.success {
-                info!(
-                    user_id = %user_id,
-                    object_id = %object_id,
-                    resource_type = ?response.resource_type,
-                    resource_amount = response.resource_amount,
-                    "Player harvested object successfully"
-                );
+    if response.success {
+        info!(
+            user_id = %user_id,
+            object_id = %object_id,
+            resource_type = ?response.resource_type,
+            resource_amount = response.resource_amount,
+            "Player harvested object successfully"
+        );
+        grant_harvest_reward(user_id, &response, entity_state);
 
-                // Convert single resource to list format
-                let resource_list = vec![(format!("{:?}", response.resource_type), response.resource_amount)];
+        // Convert single resource to list format
+        let resource_list = vec![(format!("{:?}", response.resource_type), response.resource_amount)];
 
-                ServerMessage::HarvestResult {
-                    object_id: response.object_id,
-                    success: true,
-                    message: "Harvested successfully".to_string(),
-                    resources: Some(resource_list),
-                }
-            } else {
-                let error_msg = response.error_message.as_deref().unwrap_or("Unknown error");
-                warn!(
-                    user_id = %user_id,
-                    object_id = %object_id,
-                    error = %error_msg,
-                    "Harvest failed"
-                );
-                ServerMessage::HarvestResult {
-                    object_id: response.object_id,
-                    success: false,
-                    message: error_msg.to_string(),
-                    resources: None,
-                }
-            }
+        ServerMessage::HarvestResult {
+            object_id: response.object_id,
+            success: true,
+            message: "Harvested successfully".to_string(),
+            resources: Some(resource_list),
+            error_code: None,
+            effect_id: response.effect_id,
+            sound_id: response.sound_id,
+        }
+    } else {
+        let error_msg = response.error_message.as_deref().unwrap_or("Unknown error");
+        warn!(
+            user_id = %user_id,
+            object_id = %object_id,
+            error = %error_msg,
+            "Harvest failed"
+        );
+        ServerMessage::HarvestResult {
+            object_id: response.object_id,
+            success: false,
+            message: error_msg.to_string(),
+            resources: None,
+            error_code: response.error_code,
+            effect_id: None,
+            sound_id: None,
         }
     }
 }
 
-/* ----------------------------- Socket tuning ---------------------------- */
-
-fn tuned_listener(addr: SocketAddr) -> Result<TcpListener> {
-    use socket2::{Socket, Domain, Type, Protocol};
-    let domain = match addr { SocketAddr::V4(_) => Domain::IPV4, SocketAddr::V6(_) => Domain::IPV6 };
-    let socket = Socket::new(domain, Type::STREAM, Some(Protocol::TCP))?;
+fn harvest_all_in_range(
+    user_id: &str,
+    player_position: Position,
+    entity_state: &EntityStateManager,
+    environment_manager: &EnvironmentManager,
+) -> ServerMessage {
+    if !environment_manager.harvest_all_enabled() {
+        return ServerMessage::Error {
+            message: "Harvest all is disabled".to_string(),
+        };
+    }
 
-    // Reuseaddr to speed restarts & readiness flips
-    socket.set_reuse_address(true)?;
-    // Keep connections alive (helps load balancers too)
-    socket.set_keepalive(true)?;
-    // Linux/Unix keepalive intervals (best effort)
-    #[cfg(any(target_os = "linux", target_os = "android"))]
-    {
-        use socket2::TcpKeepalive;
-        let ka = TcpKeepalive::new().with_time(Duration::from_secs(30)).with_interval(Duration::from_secs(10));
-        let _ = socket.set_tcp_keepalive(&ka);
+    // Only harvest what the player can receive: every granted resource costs an inventory op
+    // and either stacks onto an item or takes a free slot
+    let Some(mut projected) = entity_state.get_inventory(user_id) else {
+        return ServerMessage::Error {
+            message: "Player not in game. Send 'join' first.".to_string(),
+        };
+    };
+    let mut ops_left = entity_state.inventory_ops_remaining(user_id);
+    let mut refused = 0;
+    let tool = entity_state.get_equipped_tool(user_id);
+    let results = environment_manager.harvest_all_in_range(user_id, &player_position, tool.as_deref(), |resource_type| {
+        let Some(item_id) = resource_type.item_id() else {
+            return true;
+        };
+        if ops_left == 0 || !projected.add_item(item_id.to_string(), 1) {
+            refused += 1;
+            return false;
+        }
+        ops_left -= 1;
+        true
+    });
+    if refused > 0 {
+        debug!(user_id = %user_id, refused, "Harvest all clamped to inventory room and op budget");
+    }
+    for response in &results {
+        grant_harvest_reward(user_id, response, entity_state);
+    }
+    info!(user_id = %user_id, harvested = results.len(), "Player harvested all objects in range");
+    ServerMessage::HarvestAllResult { results }
+}
+
+/// Refuse a harvest before it happens if the player couldn't receive its resource
+/// (inventory full or over the inventory rate limit); None lets the harvest proceed
+fn check_harvest_reward(
+    user_id: &str,
+    object_id: &str,
+    entity_state: &EntityStateManager,
+    environment_manager: &EnvironmentManager,
+) -> Option<HarvestObjectResponse> {
+    let item_id = environment_manager.get_object(object_id)?.resource_type.item_id()?;
+    let (error_code, message) = if entity_state.inventory_ops_remaining(user_id) == 0 {
+        (HarvestErrorCode::RateLimited, "Too many inventory changes, slow down")
+    } else if !entity_state.get_inventory(user_id)?.can_add_item(item_id) {
+        (HarvestErrorCode::InventoryFull, "Inventory full")
+    } else {
+        return None;
+    };
+    debug!(user_id = %user_id, object_id = %object_id, item_id = %item_id, ?error_code, "Harvest refused before consuming the object");
+    Some(HarvestObjectResponse::failure(object_id.to_string(), user_id, error_code, message.to_string()))
+}
+
+/// Add a successful harvest's resources to the player's inventory
+fn grant_harvest_reward(user_id: &str, response: &HarvestObjectResponse, entity_state: &EntityStateManager) {
+    let Some(item_id) = response.resource_type.item_id() else {
+        return;
+    };
+    if response.resource_amount == 0 {
+        return;
+    }
+    if !matches!(entity_state.add_item(user_id, item_id.to_string(), response.resource_amount), Some((true, _))) {
+        warn!(user_id = %user_id, item_id = %item_id, amount = response.resource_amount, "Harvest reward not granted");
+    }
+}
+
+/* ------------------------------ Tick fan-out ----------------------------- */
+
+/// Run the fixed-rate tick loop, sending each batched delta to interested sessions
+pub async fn run_broadcast_tick(state: AppState, tick_rate_hz: u32) {
+    let AppState { sessions, environment_manager, tick, .. } = state;
+    crate::game::tick::run_tick_loop(tick, tick_rate_hz, |delta| {
+        fan_out_tick(&delta, &sessions, &environment_manager);
+    })
+    .await;
+}
+
+/// How often lingering disconnected players are checked for expiry
+const DISCONNECT_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Remove players whose reconnect window expired and tell everyone else they left
+pub async fn run_disconnect_sweep(state: AppState) {
+    let mut interval = tokio::time::interval(DISCONNECT_SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        for user_id in state.entity_state.expire_disconnected() {
+            state.tick.record_removed(&user_id);
+        }
+    }
+}
+
+/// How often passive health regen is applied
+const HEALTH_REGEN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Apply passive health regen and queue the changes for the next tick broadcast
+pub async fn run_health_regen(state: AppState) {
+    let mut interval = tokio::time::interval(HEALTH_REGEN_INTERVAL);
+    loop {
+        interval.tick().await;
+        for entity in state.entity_state.regen_health(HEALTH_REGEN_INTERVAL) {
+            state.tick.record(&ServerMessage::PlayerHealthChanged {
+                user_id: entity.entity_id,
+                health: entity.health,
+                is_alive: entity.is_alive,
+            });
+        }
+    }
+}
+
+/// Send one tick frame to every connected session (players and spectators)
+fn fan_out_tick(delta: &ServerMessage, sessions: &SessionRegistry, environment_manager: &EnvironmentManager) {
+    for user_id in sessions.connected_user_ids() {
+        if let Some(msg) = delta_for_recipient(delta, &user_id, environment_manager) {
+            sessions.send_to(&user_id, msg);
+        }
+    }
+}
+
+/// Tell everyone else within the object's broadcast radius that it was harvested
+/// Returns the number of players notified
+fn broadcast_object_harvested(
+    object_id: &str,
+    harvester: &str,
+    environment_manager: &EnvironmentManager,
+    sessions: &SessionRegistry,
+) -> usize {
+    environment_manager
+        .get_players_near_object(object_id)
+        .into_iter()
+        .filter(|player_id| player_id != harvester)
+        .filter(|player_id| sessions.send_to(player_id, ServerMessage::ObjectHarvested { object_id: object_id.to_string() }))
+        .count()
+}
+
+/// Tell everyone within the object's broadcast radius that it respawned
+/// Returns the number of players notified
+fn broadcast_object_respawned(
+    object_data: &EnvironmentObjectData,
+    environment_manager: &EnvironmentManager,
+    sessions: &SessionRegistry,
+) -> usize {
+    let msg = ServerMessage::ObjectRespawned {
+        object_id: object_data.object_id.clone(),
+        object_data: serde_json::to_value(object_data).unwrap_or_default(),
+    };
+    environment_manager
+        .get_players_near_object(&object_data.object_id)
+        .into_iter()
+        .filter(|player_id| sessions.send_to(player_id, msg.clone()))
+        .count()
+}
+
+/// Filter a tick delta down to what a recipient cares about
+/// Drops their own entity (they already got a direct reply) and positions outside their
+/// visible chunks; recipients without tracked chunks receive every change.
+/// Batches are filtered per message; other messages pass through unchanged
+fn delta_for_recipient(delta: &ServerMessage, recipient: &str, environment_manager: &EnvironmentManager) -> Option<ServerMessage> {
+    let (tick, entities, removed, timestamp) = match delta {
+        ServerMessage::GameStateDelta { tick, entities, removed, timestamp } => (tick, entities, removed, timestamp),
+        ServerMessage::Batch { messages } => {
+            let filtered = messages
+                .iter()
+                .filter_map(|msg| delta_for_recipient(msg, recipient, environment_manager))
+                .collect();
+            return crate::game::tick::coalesce(filtered);
+        }
+        other => return Some(other.clone()),
+    };
+    let visible = environment_manager.get_player_chunks(recipient);
+
+    let entities: Vec<_> = entities
+        .iter()
+        .filter(|e| e.entity_id != recipient)
+        .filter(|e| match (&visible, &e.position) {
+            (Some(chunks), Some(position)) => chunks.contains(&environment_manager.chunk_for_position(position)),
+            _ => true,
+        })
+        .cloned()
+        .collect();
+    let removed: Vec<_> = removed.iter().filter(|id| *id != recipient).cloned().collect();
+
+    if entities.is_empty() && removed.is_empty() {
+        return None;
+    }
+    Some(ServerMessage::GameStateDelta { tick: *tick, entities, removed, timestamp: *timestamp })
+}
+
+/* ----------------------------- Socket tuning ---------------------------- */
+
+fn tuned_listener(addr: SocketAddr) -> Result<TcpListener> {
+    use socket2::{Socket, Domain, Type, Protocol};
+    let domain = match addr { SocketAddr::V4(_) => Domain::IPV4, SocketAddr::V6(_) => Domain::IPV6 };
+    let socket = Socket::new(domain, Type::STREAM, Some(Protocol::TCP))?;
+
+    // Reuseaddr to speed restarts & readiness flips
+    socket.set_reuse_address(true)?;
+    // Keep connections alive (helps load balancers too)
+    socket.set_keepalive(true)?;
+    // Linux/Unix keepalive intervals (best effort)
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use socket2::TcpKeepalive;
+        let ka = TcpKeepalive::new().with_time(Duration::from_secs(30)).with_interval(Duration::from_secs(10));
+        let _ = socket.set_tcp_keepalive(&ka);
     }
 
     // Bind + listen
@@ -702,6 +2294,2000 @@ fn tuned_listener(addr: SocketAddr) -> Result<TcpListener> {
 
 /* ----------------------------- Shutdown hook ---------------------------- */
 
-async fn shutdown_signal() {
+/// Resolves on Ctrl+C or, on Unix, SIGTERM (what container orchestrators send)
+pub async fn shutdown_requested() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => warn!(error = %e, "Failed to listen for SIGTERM, waiting for Ctrl+C only"),
+        }
+    }
     let _ = tokio::signal::ctrl_c().await;
 }
+
+/// Wait for a shutdown signal, then tell every live WebSocket session the server is going away
+async fn shutdown_signal(sessions: SessionRegistry) {
+    shutdown_requested().await;
+    let closed = sessions.close_all(CloseCode::Shutdown);
+    info!(sessions = closed, "Shutting down, closed WebSocket sessions");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::jwt_cache::TokenInfo;
+    use crate::game::{EnvironmentObjectType, ResourceType};
+    use crate::game::fixtures::test_object;
+    use axum::body::Body;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    const TEST_TOKEN: &str = "test-token-harvester";
+
+    const TEST_SERVICE_KEY: &str = "test-service-role-key";
+
+    fn test_state() -> AppState {
+        let (bus, _rx) = crate::core::new_bus(8);
+        let jwt_cache = JwtCache::new("http://127.0.0.1:9".to_string(), "anon".to_string());
+        jwt_cache.insert(TEST_TOKEN.to_string(), TokenInfo {
+            user_id: "user-harvester-0001".to_string(),
+            email: None,
+            role: "authenticated".to_string(),
+            app_metadata: None,
+            expires_at: chrono::Utc::now().timestamp() + 3600,
+            verified_at: std::time::Instant::now(),
+        });
+        let anticheat = AntiCheat::default();
+        let environment_manager = Arc::new(EnvironmentManager::new(50.0, 3, 10.0).with_anticheat(anticheat.clone()));
+        environment_manager.add_object(test_object("tree_0_0_idx_0", 5.0, 5.0)).unwrap();
+        let events = GameEventBus::default();
+        AppState {
+            bus,
+            jwt_cache,
+            entity_state: EntityStateManager::new(120).with_event_bus(events.clone()).with_anticheat(anticheat.clone()),
+            environment_manager,
+            sessions: SessionRegistry::new(),
+            generator: Arc::new(std::sync::RwLock::new(Arc::new(EnvironmentGenerator::new(12345, 50.0)))),
+            tick: Arc::new(TickBatcher::new()),
+            recording: None,
+            ws_idle_timeout: Duration::from_secs(300),
+            ws_ping_interval: Duration::from_secs(30),
+            ws_pong_timeout: Duration::from_secs(10),
+            world_gen: Arc::new(GenerationProgress::completed()),
+            recipes: RecipeRegistry::default(),
+            events,
+            anticheat,
+        }
+    }
+
+    /// Run one game message for `user_id` against the state's managers
+    async fn handle(msg: GameMessage, user_id: &str, state: &AppState) -> ServerMessage {
+        handle_game_message(msg, user_id, &None, &state.entity_state, &state.environment_manager, &state.sessions, &state.tick).await
+    }
+
+    fn test_router() -> (axum::Router, Arc<EnvironmentManager>) {
+        let state = test_state();
+        let environment_manager = state.environment_manager.clone();
+        (router(state), environment_manager)
+    }
+
+    fn init_test_service_key() {
+        // Global OnceLock - ignore "already initialized" from other tests
+        let _ = crate::auth::jwt_cache::init_service_role_key(TEST_SERVICE_KEY.to_string());
+    }
+
+    fn test_auth_user(user_id: &str, expires_in: i64) -> AuthUser {
+        auth_user_from_token_info(&TokenInfo {
+            user_id: user_id.to_string(),
+            email: None,
+            role: "authenticated".to_string(),
+            app_metadata: None,
+            expires_at: chrono::Utc::now().timestamp() + expires_in,
+            verified_at: std::time::Instant::now(),
+        }, "mock-token".to_string())
+    }
+
+    /// Client side of a mock WebSocket driven by ws_loop
+    struct MockClient {
+        inbound: tokio::sync::mpsc::UnboundedSender<Message>,
+        outbound: tokio::sync::mpsc::UnboundedReceiver<Message>,
+        session: tokio::task::JoinHandle<()>,
+    }
+
+    impl MockClient {
+        async fn recv(&mut self) -> Option<Message> {
+            tokio::time::timeout(Duration::from_secs(5), self.outbound.recv())
+                .await
+                .expect("timed out waiting for server message")
+        }
+
+        /// Receive the next text frame parsed as JSON (skipping binary/control frames)
+        async fn recv_json(&mut self) -> serde_json::Value {
+            loop {
+                match self.recv().await {
+                    Some(Message::Text(text)) => return serde_json::from_str(text.as_str()).unwrap(),
+                    Some(_) => continue,
+                    None => panic!("session closed"),
+                }
+            }
+        }
+
+        fn send_json(&self, value: serde_json::Value) {
+            self.inbound.send(Message::Text(value.to_string().into())).unwrap();
+        }
+    }
+
+    /// Spawn ws_loop over in-memory channels and wait until the session is registered (or ended)
+    async fn spawn_mock_session(state: AppState, user_id: &str) -> MockClient {
+        spawn_mock_session_as(state, test_auth_user(user_id, 3600)).await
+    }
+
+    async fn spawn_mock_session_as(state: AppState, auth_user: AuthUser) -> MockClient {
+        spawn_mock_session_with(state, auth_user, None).await
+    }
+
+    async fn spawn_mock_session_with(state: AppState, auth_user: AuthUser, spectate: Option<Position>) -> MockClient {
+        spawn_mock_session_full(state, auth_user, SessionOptions { spectate, ..SessionOptions::default() }).await
+    }
+
+    async fn spawn_mock_session_full(state: AppState, auth_user: AuthUser, options: SessionOptions) -> MockClient {
+        let user_id = auth_user.user_id().to_string();
+        let (in_tx, in_rx) = tokio::sync::mpsc::unbounded_channel::<Message>();
+        let (out_tx, out_rx) = tokio::sync::mpsc::unbounded_channel::<Message>();
+
+        let sink = Box::pin(futures_util::sink::unfold(out_tx, |tx, msg: Message| async move {
+            tx.send(msg).map_err(|e| e.to_string())?;
+            Ok::<_, String>(tx)
+        }));
+        let stream = Box::pin(futures_util::stream::unfold(in_rx, |mut rx| async move {
+            rx.recv().await.map(|msg| (Ok::<_, axum::Error>(msg), rx))
+        }));
+
+        let sessions = state.sessions.clone();
+        let previous = sessions.current_session_id(&user_id);
+        let session = tokio::spawn(ws_loop(sink, stream, state, auth_user, options));
+        // Refused sessions end without staying registered
+        while sessions.current_session_id(&user_id) == previous && !session.is_finished() {
+            tokio::task::yield_now().await;
+        }
+        MockClient { inbound: in_tx, outbound: out_rx, session }
+    }
+
+    fn harvest_request(object_id: &str, position: Position) -> Request<Body> {
+        let body = serde_json::json!({ "objectId": object_id, "playerPosition": position });
+        Request::builder()
+            .method("POST")
+            .uri("/harvest")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_TOKEN}"))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn json_body(response: axum::response::Response) -> serde_json::Value {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_update_position_ignores_foreign_entity_id() {
+        let state = test_state();
+        let entity_state = &state.entity_state;
+        entity_state.add_player("user-victim-0001".to_string(), "victim".to_string());
+        entity_state.add_player("user-attacker-01".to_string(), "attacker".to_string());
+
+        // Payload claims to move the victim; the extra field must be ignored
+        let msg: GameMessage = serde_json::from_str(
+            r#"{"type":"update_position","entity_id":"user-victim-0001","position":{"x":99.0,"y":0.0,"z":99.0}}"#,
+        ).unwrap();
+        handle(msg, "user-attacker-01", &state).await;
+
+        let victim = entity_state.get_entity("user-victim-0001").unwrap();
+        let attacker = entity_state.get_entity("user-attacker-01").unwrap();
+        assert_eq!(victim.position.x, 0.0);
+        assert_eq!(attacker.position.x, 99.0);
+    }
+
+    #[tokio::test]
+    async fn test_too_fast_move_corrected_to_last_valid_position() {
+        let state = AppState { entity_state: EntityStateManager::new(120).with_max_move_speed(10.0), ..test_state() };
+        let user_id = "user-speedy-0001";
+        let join = GameMessage::Join { position: Some(Position::new(1.0, 0.0, 1.0)), view_distance: None };
+        handle(join, user_id, &state).await;
+
+        let step = GameMessage::UpdatePosition { position: Position::new(2.0, 0.0, 1.0), rotation: None };
+        let reply = handle(step, user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::PlayerMoved { .. }));
+
+        let teleport = GameMessage::UpdatePosition { position: Position::new(500.0, 0.0, 500.0), rotation: None };
+        let reply = handle(teleport, user_id, &state).await;
+        let ServerMessage::PositionCorrected { position, smooth, .. } = reply else {
+            panic!("expected a correction, got {reply:?}");
+        };
+        assert_eq!(position, Position::new(2.0, 0.0, 1.0));
+        assert!(smooth);
+        assert_eq!(state.entity_state.get_entity(user_id).unwrap().position, Position::new(2.0, 0.0, 1.0));
+    }
+
+    #[tokio::test]
+    async fn test_respawn_restores_health_and_moves_to_spawn() {
+        let state = AppState { entity_state: EntityStateManager::new(120).with_respawn_cooldown(Duration::ZERO), ..test_state() };
+        let user_id = "user-fallen-00001";
+        let join = GameMessage::Join { position: Some(Position::new(30.0, 0.0, 30.0)), view_distance: None };
+        handle(join, user_id, &state).await;
+
+        let reply = handle(GameMessage::Respawn, user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::Error { .. }), "living players can't respawn");
+
+        state.entity_state.update_health(user_id, 0.0);
+        let reply = handle(GameMessage::Respawn, user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::PlayerHealthChanged { health, is_alive: true, .. } if health == 100.0), "got {reply:?}");
+
+        // Everyone else learns about the reset and the move from the next tick
+        let Some(ServerMessage::GameStateDelta { entities, .. }) = state.tick.drain() else {
+            panic!("expected the respawn in the tick delta");
+        };
+        let delta = entities.iter().find(|e| e.entity_id == user_id).unwrap();
+        assert_eq!((delta.health, delta.is_alive), (Some(100.0), Some(true)));
+        assert_eq!(delta.position, Some(Position::default()));
+    }
+
+    #[tokio::test]
+    async fn test_respawn_reply_then_move_over_session() {
+        let mut state = test_state();
+        state.entity_state = EntityStateManager::new(120).with_respawn_cooldown(Duration::ZERO);
+        let user_id = "user-fallen-00002";
+        let mut client = spawn_mock_session(state.clone(), user_id).await;
+        assert_eq!(client.recv_json().await["type"], "welcome");
+        state.entity_state.update_position(user_id, Position::new(30.0, 0.0, 30.0), None);
+        state.entity_state.update_health(user_id, 0.0);
+
+        client.send_json(serde_json::json!({ "type": "respawn" }));
+        let reply = client.recv_json().await;
+        assert_eq!((reply["type"].as_str(), reply["is_alive"].as_bool()), (Some("player_health_changed"), Some(true)));
+        let moved = client.recv_json().await;
+        assert_eq!(moved["type"], "player_moved");
+        assert_eq!(moved["position"]["x"], 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_collision_checks_reject_moves_into_solid_objects() {
+        let state = AppState {
+            environment_manager: Arc::new(EnvironmentManager::new(50.0, 3, 10.0).with_collision_checks(0.4)),
+            ..test_state()
+        };
+        let (entity_state, environment_manager) = (&state.entity_state, &state.environment_manager);
+        environment_manager.add_object(test_object("tree_0_0_idx_0", 5.0, 5.0)).unwrap();
+        let user_id = "user-bumper-00001";
+        let join = GameMessage::Join { position: Some(Position::new(3.0, 0.0, 5.0)), view_distance: None };
+        handle(join, user_id, &state).await;
+
+        // Clipping into the trunk snaps the player back
+        let clip = GameMessage::UpdatePosition { position: Position::new(4.5, 0.0, 5.0), rotation: None };
+        let reply = handle(clip, user_id, &state).await;
+        let ServerMessage::PositionCorrected { position, .. } = reply else {
+            panic!("expected a correction, got {reply:?}");
+        };
+        assert_eq!(position, Position::new(3.0, 0.0, 5.0));
+
+        // Walking around it is fine
+        let around = GameMessage::UpdatePosition { position: Position::new(4.0, 0.0, 6.5), rotation: None };
+        let reply = handle(around, user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::PlayerMoved { .. }));
+        assert_eq!(entity_state.get_entity(user_id).unwrap().position, Position::new(4.0, 0.0, 6.5));
+
+        // Harvested objects no longer block
+        let harvest = HarvestObjectRequest { object_id: "tree_0_0_idx_0".to_string(), player_position: Position::new(4.0, 0.0, 6.5) };
+        assert!(environment_manager.handle_harvest_request(user_id, harvest, None).success);
+        let clip = GameMessage::UpdatePosition { position: Position::new(4.5, 0.0, 5.0), rotation: None };
+        let reply = handle(clip, user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::PlayerMoved { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_entering_chunk_sends_biome() {
+        let state = test_state();
+        let generator = EnvironmentGenerator::new(12345, 50.0);
+        let next_chunk = crate::game::ChunkCoord { x: 1, z: 0 };
+        state.environment_manager.ensure_chunk_generated(&generator, &next_chunk);
+
+        let user_id = "user-wanderer-001";
+        let mut client = spawn_mock_session(state.clone(), user_id).await;
+        assert_eq!(client.recv_json().await["type"], "welcome");
+        state.entity_state.update_position(user_id, Position::new(45.0, 0.0, 5.0), None);
+
+        // The move reply comes first; the chunk refresh is queued behind it
+        client.send_json(serde_json::json!({ "type": "update_position", "position": { "x": 55.0, "y": 0.0, "z": 5.0 } }));
+        assert_eq!(client.recv_json().await["type"], "player_moved");
+        loop {
+            let msg = client.recv_json().await;
+            if msg["type"] == "chunk_info" {
+                assert_eq!((msg["chunk_x"].as_i64(), msg["chunk_z"].as_i64()), (Some(1), Some(0)));
+                assert_eq!(msg["biome"], serde_json::to_value(generator.classify_biome(&next_chunk)).unwrap());
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_item_sends_only_the_changed_item() {
+        let state = test_state();
+        let user_id = "user-hoarder-0001";
+        let mut client = spawn_mock_session(state.clone(), user_id).await;
+        assert_eq!(client.recv_json().await["type"], "welcome");
+
+        // Join replies as before; the full inventory sync follows it
+        client.send_json(serde_json::json!({ "type": "join" }));
+        assert_eq!(client.recv_json().await["type"], "joined");
+        assert_eq!(client.recv_json().await["type"], "inventory_updated");
+        state.entity_state.add_item(user_id, "wood".to_string(), 5);
+
+        client.send_json(serde_json::json!({ "type": "remove_item", "item_id": "wood", "quantity": 3 }));
+        let reply = client.recv_json().await;
+        assert_eq!((reply["type"].as_str(), reply["success"].as_bool()), (Some("item_removed"), Some(true)));
+        let delta = client.recv_json().await;
+        assert_eq!(delta["type"], "inventory_delta");
+        assert_eq!(delta["added"], serde_json::json!([]));
+        assert_eq!(delta["removed"], serde_json::json!([["wood", 3]]));
+    }
+
+    #[tokio::test]
+    async fn test_client_add_item_is_rejected() {
+        let state = test_state();
+        let user_id = "user-duper-00001";
+        state.entity_state.add_player(user_id.to_string(), "duper".to_string());
+
+        let add = GameMessage::AddItem { item_id: "gold".to_string(), quantity: 999 };
+        let reply = handle(add, user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::ItemAdded { success: false, .. }), "got {reply:?}");
+        assert_eq!(state.entity_state.get_inventory(user_id).unwrap().get_item_quantity("gold"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_harvest_grants_resources() {
+        let state = test_state();
+        let user_id = "user-gatherer-01";
+        state.entity_state.add_player(user_id.to_string(), "gatherer".to_string());
+        let before = state.entity_state.get_inventory(user_id).unwrap().get_item_quantity("wood");
+
+        let harvest = GameMessage::HarvestObject {
+            object_id: "tree_0_0_idx_0".to_string(),
+            player_position: Position::new(6.0, 0.0, 6.0),
+        };
+        let reply = handle(harvest, user_id, &state).await;
+        let ServerMessage::HarvestResult { resources: Some(resources), .. } = reply else {
+            panic!("expected successful harvest, got {reply:?}");
+        };
+        let granted = resources[0].1;
+        assert!(granted > 0);
+        assert_eq!(state.entity_state.get_inventory(user_id).unwrap().get_item_quantity("wood"), before + granted);
+    }
+
+    #[tokio::test]
+    async fn test_harvest_refused_when_reward_cannot_be_granted() {
+        let state = test_state();
+        let user_id = "user-overloaded1";
+        state.entity_state.set_inventory_slots(user_id, Some(1));
+        state.entity_state.add_player(user_id.to_string(), "overloaded".to_string());
+        state.entity_state.add_item(user_id, "stone".to_string(), 1);
+
+        let harvest = || GameMessage::HarvestObject {
+            object_id: "tree_0_0_idx_0".to_string(),
+            player_position: Position::new(6.0, 0.0, 6.0),
+        };
+        let reply = handle(harvest(), user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::HarvestResult { success: false, error_code: Some(HarvestErrorCode::InventoryFull), .. }), "got {reply:?}");
+        assert!(!state.environment_manager.get_object("tree_0_0_idx_0").unwrap().is_harvested);
+
+        // Over the inventory rate limit: refused the same way, object untouched
+        let limited = AppState { entity_state: EntityStateManager::new(120).with_inventory_rate_limit(1), ..state.clone() };
+        limited.entity_state.add_player(user_id.to_string(), "overloaded".to_string());
+        limited.entity_state.add_item(user_id, "stone".to_string(), 1);
+        let reply = handle(harvest(), user_id, &limited).await;
+        assert!(matches!(reply, ServerMessage::HarvestResult { success: false, error_code: Some(HarvestErrorCode::RateLimited), .. }), "got {reply:?}");
+        assert!(!state.environment_manager.get_object("tree_0_0_idx_0").unwrap().is_harvested);
+    }
+
+    #[tokio::test]
+    async fn test_harvest_all_in_range_grants_every_result() {
+        let state = test_state();
+        let user_id = "user-gatherer-01";
+        state.entity_state.add_player(user_id.to_string(), "gatherer".to_string());
+
+        let msg = GameMessage::HarvestAllInRange { player_position: Position::new(6.0, 0.0, 6.0) };
+        let reply = handle(msg, user_id, &state).await;
+        let ServerMessage::HarvestAllResult { results } = reply else {
+            panic!("expected harvest_all_result, got {reply:?}");
+        };
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].object_id, "tree_0_0_idx_0");
+        let wood = state.entity_state.get_inventory(user_id).unwrap().get_item_quantity("wood");
+        assert_eq!(wood, results[0].resource_amount);
+
+        // Nothing left to take
+        let msg = GameMessage::HarvestAllInRange { player_position: Position::new(6.0, 0.0, 6.0) };
+        let reply = handle(msg, user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::HarvestAllResult { results } if results.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_harvest_all_clamped_to_inventory_budget() {
+        let user_id = "user-gatherer-02";
+        let harvest_all = || GameMessage::HarvestAllInRange { player_position: Position::new(6.0, 0.0, 6.0) };
+        let is_harvested = |state: &AppState, id: &str| state.environment_manager.get_object(id).unwrap().is_harvested;
+
+        // Two inventory ops left: only the two nearest trees are taken
+        let mut state = test_state();
+        state.entity_state = EntityStateManager::new(120).with_inventory_rate_limit(2);
+        state.entity_state.add_player(user_id.to_string(), "gatherer".to_string());
+        state.environment_manager.add_object(test_object("tree_0_0_idx_1", 6.0, 8.0)).unwrap();
+        state.environment_manager.add_object(test_object("tree_0_0_idx_2", 6.0, 12.0)).unwrap();
+        let reply = handle(harvest_all(), user_id, &state).await;
+        assert!(matches!(&reply, ServerMessage::HarvestAllResult { results } if results.len() == 2), "got {reply:?}");
+        assert!(!is_harvested(&state, "tree_0_0_idx_2"));
+
+        // One slot, already holding stone: wood doesn't fit, more stone stacks
+        let state = test_state();
+        state.entity_state.set_inventory_slots(user_id, Some(1));
+        state.entity_state.add_player(user_id.to_string(), "gatherer".to_string());
+        state.entity_state.add_item(user_id, "stone".to_string(), 1);
+        let mut rock = test_object("rock_0_0_idx_1", 6.0, 8.0);
+        rock.object_type = EnvironmentObjectType::Rock;
+        rock.resource_type = ResourceType::Stone;
+        state.environment_manager.add_object(rock).unwrap();
+        let reply = handle(harvest_all(), user_id, &state).await;
+        let ServerMessage::HarvestAllResult { results } = reply else {
+            panic!("expected harvest_all_result, got {reply:?}");
+        };
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].object_id, "rock_0_0_idx_1");
+        assert!(!is_harvested(&state, "tree_0_0_idx_0"));
+    }
+
+    #[tokio::test]
+    async fn test_examine_reaches_beyond_harvest_range() {
+        let state = test_state();
+        let user_id = "user-looker-0001";
+        let interact = |action: &str, x: f32| -> GameMessage {
+            serde_json::from_value(serde_json::json!({
+                "type": "interact_object",
+                "object_id": "tree_0_0_idx_0",
+                "action": action,
+                "player_position": {"x": x, "y": 0.0, "z": 5.0},
+            }))
+            .unwrap()
+        };
+
+        // 20m from the tree: inside interaction range, outside harvest range
+        let reply = handle(interact("examine", 25.0), user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::ObjectExamined { .. }), "got {reply:?}");
+        let reply = handle(interact("harvest", 25.0), user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::HarvestResult { success: false, error_code: Some(crate::game::HarvestErrorCode::TooFar), .. }), "got {reply:?}");
+
+        let reply = handle(interact("examine", 40.0), user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::Error { ref message } if message.starts_with("Too far")), "got {reply:?}");
+    }
+
+    #[tokio::test]
+    async fn test_interact_examine_returns_metadata() {
+        let state = test_state();
+        let msg: GameMessage = serde_json::from_str(
+            r#"{"type":"interact_object","object_id":"tree_0_0_idx_0","action":"examine","player_position":{"x":0.0,"y":0.0,"z":0.0}}"#,
+        ).unwrap();
+
+        let reply = handle(msg, "user-examiner-01", &state).await;
+        let ServerMessage::ObjectExamined { object_data, is_harvested, .. } = reply else {
+            panic!("expected object_examined, got {reply:?}");
+        };
+        assert_eq!(object_data["objectId"], "tree_0_0_idx_0");
+        assert_eq!(object_data["assetName"], "Tree_Oak_01");
+        assert!(!is_harvested);
+    }
+
+    #[tokio::test]
+    async fn test_interact_plant_consumes_seed() {
+        let state = test_state();
+        let user_id = "user-planter-001";
+        state.entity_state.add_player(user_id.to_string(), "planter".to_string());
+        state.entity_state.add_item(user_id, SEED_ITEM_ID.to_string(), 2);
+        let objects_before = state.environment_manager.get_stats().total_objects;
+
+        let msg: GameMessage = serde_json::from_str(
+            r#"{"type":"interact_object","action":"plant","player_position":{"x":20.0,"y":0.0,"z":20.0}}"#,
+        ).unwrap();
+        let reply = handle(msg, user_id, &state).await;
+
+        let ServerMessage::ObjectPlanted { object_data } = reply else {
+            panic!("expected object_planted, got {reply:?}");
+        };
+        let object_id = object_data["objectId"].as_str().unwrap();
+        assert!(state.environment_manager.get_object(object_id).is_some());
+        assert_eq!(state.environment_manager.get_stats().total_objects, objects_before + 1);
+        assert!(state.entity_state.get_inventory(user_id).unwrap().has_item(SEED_ITEM_ID, 1));
+        assert!(!state.entity_state.get_inventory(user_id).unwrap().has_item(SEED_ITEM_ID, 2));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_plant_keeps_seed_under_inventory_rate_limit() {
+        let user_id = "user-planter-003";
+        // Enough ops for the grant and one plant; a refund after a second plant would be refused
+        let limited = AppState { entity_state: EntityStateManager::new(120).with_inventory_rate_limit(3), ..test_state() };
+        limited.entity_state.add_player(user_id.to_string(), "planter".to_string());
+        limited.entity_state.add_item(user_id, SEED_ITEM_ID.to_string(), 2);
+
+        let plant = || GameMessage::InteractObject {
+            object_id: String::new(),
+            action: InteractAction::Plant,
+            player_position: Position::default(),
+            target_position: None,
+        };
+        let reply = handle(plant(), user_id, &limited).await;
+        assert!(matches!(reply, ServerMessage::ObjectPlanted { .. }), "got {reply:?}");
+
+        // Same spot again: rejected for spacing before the seed is taken
+        let reply = handle(plant(), user_id, &limited).await;
+        let ServerMessage::Error { message } = reply else {
+            panic!("expected a spacing error, got {reply:?}");
+        };
+        assert_eq!(message, "Too close to another object");
+        let inventory = limited.entity_state.get_inventory(user_id).unwrap();
+        assert!(inventory.has_item(SEED_ITEM_ID, 1));
+        assert!(!inventory.has_item(SEED_ITEM_ID, 2));
+    }
+
+    #[tokio::test]
+    async fn test_interact_plant_checks_range_from_server_position() {
+        let state = test_state();
+        let user_id = "user-planter-002";
+        state.entity_state.add_player(user_id.to_string(), "planter".to_string());
+        state.entity_state.add_item(user_id, SEED_ITEM_ID.to_string(), 1);
+        let far = state.entity_state.get_entity(user_id).unwrap().position;
+        let far = Position::new(far.x + 200.0, far.y, far.z + 200.0);
+
+        // Claiming to stand next to a far target doesn't extend the reach
+        let msg = GameMessage::InteractObject {
+            object_id: String::new(),
+            action: InteractAction::Plant,
+            player_position: far,
+            target_position: Some(far),
+        };
+        let reply = handle(msg, user_id, &state).await;
+
+        let ServerMessage::Error { message } = reply else {
+            panic!("expected a range error, got {reply:?}");
+        };
+        assert!(message.starts_with("Too far"), "{message}");
+        assert!(state.entity_state.get_inventory(user_id).unwrap().has_item(SEED_ITEM_ID, 1));
+    }
+
+    #[tokio::test]
+    async fn test_equipped_tool_scales_harvest() {
+        let state = test_state();
+        let user_id = "user-lumberjack1";
+        state.entity_state.add_player(user_id.to_string(), "lumberjack".to_string());
+        state.entity_state.add_item(user_id, "iron_axe".to_string(), 1);
+
+        let equip = GameMessage::EquipTool { item_id: Some("iron_axe".to_string()) };
+        let reply = handle(equip, user_id, &state).await;
+        assert!(matches!(reply, ServerMessage::ToolEquipped { .. }));
+
+        let harvest = GameMessage::HarvestObject {
+            object_id: "tree_0_0_idx_0".to_string(),
+            player_position: Position::new(6.0, 0.0, 6.0),
+        };
+        let reply = handle(harvest, user_id, &state).await;
+        let ServerMessage::HarvestResult { resources: Some(resources), .. } = reply else {
+            panic!("expected successful harvest, got {reply:?}");
+        };
+        assert_eq!(resources, vec![("Wood".to_string(), 10)]);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_returns_json_error() {
+        let (app, _) = test_router();
+        let oversized = vec![b'a'; 2 * 1024 * 1024];
+        let request = Request::builder()
+            .method("POST")
+            .uri("/echo")
+            .header("content-type", "application/json")
+            .header("content-length", oversized.len())
+            .body(Body::from(oversized))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = json_body(response).await;
+        assert_eq!(body["code"], "payload_too_large");
+        assert!(body["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_echo_negotiates_msgpack_or_json() {
+        let echo = |accept: Option<&'static str>| {
+            let mut request = Request::builder()
+                .method("POST")
+                .uri("/echo")
+                .header("content-type", "application/json");
+            if let Some(accept) = accept {
+                request = request.header("accept", accept);
+            }
+            let (app, _) = test_router();
+            app.oneshot(request.body(Body::from(r#"{"name":"bug"}"#)).unwrap())
+        };
+
+        let response = echo(Some("application/msgpack")).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "application/msgpack");
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let decoded: serde_json::Value = rmp_serde::from_slice(&bytes).unwrap();
+        assert!(decoded["message"].is_string());
+
+        for accept in [None, Some("application/json")] {
+            let response = echo(accept).await.unwrap();
+            assert_eq!(response.headers()["content-type"], "application/json");
+            assert_eq!(json_body(response).await["message"], decoded["message"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_returns_json_error() {
+        let app = axum::Router::new()
+            .route("/slow", get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "done"
+            }))
+            .layer(
+                ServiceBuilder::new()
+                    .layer(axum::error_handling::HandleErrorLayer::new(handle_middleware_error))
+                    .timeout(Duration::from_millis(10)),
+            );
+
+        let response = app
+            .oneshot(Request::builder().uri("/slow").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+        let body = json_body(response).await;
+        assert_eq!(body, serde_json::json!({ "error": "request timed out", "code": "timeout" }));
+    }
+
+    #[tokio::test]
+    async fn test_http_harvest_success() {
+        let (app, _) = test_router();
+        let response = app
+            .oneshot(harvest_request("tree_0_0_idx_0", Position::new(6.0, 0.0, 6.0)))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["playerId"], "user-harvester-0001");
+        assert_eq!(body["resourceType"], "Wood");
+        assert_eq!(body["resourceAmount"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_http_harvest_out_of_range_rejected() {
+        let (app, _) = test_router();
+        let response = app
+            .oneshot(harvest_request("tree_0_0_idx_0", Position::new(40.0, 0.0, 40.0)))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = json_body(response).await;
+        assert_eq!(body["success"], false);
+        assert!(body["errorMessage"].as_str().unwrap().starts_with("Too far"));
+    }
+
+    #[tokio::test]
+    async fn test_harvest_despawns_object_for_other_players_in_chunk() {
+        let state = test_state();
+        // Both sessions track the spawn chunk from their welcome
+        let mut harvester = spawn_mock_session(state.clone(), "user-harvester-0001").await;
+        let mut watcher = spawn_mock_session(state.clone(), "user-watcher-0002").await;
+
+        harvester.send_json(serde_json::json!({
+            "type": "harvest_object",
+            "object_id": "tree_0_0_idx_0",
+            "player_position": { "x": 6.0, "y": 0.0, "z": 6.0 },
+        }));
+        loop {
+            let msg = harvester.recv_json().await;
+            assert_ne!(msg["type"], "object_harvested");
+            if msg["type"] == "harvest_result" {
+                assert_eq!(msg["success"], true);
+                break;
+            }
+        }
+        loop {
+            let msg = watcher.recv_json().await;
+            if msg["type"] == "object_harvested" {
+                assert_eq!(msg["object_id"], "tree_0_0_idx_0");
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spectator_sees_moves_but_cannot_mutate() {
+        let state = test_state();
+        let spectator_id = "user-spectator-01";
+        let mut spectator = spawn_mock_session_with(
+            state.clone(),
+            test_auth_user(spectator_id, 3600),
+            Some(Position::new(0.0, 0.0, 0.0)),
+        ).await;
+        let welcome = spectator.recv_json().await;
+        assert_eq!(welcome["spectator"], true);
+
+        spectator.send_json(serde_json::json!({
+            "type": "update_position",
+            "position": { "x": 3.0, "y": 0.0, "z": 3.0 },
+        }));
+        loop {
+            let msg = spectator.recv_json().await;
+            if msg["type"] == "error" {
+                break;
+            }
+        }
+        assert!(state.entity_state.get_entity(spectator_id).is_none());
+
+        let mut player = spawn_mock_session(state.clone(), "user-player-0001").await;
+        player.send_json(serde_json::json!({ "type": "join" }));
+        player.send_json(serde_json::json!({
+            "type": "update_position",
+            "position": { "x": 5.0, "y": 0.0, "z": 5.0 },
+        }));
+        loop {
+            if player.recv_json().await["type"] == "player_moved" {
+                break;
+            }
+        }
+
+        fan_out_tick(&state.tick.drain().unwrap(), &state.sessions, &state.environment_manager);
+        loop {
+            let msg = spectator.recv_json().await;
+            if msg["type"] == "game_state_delta" {
+                assert_eq!(msg["entities"][0]["entity_id"], "user-player-0001");
+                assert_eq!(msg["entities"][0]["position"]["x"], 5.0);
+                break;
+            }
+        }
+        assert!(state.entity_state.get_entity(spectator_id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_clears_player_environment_state() {
+        let state = test_state();
+        let user_id = "user-leaver-0001";
+        let mut client = spawn_mock_session(state.clone(), user_id).await;
+        client.send_json(serde_json::json!({ "type": "join", "view_distance": 2 }));
+        loop {
+            if client.recv_json().await["type"] == "joined" {
+                break;
+            }
+        }
+        assert!(state.environment_manager.has_player_state(user_id));
+
+        let MockClient { inbound, session, .. } = client;
+        drop(inbound);
+        session.await.unwrap();
+
+        assert!(!state.environment_manager.has_player_state(user_id));
+        assert!(state.entity_state.get_entity(user_id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_connections_accepted_while_world_generating() {
+        let mut state = test_state();
+        state.environment_manager = Arc::new(EnvironmentManager::new(50.0, 3, 10.0));
+        state.world_gen = Arc::new(GenerationProgress::new());
+
+        let response = router(state.clone())
+            .oneshot(Request::builder().uri("/ready").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(json_body(response).await["status"], "generating");
+
+        // Spawn chunks are generated on demand for early joiners
+        let mut client = spawn_mock_session(state.clone(), "user-early-0001").await;
+        let welcome = client.recv_json().await;
+        assert_eq!(welcome["type"], "welcome");
+        assert!(!welcome["nearby_objects"].as_array().unwrap().is_empty());
+        assert!(state.environment_manager.is_chunk_generated(&crate::game::ChunkCoord { x: 0, z: 0 }));
+    }
+
+    #[tokio::test]
+    async fn test_session_compresses_frames_above_threshold() {
+        let frames = FramePolicy { compress_above_bytes: Some(100), ..Default::default() };
+        let mut client = spawn_mock_session_full(test_state(), test_auth_user("user-squeezed-01", 3600), SessionOptions { frames, ..SessionOptions::default() }).await;
+
+        // Welcome carries the object snapshot, so it's over the threshold: compressed binary
+        let Some(Message::Binary(bytes)) = client.recv().await else {
+            panic!("expected a compressed welcome frame");
+        };
+        let welcome: serde_json::Value = serde_json::from_str(&crate::transports::codec::decode_frame(&bytes).unwrap()).unwrap();
+        assert_eq!(welcome["type"], "welcome");
+
+        // Pong is tiny: plain text
+        client.send_json(serde_json::json!({ "type": "ping" }));
+        let Some(Message::Text(text)) = client.recv().await else {
+            panic!("small frames stay text");
+        };
+        assert!(text.as_str().contains("pong"));
+    }
+
+    #[tokio::test]
+    async fn test_app_metadata_overrides_inventory_slots() {
+        let state = test_state();
+        let mut auth_user = test_auth_user("user-vip-000001", 3600);
+        auth_user.claims.app_metadata = Some(serde_json::json!({ "inventory_slots": 40 }));
+        let mut client = spawn_mock_session_as(state.clone(), auth_user).await;
+        assert_eq!(client.recv_json().await["type"], "welcome");
+        assert_eq!(state.entity_state.get_inventory("user-vip-000001").unwrap().max_slots, 40);
+
+        // Players without the metadata get the configured default
+        let mut regular = spawn_mock_session(state.clone(), "user-regular-0001").await;
+        assert_eq!(regular.recv_json().await["type"], "welcome");
+        assert_eq!(
+            state.entity_state.get_inventory("user-regular-0001").unwrap().max_slots,
+            crate::game::entity_state::DEFAULT_INVENTORY_SLOTS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_welcome_carries_initial_state() {
+        let state = test_state();
+        state.entity_state.add_player("user-neighbour-01".to_string(), "neighbour".to_string());
+        state.entity_state.update_position("user-neighbour-01", Position::new(20.0, 0.0, 20.0), None);
+
+        let user_id = "user-welcomed-001";
+        let mut client = spawn_mock_session(state.clone(), user_id).await;
+        let welcome = client.recv_json().await;
+        assert_eq!(welcome["type"], "welcome");
+        assert_eq!(welcome["user_id"], user_id);
+        assert_eq!(welcome["spectator"], false);
+
+        // Player is spawned on connect and included among the nearby players
+        let spawn: Position = serde_json::from_value(welcome["spawn_position"].clone()).unwrap();
+        assert_eq!(spawn, state.entity_state.get_entity(user_id).unwrap().position);
+        let players: Vec<&str> = welcome["nearby_players"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["entity_id"].as_str().unwrap())
+            .collect();
+        assert!(players.contains(&user_id));
+        assert!(players.contains(&"user-neighbour-01"));
+
+        // Objects cover the spawn chunk
+        let spawn_chunk = state.environment_manager.chunk_for_position(&spawn);
+        let spawn_chunk_objects = state.environment_manager.get_objects_in_chunks(&[spawn_chunk]);
+        assert!(!spawn_chunk_objects.is_empty());
+        let object_ids: Vec<&str> = welcome["nearby_objects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|o| o["objectId"].as_str().unwrap())
+            .collect();
+        assert!(spawn_chunk_objects.iter().all(|o| object_ids.contains(&o.object_id.as_str())));
+        assert_eq!(welcome["server_tick"], state.tick.current_tick());
+    }
+
+    #[tokio::test]
+    async fn test_welcome_tracks_player_chunks() {
+        let state = test_state();
+        let user_id = "user-tracked-0001";
+        let mut client = spawn_mock_session(state.clone(), user_id).await;
+        let welcome = client.recv_json().await;
+        assert_eq!(welcome["type"], "welcome");
+
+        assert!(state.environment_manager.is_tracking_player(user_id));
+        let spawn: Position = serde_json::from_value(welcome["spawn_position"].clone()).unwrap();
+        let expected = state.environment_manager.get_nearby_chunks_for_player(user_id, &spawn);
+        let tracked = state.environment_manager.get_player_chunks(user_id).unwrap();
+        assert_eq!(tracked.len(), expected.len());
+        assert!(expected.iter().all(|chunk| tracked.contains(chunk)));
+
+        // Tracking ends with the session
+        drop(client.inbound);
+        client.session.await.unwrap();
+        assert!(!state.environment_manager.is_tracking_player(user_id));
+    }
+
+    #[tokio::test]
+    async fn test_welcome_includes_spawn_chunk_biome() {
+        let state = test_state();
+        let mut client = spawn_mock_session(state.clone(), "user-newcomer-001").await;
+        let welcome = client.recv_json().await;
+        assert!(welcome.get("biome").is_none(), "ungenerated spawn chunk has no biome");
+
+        let generator = EnvironmentGenerator::new(12345, 50.0);
+        let spawn_chunk = crate::game::ChunkCoord { x: 0, z: 0 };
+        state.environment_manager.ensure_chunk_generated(&generator, &spawn_chunk);
+        let mut client = spawn_mock_session(state.clone(), "user-newcomer-002").await;
+        let welcome = client.recv_json().await;
+        assert_eq!(welcome["biome"], serde_json::to_value(generator.classify_biome(&spawn_chunk)).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_requested_view_distance_sizes_welcome_grid() {
+        let state = test_state();
+        let user_id = "user-nearsighted1";
+        let options = SessionOptions { view_distance: Some(1), ..SessionOptions::default() };
+        let mut client = spawn_mock_session_full(state.clone(), test_auth_user(user_id, 3600), options).await;
+        assert_eq!(client.recv_json().await["type"], "welcome");
+        assert_eq!(state.environment_manager.get_player_view_distance(user_id), 1);
+        assert_eq!(state.environment_manager.get_player_chunks(user_id).unwrap().len(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_join_view_distance_refreshes_visible_chunks() {
+        let state = test_state();
+        state.environment_manager.add_object(test_object("tree_2_0_idx_0", 110.0, 5.0)).unwrap();
+        let user_id = "user-nearsighted2";
+        let mut client = spawn_mock_session(state.clone(), user_id).await;
+        assert_eq!(client.recv_json().await["type"], "welcome");
+        assert_eq!(state.environment_manager.get_player_chunks(user_id).unwrap().len(), 49);
+
+        // Shrinking the view distance drops the far chunk and its objects
+        client.send_json(serde_json::json!({ "type": "join", "view_distance": 1 }));
+        loop {
+            let msg = client.recv_json().await;
+            if msg["type"] == "objects_despawned" {
+                assert_eq!(msg["object_ids"], serde_json::json!(["tree_2_0_idx_0"]));
+                break;
+            }
+        }
+        assert_eq!(state.environment_manager.get_player_chunks(user_id).unwrap().len(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_mock_session_ping_pong() {
+        let mut client = spawn_mock_session(test_state(), "user-pinger-0001").await;
+        assert_eq!(client.recv_json().await["type"], "welcome");
+
+        client.send_json(serde_json::json!({ "type": "ping" }));
+        loop {
+            if client.recv_json().await["type"] == "pong" {
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_near_expiry_token_warned() {
+        let auth_user = test_auth_user("user-expiring-01", 120);
+        let expires_at = auth_user.claims.exp;
+        let mut client = spawn_mock_session_as(test_state(), auth_user).await;
+
+        loop {
+            let msg = client.recv_json().await;
+            if msg["type"] == "token_expiring_soon" {
+                assert_eq!(msg["expires_at"], expires_at);
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_ws_token_precedence() {
+        let token = |s: &str| Some(s.to_string());
+        assert_eq!(select_ws_token(token("header"), None), Some(("header".to_string(), TokenSource::Header)));
+        assert_eq!(select_ws_token(None, token("query")), Some(("query".to_string(), TokenSource::Query)));
+        assert_eq!(select_ws_token(token("same"), token("same")), Some(("same".to_string(), TokenSource::Header)));
+        assert_eq!(
+            select_ws_token(token("header"), token("query")),
+            Some(("header".to_string(), TokenSource::HeaderOverridingQuery))
+        );
+        assert_eq!(select_ws_token(None, None), None);
+    }
+
+    #[test]
+    fn test_token_expiry_warning_threshold() {
+        let now = chrono::Utc::now().timestamp();
+        assert!(token_expiry_warning(now + 120, 300).is_some());
+        assert!(token_expiry_warning(now + 3600, 300).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_connection_replaces_old_session() {
+        let state = test_state();
+        let user_id = "user-two-tabs-01";
+        let mut first = spawn_mock_session(state.clone(), user_id).await;
+        first.send_json(serde_json::json!({ "type": "join" }));
+        loop {
+            if first.recv_json().await["type"] == "joined" {
+                break;
+            }
+        }
+
+        let _second = spawn_mock_session(state.clone(), user_id).await;
+        loop {
+            match first.recv().await {
+                Some(Message::Close(Some(frame))) => {
+                    assert_eq!(frame.code, CloseCode::SessionReplaced.code());
+                    assert_eq!(frame.reason.as_str(), "Connected from another location");
+                    break;
+                }
+                Some(_) => continue,
+                None => panic!("old session ended without a close frame"),
+            }
+        }
+        first.session.await.unwrap();
+
+        // The replaced session must not tear down the entity now owned by the new one
+        assert!(state.entity_state.get_entity(user_id).is_some());
+        assert_eq!(state.sessions.session_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_permessage_deflate_offer_declined() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(test_state())).await.unwrap() });
+
+        let response = reqwest::Client::new()
+            .get(format!("http://{addr}/ws"))
+            .header("connection", "upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-version", "13")
+            .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .header("sec-websocket-extensions", "permessage-deflate; client_max_window_bits")
+            .bearer_auth(TEST_TOKEN)
+            .send()
+            .await
+            .unwrap();
+
+        // Upgrade succeeds without negotiating the extension (frames stay uncompressed)
+        assert_eq!(response.status(), reqwest::StatusCode::SWITCHING_PROTOCOLS);
+        assert!(response.headers().get("sec-websocket-extensions").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_connection_rejected_by_policy() {
+        let mut state = test_state();
+        state.sessions = SessionRegistry::new().with_duplicate_policy(DuplicateSessionPolicy::RejectNew);
+        let user_id = "user-harvester-0001"; // TEST_TOKEN's user
+        let _first = spawn_mock_session(state.clone(), user_id).await;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state)).await.unwrap() });
+
+        let response = reqwest::Client::new()
+            .get(format!("http://{addr}/ws"))
+            .header("connection", "upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-version", "13")
+            .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .bearer_auth(TEST_TOKEN)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_deeply_nested_message_gets_error() {
+        let state = test_state();
+        let mut client = spawn_mock_session(state, "user-nested-0001").await;
+
+        let nested = format!(r#"{{"type":"ping","extra":{}{}}}"#, "[".repeat(1000), "]".repeat(1000));
+        client.inbound.send(Message::Text(nested.into())).unwrap();
+        loop {
+            let msg = client.recv_json().await;
+            if msg["type"] == "error" {
+                assert!(msg["message"].as_str().unwrap().contains("nested too deeply"));
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_outbound_frames_carry_sequence_and_acks_are_recorded() {
+        let state = test_state();
+        let mut client = spawn_mock_session(state.clone(), "user-sequenced-01").await;
+
+        let welcome = client.recv_json().await;
+        assert_eq!(welcome["type"], "welcome");
+        assert_eq!(welcome["seq"], 1);
+
+        let mut last_seq = 1;
+        for _ in 0..3 {
+            client.send_json(serde_json::json!({ "type": "ping" }));
+            let pong = loop {
+                let msg = client.recv_json().await;
+                let seq = msg["seq"].as_u64().unwrap();
+                assert!(seq > last_seq, "seq went from {last_seq} to {seq}");
+                last_seq = seq;
+                if msg["type"] == "pong" {
+                    break msg;
+                }
+            };
+            assert_eq!(pong["seq"], last_seq);
+        }
+
+        // Ack the welcome and the latest frame; everything in between counts as skipped
+        client.send_json(serde_json::json!({ "type": "ack", "seq": 1 }));
+        client.send_json(serde_json::json!({ "type": "ack", "seq": last_seq }));
+        client.send_json(serde_json::json!({ "type": "ack", "seq": last_seq + 100 }));
+        // Acks get no reply; a ping round-trip shows they were processed
+        client.send_json(serde_json::json!({ "type": "ping" }));
+        while client.recv_json().await["type"] != "pong" {}
+
+        let stats = state.sessions.ack_metrics().stats();
+        assert_eq!(stats.acks, 2);
+        assert_eq!(stats.skipped, last_seq - 2);
+        assert_eq!(stats.rejected, 1);
+        assert!(stats.max_latency_ms >= stats.mean_latency_ms);
+
+        init_test_service_key();
+        let request = Request::builder()
+            .uri("/admin/acks")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = router(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await["acks"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_idle_session_closed_after_timeout() {
+        let mut state = test_state();
+        state.ws_idle_timeout = Duration::from_millis(100);
+        let mut client = spawn_mock_session(state.clone(), "user-idle-000001").await;
+
+        loop {
+            match client.recv().await {
+                Some(Message::Close(Some(frame))) => {
+                    assert_eq!(frame.code, CloseCode::Timeout.code());
+                    assert_eq!(frame.reason.as_str(), "Idle timeout");
+                    break;
+                }
+                Some(_) => continue,
+                None => panic!("session ended without a close frame"),
+            }
+        }
+        client.session.await.unwrap();
+        assert!(!state.sessions.is_connected("user-idle-000001"));
+    }
+
+    #[tokio::test]
+    async fn test_unresponsive_session_closed_after_keepalive_timeout() {
+        let mut state = test_state();
+        state.ws_ping_interval = Duration::from_millis(50);
+        state.ws_pong_timeout = Duration::from_millis(100);
+
+        // A client that answers pings stays connected well past the timeout
+        let mut alive = spawn_mock_session(state.clone(), "user-alive-000001").await;
+        let started = tokio::time::Instant::now();
+        while started.elapsed() < Duration::from_millis(400) {
+            if let Some(Message::Ping(payload)) = alive.recv().await {
+                alive.inbound.send(Message::Pong(payload)).unwrap();
+            }
+        }
+        assert!(state.sessions.is_connected("user-alive-000001"));
+
+        // A vanished client never answers and is closed after one ping + timeout
+        let mut silent = spawn_mock_session(state.clone(), "user-silent-00001").await;
+        let mut pinged = false;
+        loop {
+            match silent.recv().await {
+                Some(Message::Ping(_)) => pinged = true,
+                Some(Message::Close(Some(frame))) => {
+                    assert_eq!(frame.code, CloseCode::Timeout.code());
+                    assert_eq!(frame.reason.as_str(), "Keepalive timeout");
+                    break;
+                }
+                Some(_) => continue,
+                None => panic!("session ended without a close frame"),
+            }
+        }
+        assert!(pinged);
+        silent.session.await.unwrap();
+        assert!(!state.sessions.is_connected("user-silent-00001"));
+    }
+
+    #[tokio::test]
+    async fn test_admin_kick_closes_socket() {
+        init_test_service_key();
+        let state = test_state();
+        let mut client = spawn_mock_session(state.clone(), "user-kicked-0001").await;
+        let app = router(state.clone());
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/admin/kick")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::from(r#"{"user_id":"user-kicked-0001","reason":"griefing"}"#))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Skip connect/environment frames until the kick arrives
+        loop {
+            let msg = client.recv_json().await;
+            if msg["type"] == "kicked" {
+                assert_eq!(msg["reason"], "griefing");
+                break;
+            }
+        }
+        // Deliberate disconnects carry no retry hint
+        let notice = client.recv_json().await;
+        assert_eq!(notice["type"], "disconnecting");
+        assert!(notice.get("retry_after_ms").is_none());
+        let Some(Message::Close(Some(frame))) = client.recv().await else {
+            panic!("expected a close frame");
+        };
+        assert_eq!(frame.code, CloseCode::Kicked.code());
+        assert_eq!(frame.reason.as_str(), "griefing");
+        client.session.await.unwrap();
+        assert!(!state.sessions.is_connected("user-kicked-0001"));
+    }
+
+    #[tokio::test]
+    async fn test_admin_kick_truncates_long_close_reason() {
+        init_test_service_key();
+        let state = test_state();
+        let mut client = spawn_mock_session(state.clone(), "user-kicked-0002").await;
+        let reason = "违反规则".repeat(20);
+        let body = serde_json::json!({ "user_id": "user-kicked-0002", "reason": reason });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/admin/kick")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The JSON notices keep the full reason, only the close frame is cut
+        let (mut kicked, mut notice) = (false, false);
+        let frame = loop {
+            match client.recv().await {
+                Some(Message::Text(text)) => {
+                    let msg: serde_json::Value = serde_json::from_str(&text).unwrap();
+                    kicked |= msg["type"] == "kicked" && msg["reason"] == reason.as_str();
+                    notice |= msg["type"] == "disconnecting" && msg["reason"] == reason.as_str();
+                }
+                Some(Message::Close(Some(frame))) => break frame,
+                Some(_) => continue,
+                None => panic!("session ended without a close frame"),
+            }
+        };
+        assert!(kicked && notice);
+        assert_eq!(frame.code, CloseCode::Kicked.code());
+        assert!(frame.reason.len() <= crate::transports::close::MAX_CLOSE_REASON_BYTES);
+        assert!(reason.starts_with(frame.reason.as_str()));
+        client.session.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_admin_disconnect_all_caps_grace_period() {
+        init_test_service_key();
+        let state = test_state();
+        let mut client = spawn_mock_session(state.clone(), "user-maint-00004").await;
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/admin/disconnect_all")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::from(r#"{"grace_seconds":18446744073709551615}"#))
+            .unwrap();
+        let response = router(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(json_body(response).await["grace_seconds"], MAX_DISCONNECT_GRACE_SECS);
+
+        loop {
+            let msg = client.recv_json().await;
+            if msg["type"] == "shutting_down" {
+                assert_eq!(msg["grace_seconds"], MAX_DISCONNECT_GRACE_SECS);
+                break;
+            }
+        }
+    }
+
+    /// Skip frames until the server's close frame
+    async fn expect_close(client: &mut MockClient) -> CloseFrame {
+        loop {
+            match client.recv().await {
+                Some(Message::Close(Some(frame))) => return frame,
+                Some(_) => continue,
+                None => panic!("session ended without a close frame"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_closes_with_auth_code() {
+        let state = test_state();
+        let mut client = spawn_mock_session_as(state.clone(), test_auth_user("user-expired-0001", -1)).await;
+        let frame = expect_close(&mut client).await;
+        assert_eq!(frame.code, CloseCode::AuthFailed.code());
+        assert_eq!(frame.reason.as_str(), "Token expired");
+        client.session.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transport_error_closes_with_protocol_code() {
+        let (out_tx, mut out_rx) = tokio::sync::mpsc::unbounded_channel::<Message>();
+        let sink = Box::pin(futures_util::sink::unfold(out_tx, |tx, msg: Message| async move {
+            tx.send(msg).map_err(|e| e.to_string())?;
+            Ok::<_, String>(tx)
+        }));
+        let stream = futures_util::stream::iter(vec![Err(axum::Error::new("invalid frame"))]);
+        let auth_user = test_auth_user("user-garbled-0001", 3600);
+        ws_loop(sink, stream, test_state(), auth_user, SessionOptions::default()).await;
+
+        let mut last = None;
+        while let Ok(msg) = out_rx.try_recv() {
+            last = Some(msg);
+        }
+        let Some(Message::Close(Some(frame))) = last else {
+            panic!("expected a close frame, got {last:?}");
+        };
+        assert_eq!(frame.code, CloseCode::ProtocolError.code());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_closes_live_sessions() {
+        let state = test_state();
+        let mut client = spawn_mock_session(state.clone(), "user-lastcall-0001").await;
+        assert_eq!(state.sessions.close_all(CloseCode::Shutdown), 1);
+        let frame = expect_close(&mut client).await;
+        assert_eq!(frame.code, CloseCode::Shutdown.code());
+        assert_eq!(frame.reason.as_str(), "Server shutting down");
+        client.session.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_admin_disconnect_all_closes_every_session() {
+        init_test_service_key();
+        let state = test_state();
+        let mut clients = Vec::new();
+        for user_id in ["user-maint-00001", "user-maint-00002", "user-maint-00003"] {
+            clients.push(spawn_mock_session(state.clone(), user_id).await);
+        }
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/admin/disconnect_all")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::from(r#"{"reason":"Deploying v2","grace_seconds":0}"#))
+            .unwrap();
+        let response = router(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(json_body(response).await["notified"], 3);
+
+        for mut client in clients {
+            let mut warned = false;
+            let frame = loop {
+                match client.recv().await {
+                    Some(Message::Text(text)) => {
+                        let msg: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        warned |= msg["type"] == "shutting_down" && msg["reason"] == "Deploying v2";
+                    }
+                    Some(Message::Close(Some(frame))) => break frame,
+                    Some(_) => continue,
+                    None => panic!("session ended without a close frame"),
+                }
+            };
+            assert!(warned, "expected a shutting_down notice before the close");
+            assert_eq!(frame.code, CloseCode::Shutdown.code());
+            assert_eq!(frame.reason.as_str(), "Deploying v2");
+            client.session.await.unwrap();
+        }
+        assert_eq!(state.sessions.session_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_server_full_close_carries_retry_hint() {
+        let mut state = test_state();
+        state.entity_state = EntityStateManager::new(120).with_max_entities(1);
+        state.entity_state.add_player("user-occupant-001".to_string(), "occupant".to_string());
+
+        let mut client = spawn_mock_session(state.clone(), "user-overflow-01").await;
+        let notice = client.recv_json().await;
+        assert_eq!(notice["type"], "disconnecting");
+        assert_eq!(notice["retry_after_ms"], SERVER_FULL_RETRY_AFTER.as_millis() as u64);
+        let Some(Message::Close(Some(frame))) = client.recv().await else {
+            panic!("expected a close frame");
+        };
+        assert_eq!(frame.code, CloseCode::ServerFull.code());
+        assert_eq!(frame.reason.as_str(), "Server full");
+        client.session.await.unwrap();
+        assert!(!state.sessions.is_connected("user-overflow-01"));
+    }
+
+    #[tokio::test]
+    async fn test_admin_kick_requires_service_key() {
+        init_test_service_key();
+        let (app, _) = test_router();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/admin/kick")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_TOKEN}"))
+            .body(Body::from(r#"{"user_id":"user-harvester-0001"}"#))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_report_disabled_without_service_key() {
+        init_test_service_key();
+        let mut headers = http::HeaderMap::new();
+        headers.insert(http::header::AUTHORIZATION, format!("Bearer {TEST_SERVICE_KEY}").parse().unwrap());
+
+        // The service key is a process-wide OnceLock, so the unset case is checked directly
+        let response = authorize_admin(false, &headers).unwrap_err().into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = json_body(response).await;
+        assert_eq!(body["error"], "admin disabled");
+        assert_eq!(body["code"], "admin_disabled");
+
+        assert!(authorize_admin(true, &headers).is_ok());
+        assert_eq!(authorize_admin(true, &http::HeaderMap::new()), Err(AdminRejection::Forbidden));
+    }
+
+    #[tokio::test]
+    async fn test_admin_runtime_reports_build() {
+        init_test_service_key();
+        let (app, _) = test_router();
+        let request = Request::builder()
+            .uri("/admin/runtime")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        let expected_features: Vec<&str> = if cfg!(feature = "jemalloc") { vec!["jemalloc"] } else { vec![] };
+        assert_eq!(body["features"], serde_json::json!(expected_features));
+        let expected_allocator = if cfg!(all(feature = "jemalloc", not(target_env = "msvc"))) { "jemalloc" } else { "system" };
+        assert_eq!(body["allocator"], expected_allocator);
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert!(body["tokio_workers"].as_u64().unwrap() >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_admin_reseed_regenerates_world() {
+        init_test_service_key();
+        let state = test_state();
+        let mut client = spawn_mock_session(state.clone(), "user-watcher-0002").await;
+        assert_eq!(client.recv_json().await["type"], "welcome");
+        let before = state.environment_manager.get_object("tree_0_0_idx_0").unwrap();
+        assert_eq!(state.environment_manager.get_stats().tracked_players, 1);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/admin/world/reseed")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::from(r#"{"seed":999}"#))
+            .unwrap();
+        let response = router(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await["seed"], 999);
+
+        assert_eq!(state.generator().seed(), 999);
+        let expected = EnvironmentGenerator::new(999, 50.0).generate_chunk(&crate::game::ChunkCoord { x: 0, z: 0 });
+        let after = state.environment_manager.get_object(&expected[0].object_id).unwrap();
+        assert_eq!(after.position, expected[0].position);
+        assert_ne!(after.position, before.position);
+        // Chunks shown from the old world are forgotten
+        assert_eq!(state.environment_manager.get_stats().tracked_players, 0);
+
+        loop {
+            let msg = client.recv_json().await;
+            if msg["type"] == "world_reset" {
+                assert_eq!(msg["seed"], 999);
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recipes_list_reflects_reload() {
+        init_test_service_key();
+        let path = std::env::temp_dir().join(format!("bugwars-recipes-{}.json", ulid::Ulid::new()));
+        let write_recipes = |output: &str| {
+            let recipes = serde_json::json!([{
+                "recipe_id": "planks",
+                "inputs": [{ "item_id": "wood", "quantity": 2 }],
+                "output": { "item_id": output, "quantity": 4 }
+            }]);
+            std::fs::write(&path, recipes.to_string()).unwrap();
+        };
+        write_recipes("plank");
+
+        let mut state = test_state();
+        state.recipes = RecipeRegistry::from_file(&path).unwrap();
+        let list = || async {
+            let request = Request::builder().uri("/recipes").body(Body::empty()).unwrap();
+            json_body(router(state.clone()).oneshot(request).await.unwrap()).await
+        };
+        assert_eq!(list().await[0]["output"]["item_id"], "plank");
+
+        write_recipes("oak_plank");
+        let reload = Request::builder()
+            .method("POST")
+            .uri("/admin/recipes/reload")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = router(state.clone()).oneshot(reload).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await["recipes"], 1);
+        assert_eq!(list().await[0]["output"]["item_id"], "oak_plank");
+
+        // A broken file is rejected and the loaded recipes stay in place
+        std::fs::write(&path, "[{").unwrap();
+        let reload = Request::builder()
+            .method("POST")
+            .uri("/admin/recipes/reload")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = router(state.clone()).oneshot(reload).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(list().await[0]["output"]["item_id"], "oak_plank");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_admin_replay_recording_leaves_live_world_alone() {
+        init_test_service_key();
+        let dir = std::env::temp_dir().join(format!("bugwars-recordings-{}", ulid::Ulid::new()));
+        let user_id = "user-recorded-02";
+        let mut recorder = SessionRecorder::new(user_id, None, 16);
+        recorder.record_inbound(&GameMessage::Join { position: Some(Position::new(3.0, 0.0, 4.0)), view_distance: None });
+        recorder.record_inbound(&GameMessage::AddItem { item_id: "wood".to_string(), quantity: 3 });
+        let path = recorder.write_to_dir(&dir, 1).unwrap();
+        let file = path.file_name().unwrap().to_str().unwrap().to_string();
+
+        let mut state = test_state();
+        state.recording = Some(RecordingConfig { capacity: 16, dir: Some(dir.clone()) });
+        let replay = |file: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/admin/recordings/replay")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+                .body(Body::from(serde_json::json!({ "file": file }).to_string()))
+                .unwrap()
+        };
+
+        let response = router(state.clone()).oneshot(replay(&file)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["user_id"], user_id);
+        assert_eq!(body["responses"].as_array().unwrap().len(), 2);
+        assert!(state.entity_state.get_entity(user_id).is_none());
+
+        let response = router(state.clone()).oneshot(replay("../recipes.json")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = router(state.clone()).oneshot(replay("missing.json")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn respawn_request(object_id: &str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/admin/env/respawn")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::from(serde_json::json!({ "object_id": object_id }).to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_admin_respawn_restores_and_broadcasts() {
+        init_test_service_key();
+        let state = test_state();
+        let watcher = "user-watcher-0003";
+        let mut client = spawn_mock_session(state.clone(), watcher).await;
+        assert_eq!(client.recv_json().await["type"], "welcome");
+
+        let harvest = HarvestObjectRequest { object_id: "tree_0_0_idx_0".to_string(), player_position: Position::new(5.0, 0.0, 5.0) };
+        assert!(state.environment_manager.handle_harvest_request("user-harvester-0001", harvest, None).success);
+
+        let response = router(state.clone()).oneshot(respawn_request("tree_0_0_idx_0")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await["notified"], 1);
+        assert!(!state.environment_manager.get_object("tree_0_0_idx_0").unwrap().is_harvested);
+
+        loop {
+            let msg = client.recv_json().await;
+            if msg["type"] == "object_respawned" {
+                assert_eq!(msg["object_id"], "tree_0_0_idx_0");
+                assert_eq!(msg["object_data"]["objectId"], "tree_0_0_idx_0");
+                break;
+            }
+        }
+    }
+
+    fn admin_post(uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_admin_damage_respects_teams_and_reaches_the_tick() {
+        init_test_service_key();
+        let state = test_state();
+        state.entity_state.add_npc("npc-00000001".to_string());
+        state.entity_state.add_player("user-target-0001".to_string(), "target".to_string());
+        let damage = || admin_post("/admin/entities/damage", serde_json::json!({
+            "attacker_id": "npc-00000001", "target_id": "user-target-0001", "amount": 25.0
+        }));
+        let join_red = |entity_id: &str| admin_post("/admin/entities/team", serde_json::json!({ "entity_id": entity_id, "team": "red" }));
+
+        for entity_id in ["npc-00000001", "user-target-0001"] {
+            let response = router(state.clone()).oneshot(join_red(entity_id)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(json_body(response).await["team"], "red");
+        }
+        let response = router(state.clone()).oneshot(damage()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(state.entity_state.get_entity("user-target-0001").unwrap().health, 100.0);
+
+        let leave = admin_post("/admin/entities/team", serde_json::json!({ "entity_id": "npc-00000001", "team": null }));
+        assert_eq!(router(state.clone()).oneshot(leave).await.unwrap().status(), StatusCode::OK);
+        let response = router(state.clone()).oneshot(damage()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await["health"], 75.0);
+
+        let Some(ServerMessage::GameStateDelta { entities, .. }) = state.tick.drain() else {
+            panic!("expected the health change in the next tick");
+        };
+        assert!(entities.iter().any(|e| e.entity_id == "user-target-0001" && e.health == Some(75.0)));
+
+        let missing = admin_post("/admin/entities/team", serde_json::json!({ "entity_id": "npc-missing-0001", "team": "red" }));
+        assert_eq!(router(state).oneshot(missing).await.unwrap().status(), StatusCode::NOT_FOUND);
+    }
+
+    fn move_entity_request(entity_id: &str, x: f32) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/admin/entities/move")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::from(serde_json::json!({ "entity_id": entity_id, "position": { "x": x, "y": 0.0, "z": 3.0 } }).to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_npc_moves_broadcast_at_their_sync_rate() {
+        init_test_service_key();
+        let mut state = test_state();
+        state.tick = Arc::new(TickBatcher::new().with_sync_interval(EntityType::Npc, 3));
+        state.entity_state.add_npc("npc-00000001".to_string());
+        let mut watcher = spawn_mock_session(state.clone(), "user-watcher-0004").await;
+        assert_eq!(watcher.recv_json().await["type"], "welcome");
+
+        let npc_positions = |delta: Option<ServerMessage>| -> Vec<f32> {
+            match delta {
+                Some(ServerMessage::GameStateDelta { entities, .. }) => entities
+                    .into_iter()
+                    .filter(|e| e.entity_id == "npc-00000001")
+                    .filter_map(|e| e.position.map(|p| p.x))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        };
+
+        // First move goes out on the next tick and reaches the watcher
+        let response = router(state.clone()).oneshot(move_entity_request("npc-00000001", 3.0)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let delta = state.tick.drain().unwrap();
+        fan_out_tick(&delta, &state.sessions, &state.environment_manager);
+        loop {
+            let msg = watcher.recv_json().await;
+            if msg["type"] == "game_state_delta" {
+                assert_eq!(msg["entities"][0]["entity_id"], "npc-00000001");
+                break;
+            }
+        }
+
+        // Later moves are held back until three ticks have passed, then merged
+        router(state.clone()).oneshot(move_entity_request("npc-00000001", 4.0)).await.unwrap();
+        assert!(npc_positions(state.tick.drain()).is_empty());
+        router(state.clone()).oneshot(move_entity_request("npc-00000001", 5.0)).await.unwrap();
+        assert!(npc_positions(state.tick.drain()).is_empty());
+        assert_eq!(npc_positions(state.tick.drain()), vec![5.0]);
+
+        // Players move through their own session
+        state.entity_state.add_player("user-bystander-01".to_string(), "bystander".to_string());
+        let response = router(state).oneshot(move_entity_request("user-bystander-01", 3.0)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_admin_entities_paginates() {
+        init_test_service_key();
+        let state = test_state();
+        for i in 0..3 {
+            state.entity_state.add_npc(format!("npc-0000000{i}"));
+        }
+        let request = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = router(state.clone()).oneshot(request("/admin/entities?offset=2&limit=2")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["total"], 3);
+        assert_eq!(body["entities"].as_array().unwrap().len(), 1);
+        assert_eq!(body["entities"][0]["entity_id"], "npc-00000002");
+
+        let body = json_body(router(state).oneshot(request("/admin/entities?offset=5")).await.unwrap()).await;
+        assert_eq!(body["entities"].as_array().unwrap().len(), 0);
+        assert_eq!(body["limit"], DEFAULT_ENTITY_PAGE_LIMIT);
+    }
+
+    #[tokio::test]
+    async fn test_admin_harvest_audit_filters_by_player() {
+        init_test_service_key();
+        let state = test_state();
+        let harvest = |player_id: &str, x: f32| {
+            let request = HarvestObjectRequest { object_id: "tree_0_0_idx_0".to_string(), player_position: Position::new(x, 0.0, 5.0) };
+            state.environment_manager.handle_harvest_request(player_id, request, None)
+        };
+        assert!(!harvest("user-cheater-0002", 40.0).success);
+        assert!(harvest("user-harvester-0001", 5.0).success);
+
+        let request = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = router(state.clone()).oneshot(request("/admin/audit/harvests?player=user-cheater-0002")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        assert_eq!(body[0]["result"], "rejected");
+        assert_eq!(body[0]["error_code"], "too_far");
+
+        let body = json_body(router(state).oneshot(request("/admin/audit/harvests")).await.unwrap()).await;
+        assert_eq!(body[0]["result"], "granted");
+        assert_eq!(body[0]["player_id"], "user-harvester-0001");
+        assert_eq!(body[1]["player_id"], "user-cheater-0002");
+    }
+
+    #[tokio::test]
+    async fn test_admin_anticheat_shadow_mode_lets_far_harvest_through() {
+        init_test_service_key();
+        let state = test_state();
+        let far_harvest = || {
+            let request = HarvestObjectRequest { object_id: "tree_0_0_idx_0".to_string(), player_position: Position::new(40.0, 0.0, 5.0) };
+            state.environment_manager.handle_harvest_request("user-harvester-0001", request, None)
+        };
+        assert_eq!(far_harvest().error_code, Some(crate::game::HarvestErrorCode::TooFar));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/admin/anticheat")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::from(r#"{"mode":"shadow"}"#))
+            .unwrap();
+        let response = router(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["mode"], "shadow");
+        assert_eq!(body["violations"]["harvest_range"], 1);
+
+        assert!(far_harvest().success);
+        assert_eq!(state.anticheat.violations().harvest_range, 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_entities_sends_moves_as_ndjson() {
+        init_test_service_key();
+        let state = test_state();
+        let request = Request::builder()
+            .uri("/stream/entities")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = router(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], NDJSON_CONTENT_TYPE);
+        assert_eq!(state.events.subscriber_count(), 1);
+
+        state.entity_state.add_player("user-walker-0001".to_string(), "walker".to_string());
+        state.entity_state.update_position("user-walker-0001", Position::new(3.0, 0.0, 4.0), None);
+
+        let mut body = response.into_body();
+        let frame = tokio::time::timeout(Duration::from_secs(1), body.frame()).await.unwrap().unwrap().unwrap();
+        let line = frame.into_data().unwrap();
+        assert_eq!(line.last(), Some(&b'\n'));
+        let update: serde_json::Value = serde_json::from_slice(&line).unwrap();
+        assert_eq!(update["type"], "entity_moved");
+        assert_eq!(update["entity_id"], "user-walker-0001");
+        assert_eq!(update["position"]["x"], 3.0);
+
+        // Client disconnect drops the body and its subscription
+        drop(body);
+        assert_eq!(state.events.subscriber_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_entities_requires_admin() {
+        init_test_service_key();
+        let request = Request::builder().uri("/stream/entities").body(Body::empty()).unwrap();
+        let response = router(test_state()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_admin_respawn_unknown_object_is_404() {
+        init_test_service_key();
+        let (app, _) = test_router();
+        let response = app.oneshot(respawn_request("tree_9_9_idx_99")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_debug_noise_grid() {
+        init_test_service_key();
+        let (app, _) = test_router();
+        let request = Request::builder()
+            .uri("/debug/noise?x0=-50&z0=10&w=12&h=7&layer=rock&step=2.5")
+            .header("authorization", format!("Bearer {TEST_SERVICE_KEY}"))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        let values = body["values"].as_array().unwrap();
+        assert_eq!(values.len(), 7);
+        for row in values {
+            let row = row.as_array().unwrap();
+            assert_eq!(row.len(), 12);
+            assert!(row.iter().all(|v| (-1.0..=1.0).contains(&v.as_f64().unwrap())));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_banned_user_upgrade_rejected() {
+        let state = test_state();
+        state.sessions.ban("user-harvester-0001", "cheating");
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state)).await.unwrap() });
+
+        let response = reqwest::Client::new()
+            .get(format!("http://{addr}/ws"))
+            .header("connection", "upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-version", "13")
+            .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .bearer_auth(TEST_TOKEN)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_rejected_when_player_cap_reached() {
+        const ADMIN_TOKEN: &str = "test-admin-token";
+        let mut state = test_state();
+        state.entity_state = state.entity_state.clone().with_max_players(1);
+        state.jwt_cache.insert(ADMIN_TOKEN.to_string(), TokenInfo {
+            user_id: "admin-000000001".to_string(),
+            email: None,
+            role: ADMIN_ROLE.to_string(),
+            app_metadata: None,
+            expires_at: chrono::Utc::now().timestamp() + 3600,
+            verified_at: std::time::Instant::now(),
+        });
+        state.entity_state.add_player("user-first-00001".to_string(), "first".to_string()).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state)).await.unwrap() });
+
+        let upgrade = |query: &str, token: &str| {
+            reqwest::Client::new()
+                .get(format!("http://{addr}/ws{query}"))
+                .header("connection", "upgrade")
+                .header("upgrade", "websocket")
+                .header("sec-websocket-version", "13")
+                .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+                .bearer_auth(token)
+                .send()
+        };
+
+        let response = upgrade("", TEST_TOKEN).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert!(response.headers().get("retry-after").is_some());
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["type"], "server_full");
+        assert_eq!(body["current"], 1);
+        assert_eq!(body["max"], 1);
+
+        // Spectators and admins aren't counted against the cap
+        let response = upgrade("?spectator=true", TEST_TOKEN).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::SWITCHING_PROTOCOLS);
+        let response = upgrade("", ADMIN_TOKEN).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::SWITCHING_PROTOCOLS);
+    }
+
+    #[tokio::test]
+    async fn test_openapi_document_lists_routes() {
+        let (app, _) = test_router();
+        let request = Request::builder().uri("/openapi.json").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let doc = json_body(response).await;
+
+        assert!(doc["openapi"].as_str().unwrap().starts_with("3."));
+        assert_eq!(doc["components"]["securitySchemes"]["serviceRole"]["scheme"], "bearer");
+        let paths = doc["paths"].as_object().unwrap();
+        for path in ["/health", "/echo", "/harvest", "/stream/entities", "/admin/kick", "/admin/runtime", "/admin/disconnect_all"] {
+            assert!(paths.contains_key(path), "{path} missing from the spec");
+        }
+        assert_eq!(doc["paths"]["/harvest"]["post"]["security"][0]["playerJwt"], serde_json::json!([]));
+
+        // Every documented path is actually routed
+        for (path, ops) in paths {
+            let method = ops.as_object().unwrap().keys().next().unwrap().to_uppercase();
+            let request = Request::builder().method(method.as_str()).uri(path.as_str()).body(Body::empty()).unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_ne!(response.status(), StatusCode::NOT_FOUND, "{method} {path} isn't routed");
+            assert_ne!(response.status(), StatusCode::METHOD_NOT_ALLOWED, "{method} {path} isn't routed");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_harvest_requires_auth() {
+        let (app, _) = test_router();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/harvest")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"objectId":"tree_0_0_idx_0","playerPosition":{"x":0,"y":0,"z":0}}"#))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,265 @@
+// src/transports/openapi.rs
+// Hand-written OpenAPI 3.1 document for the HTTP API, served at /openapi.json
+//
+// Kept next to the router rather than generated from annotations; when a route is added
+// or its body changes, update the matching entry here (the https tests check that every
+// documented path is routed). The WebSocket protocol at /ws isn't described by OpenAPI.
+
+use serde_json::{json, Value};
+
+/// Routes that take the service role key as their Bearer token
+const ADMIN_SECURITY: &str = "serviceRole";
+/// Routes that take a player's Supabase JWT
+const PLAYER_SECURITY: &str = "playerJwt";
+
+/// Build the OpenAPI document
+pub fn document() -> Value {
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "bugwars game server",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "components": {
+            "securitySchemes": {
+                PLAYER_SECURITY: {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "bearerFormat": "JWT",
+                    "description": "Supabase access token of the player",
+                },
+                ADMIN_SECURITY: {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "SUPABASE_SERVICE_ROLE_KEY; admin routes answer 503 when it isn't configured",
+                },
+            },
+            "schemas": schemas(),
+        },
+        "paths": {
+            "/health": {
+                "get": public_op("Liveness probe", text_response("OK")),
+            },
+            "/ready": {
+                "get": public_op("Readiness probe (503 while the world is still generating)", json_response(object(&[
+                    ("status", string()),
+                    ("chunks_done", integer()),
+                ]))),
+            },
+            "/echo": {
+                "post": with_body(
+                    public_op("Round-trip a name through the app bus", json_response(object(&[("message", string())]))),
+                    object(&[("name", string())]),
+                ),
+            },
+            "/stats": {
+                "get": public_op("Server counters from the app bus", json_response(json!({ "type": "object" }))),
+            },
+            "/recipes": {
+                "get": public_op("Crafting recipes", json_response(json!({ "type": "array", "items": { "type": "object" } }))),
+            },
+            "/harvest": {
+                "post": with_body(
+                    secured_op(PLAYER_SECURITY, "Harvest an environment object (422 when the harvest is refused)", json_response(schema_ref("HarvestObjectResponse"))),
+                    schema_ref("HarvestObjectRequest"),
+                ),
+            },
+            "/admin/kick": {
+                "post": with_body(
+                    admin_op("Disconnect a player, optionally banning them"),
+                    object(&[("user_id", string()), ("reason", string()), ("ban", boolean())]),
+                ),
+            },
+            "/admin/runtime": {
+                "get": admin_op("Build and runtime diagnostics"),
+            },
+            "/admin/world/reseed": {
+                "post": with_body(admin_op("Regenerate the world with a new seed"), object(&[("seed", integer())])),
+            },
+            "/admin/env/respawn": {
+                "post": with_body(admin_op("Respawn a harvested object now"), object(&[("object_id", string())])),
+            },
+            "/admin/recipes/reload": {
+                "post": admin_op("Reload crafting recipes from disk"),
+            },
+            "/admin/audit/harvests": {
+                "get": with_params(admin_op("Recent harvest attempts, newest first"), &[
+                    ("player", string(), false),
+                    ("limit", integer(), false),
+                ]),
+            },
+            "/admin/entities": {
+                "get": with_params(admin_op("Tracked entities one page at a time, ordered by id"), &[
+                    ("offset", integer(), false),
+                    ("limit", integer(), false),
+                ]),
+            },
+            "/admin/entities/move": {
+                "post": with_body(
+                    admin_op("Move an NPC, enemy or boss (players are refused)"),
+                    object(&[("entity_id", string()), ("position", schema_ref("Position")), ("rotation", schema_ref("Rotation"))]),
+                ),
+            },
+            "/admin/recordings/replay": {
+                "post": with_body(
+                    admin_op("Replay a recorded session against an empty copy of the world"),
+                    object(&[("file", string())]),
+                ),
+            },
+            "/admin/entities/damage": {
+                "post": with_body(
+                    admin_op("Deal damage between entities (team and PvP rules apply)"),
+                    object(&[("attacker_id", string()), ("target_id", string()), ("amount", number())]),
+                ),
+            },
+            "/admin/entities/team": {
+                "post": with_body(
+                    admin_op("Put an entity on a team, or take it off with a null team"),
+                    object(&[("entity_id", string()), ("team", json!({ "type": ["string", "null"] }))]),
+                ),
+            },
+            "/admin/anticheat": {
+                "get": admin_op("Anti-cheat mode and violation counters"),
+                "post": with_body(
+                    admin_op("Switch anti-cheat between enforce and shadow mode"),
+                    object(&[("mode", json!({ "type": "string", "enum": ["enforce", "shadow"] }))]),
+                ),
+            },
+            "/admin/acks": {
+                "get": admin_op("WebSocket ack counts, skipped sequence numbers and ack latency"),
+            },
+            "/admin/disconnect_all": {
+                "post": with_body(
+                    admin_op("Warn every session, then disconnect them after a grace period"),
+                    object(&[("reason", string()), ("grace_seconds", integer())]),
+                ),
+            },
+            "/stream/entities": {
+                "get": admin_op("Newline-delimited JSON stream of entity updates"),
+            },
+            "/debug/noise": {
+                "get": with_params(admin_op("Sample a world generation noise layer"), &[
+                    ("x0", number(), true),
+                    ("z0", number(), true),
+                    ("w", integer(), true),
+                    ("h", integer(), true),
+                    ("layer", json!({ "type": "string", "enum": ["tree_density", "tree_type", "rock", "bush"] }), true),
+                    ("step", number(), false),
+                ]),
+            },
+            "/openapi.json": {
+                "get": public_op("This document", json_response(json!({ "type": "object" }))),
+            },
+        },
+    })
+}
+
+fn schemas() -> Value {
+    let position = object(&[("x", number()), ("y", number()), ("z", number())]);
+    json!({
+        "Position": position,
+        "Rotation": object(&[("x", number()), ("y", number()), ("z", number()), ("w", number())]),
+        "HarvestObjectRequest": object(&[
+            ("objectId", string()),
+            ("playerPosition", schema_ref("Position")),
+        ]),
+        "HarvestObjectResponse": object(&[
+            ("success", boolean()),
+            ("objectId", string()),
+            ("playerId", string()),
+            ("resourceType", string()),
+            ("resourceAmount", integer()),
+            ("errorMessage", string()),
+            ("errorCode", string()),
+            ("effectId", string()),
+            ("soundId", string()),
+        ]),
+        "ApiError": object(&[("error", string()), ("code", string())]),
+    })
+}
+
+fn public_op(summary: &str, ok: Value) -> Value {
+    json!({
+        "summary": summary,
+        "responses": { "200": ok },
+    })
+}
+
+fn secured_op(scheme: &str, summary: &str, ok: Value) -> Value {
+    json!({
+        "summary": summary,
+        "security": [{ scheme: [] }],
+        "responses": {
+            "200": ok,
+            "401": { "description": "Missing, invalid or expired token" },
+        },
+    })
+}
+
+fn admin_op(summary: &str) -> Value {
+    json!({
+        "summary": summary,
+        "security": [{ ADMIN_SECURITY: [] }],
+        "responses": {
+            "200": json_response(json!({ "type": "object" })),
+            "403": { "description": "Wrong or missing service role key" },
+            "503": json_response(schema_ref("ApiError")),
+        },
+    })
+}
+
+fn with_body(mut op: Value, schema: Value) -> Value {
+    op["requestBody"] = json!({
+        "required": true,
+        "content": { "application/json": { "schema": schema } },
+    });
+    op
+}
+
+fn with_params(mut op: Value, params: &[(&str, Value, bool)]) -> Value {
+    op["parameters"] = params
+        .iter()
+        .map(|(name, schema, required)| json!({ "name": name, "in": "query", "required": required, "schema": schema }))
+        .collect();
+    op
+}
+
+fn json_response(schema: Value) -> Value {
+    json!({
+        "description": "Success",
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+fn text_response(example: &str) -> Value {
+    json!({
+        "description": "Success",
+        "content": { "text/plain": { "schema": { "type": "string", "example": example } } },
+    })
+}
+
+fn object(properties: &[(&str, Value)]) -> Value {
+    let properties: serde_json::Map<String, Value> =
+        properties.iter().map(|(name, schema)| (name.to_string(), schema.clone())).collect();
+    json!({ "type": "object", "properties": properties })
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+fn integer() -> Value {
+    json!({ "type": "integer" })
+}
+
+fn number() -> Value {
+    json!({ "type": "number" })
+}
+
+fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
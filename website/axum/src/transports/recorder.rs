@@ -0,0 +1,206 @@
+// src/transports/recorder.rs
+// Opt-in per-session message recorder and replay harness for debugging
+//
+// With SESSION_RECORDING=1 every WebSocket session keeps a ring buffer of inbound
+// GameMessages and outbound ServerMessages. When SESSION_RECORDING_DIR is set the log
+// is written there as `<user_id>-<session_id>.json` when the session ends. `replay`
+// feeds a recorded log back through the game message handler to reproduce state;
+// POST /admin/recordings/replay runs it on a file from that directory.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::game::{EntityStateManager, EnvironmentManager, GameMessage, ServerMessage, TickBatcher};
+use super::sessions::SessionRegistry;
+
+/// Default number of messages kept per session (oldest are dropped)
+pub const DEFAULT_RECORDING_CAPACITY: usize = 10_000;
+
+/// Recording settings (built by `Config` from the SESSION_RECORDING* variables)
+#[derive(Debug, Clone)]
+pub struct RecordingConfig {
+    pub capacity: usize,
+    /// Directory to write session logs to (None keeps them in memory only)
+    pub dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    pub timestamp_ms: i64,
+    pub user_id: String,
+    pub direction: Direction,
+    pub payload: serde_json::Value,
+}
+
+/// Recorded log of one session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLog {
+    pub user_id: String,
+    pub user_email: Option<String>,
+    pub messages: Vec<RecordedMessage>,
+}
+
+impl SessionLog {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let raw = std::fs::read_to_string(path.as_ref())?;
+        Ok(serde_json::from_str(&raw)?)
+    }
+}
+
+/// Ring buffer of one session's messages
+pub struct SessionRecorder {
+    user_id: String,
+    user_email: Option<String>,
+    capacity: usize,
+    messages: VecDeque<RecordedMessage>,
+}
+
+impl SessionRecorder {
+    pub fn new(user_id: &str, user_email: Option<String>, capacity: usize) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            user_email,
+            capacity: capacity.max(1),
+            messages: VecDeque::new(),
+        }
+    }
+
+    pub fn record_inbound(&mut self, msg: &GameMessage) {
+        self.push(Direction::Inbound, serde_json::to_value(msg));
+    }
+
+    pub fn record_outbound(&mut self, msg: &ServerMessage) {
+        self.push(Direction::Outbound, serde_json::to_value(msg));
+    }
+
+    fn push(&mut self, direction: Direction, payload: serde_json::Result<serde_json::Value>) {
+        let Ok(payload) = payload else {
+            return;
+        };
+        if self.messages.len() >= self.capacity {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(RecordedMessage {
+            timestamp_ms: chrono::Utc::now().timestamp_millis(),
+            user_id: self.user_id.clone(),
+            direction,
+            payload,
+        });
+    }
+
+    /// Snapshot of the recorded session
+    pub fn log(&self) -> SessionLog {
+        SessionLog {
+            user_id: self.user_id.clone(),
+            user_email: self.user_email.clone(),
+            messages: self.messages.iter().cloned().collect(),
+        }
+    }
+
+    /// Write the log to `<dir>/<user_id>-<session_id>.json`
+    pub fn write_to_dir(&self, dir: &Path, session_id: u64) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}-{}.json", self.user_id, session_id));
+        std::fs::write(&path, serde_json::to_vec(&self.log())?)?;
+        info!(user_id = %self.user_id, path = %path.display(), messages = self.messages.len(), "Wrote session recording");
+        Ok(path)
+    }
+}
+
+/// Replay a recorded session's inbound messages against the given managers
+/// Returns the handler responses in order
+pub async fn replay(
+    log: &SessionLog,
+    entity_state: &EntityStateManager,
+    environment_manager: &Arc<EnvironmentManager>,
+) -> Vec<ServerMessage> {
+    // Nobody is connected during a replay, so queued follow-up messages and broadcasts are dropped
+    let sessions = SessionRegistry::new();
+    let tick = TickBatcher::new();
+    let mut responses = Vec::new();
+    for recorded in log.messages.iter().filter(|m| m.direction == Direction::Inbound) {
+        match serde_json::from_value::<GameMessage>(recorded.payload.clone()) {
+            Ok(msg) => {
+                let response = super::https::handle_game_message(
+                    msg,
+                    &recorded.user_id,
+                    &log.user_email,
+                    entity_state,
+                    environment_manager,
+                    &sessions,
+                    &tick,
+                )
+                .await;
+                responses.push(response);
+            }
+            Err(e) => warn!(error = %e, "Skipping unreadable recorded message"),
+        }
+    }
+    responses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Position;
+
+    #[tokio::test]
+    async fn test_replay_reproduces_final_state() {
+        let user_id = "user-recorded-01";
+        let session = [
+            GameMessage::Join { position: Some(Position::new(1.0, 0.0, 2.0)), view_distance: None },
+            GameMessage::UpdatePosition { position: Position::new(10.0, 0.0, 20.0), rotation: None },
+            GameMessage::AddItem { item_id: "wood".to_string(), quantity: 7 },
+            GameMessage::RemoveItem { item_id: "wood".to_string(), quantity: 2 },
+            GameMessage::UpdateHealth { health: 42.0 },
+        ];
+
+        // Record a live session
+        let entity_state = EntityStateManager::new(120);
+        let environment_manager = Arc::new(EnvironmentManager::new(50.0, 3, 10.0));
+        let mut recorder = SessionRecorder::new(user_id, None, 64);
+        for msg in session {
+            recorder.record_inbound(&msg);
+            let response = crate::transports::https::handle_game_message(msg, user_id, &None, &entity_state, &environment_manager, &SessionRegistry::new(), &TickBatcher::new()).await;
+            recorder.record_outbound(&response);
+        }
+
+        // Round-trip the log through JSON and replay it against fresh managers
+        let log: SessionLog = serde_json::from_str(&serde_json::to_string(&recorder.log()).unwrap()).unwrap();
+        let replay_state = EntityStateManager::new(120);
+        let replay_env = Arc::new(EnvironmentManager::new(50.0, 3, 10.0));
+        let responses = replay(&log, &replay_state, &replay_env).await;
+        assert_eq!(responses.len(), 5);
+
+        let original = entity_state.get_entity(user_id).unwrap();
+        let replayed = replay_state.get_entity(user_id).unwrap();
+        assert_eq!(replayed.position, original.position);
+        assert_eq!(replayed.health, original.health);
+        assert_eq!(replayed.display_name, original.display_name);
+        assert_eq!(replayed.inventory.items, original.inventory.items);
+    }
+
+    #[test]
+    fn test_recorder_ring_buffer_drops_oldest() {
+        let mut recorder = SessionRecorder::new("user-1", None, 2);
+        for _ in 0..3 {
+            recorder.record_inbound(&GameMessage::Ping);
+        }
+        recorder.record_outbound(&ServerMessage::Pong { timestamp: 1 });
+
+        let log = recorder.log();
+        assert_eq!(log.messages.len(), 2);
+        assert_eq!(log.messages[1].direction, Direction::Outbound);
+    }
+}
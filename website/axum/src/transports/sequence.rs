@@ -0,0 +1,190 @@
+// src/transports/sequence.rs
+// Outbound sequence numbers and client acks for WebSocket sessions
+//
+// Every JSON ServerMessage a session sends is stamped with a per-session `seq` that
+// starts at 1 and only goes up. Clients may answer with `{"type":"ack","seq":N}`;
+// acks are optional, so the server only uses them for metrics: round-trip latency
+// of acked frames and how many sequence numbers clients skipped over.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Send times kept per session for latency measurement; older frames can still be
+/// acked (and count towards gaps) but report no latency
+const MAX_PENDING_ACKS: usize = 1024;
+
+/// Per-session outbound sequence counter and unacked send times
+#[derive(Debug, Default)]
+pub struct OutboundSequence {
+    last_sent: u64,
+    last_acked: u64,
+    pending: VecDeque<(u64, Instant)>,
+}
+
+/// Result of a client ack that moved the session forward
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AckRecord {
+    /// Time since the acked frame was sent (None if it fell out of the pending window)
+    pub latency: Option<Duration>,
+    /// Sequence numbers between the previous ack and this one that were never acked
+    pub skipped: u64,
+}
+
+/// Why an ack was ignored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckRejected {
+    /// Acked a sequence number the server hasn't sent yet
+    Unknown,
+    /// Acked a sequence number at or below the last ack
+    Stale,
+}
+
+impl OutboundSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sequence number of the most recently stamped frame (0 before the first)
+    pub fn last_sent(&self) -> u64 {
+        self.last_sent
+    }
+
+    /// Stamp the next sequence number into a serialized JSON object
+    /// Anything that isn't a JSON object is returned untouched and uses no number
+    pub fn stamp(&mut self, json: String) -> String {
+        let Some(rest) = json.strip_prefix('{') else {
+            return json;
+        };
+        self.last_sent += 1;
+        if self.pending.len() == MAX_PENDING_ACKS {
+            self.pending.pop_front();
+        }
+        self.pending.push_back((self.last_sent, Instant::now()));
+
+        let separator = if rest.trim_start().starts_with('}') { "" } else { "," };
+        format!("{{\"seq\":{}{separator}{rest}", self.last_sent)
+    }
+
+    /// Record a client ack for `seq`
+    /// Acks are per frame: frames between the previous ack and this one count as skipped
+    pub fn ack(&mut self, seq: u64) -> Result<AckRecord, AckRejected> {
+        if seq > self.last_sent {
+            return Err(AckRejected::Unknown);
+        }
+        if seq <= self.last_acked {
+            return Err(AckRejected::Stale);
+        }
+        let skipped = seq - self.last_acked - 1;
+        self.last_acked = seq;
+
+        let mut latency = None;
+        while let Some(&(pending_seq, sent_at)) = self.pending.front() {
+            if pending_seq > seq {
+                break;
+            }
+            if pending_seq == seq {
+                latency = Some(sent_at.elapsed());
+            }
+            self.pending.pop_front();
+        }
+        Ok(AckRecord { latency, skipped })
+    }
+}
+
+/// Ack counters shared by every session (served at /admin/acks)
+#[derive(Debug, Clone, Default)]
+pub struct AckMetrics {
+    acks: Arc<AtomicU64>,
+    skipped: Arc<AtomicU64>,
+    rejected: Arc<AtomicU64>,
+    timed: Arc<AtomicU64>,
+    latency_total_us: Arc<AtomicU64>,
+    latency_max_us: Arc<AtomicU64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AckStats {
+    pub acks: u64,
+    pub skipped: u64,
+    pub rejected: u64,
+    /// Mean/max over acks whose send time was still known
+    pub mean_latency_ms: f64,
+    pub max_latency_ms: f64,
+}
+
+impl AckMetrics {
+    pub fn record(&self, record: &AckRecord) {
+        self.acks.fetch_add(1, Ordering::Relaxed);
+        self.skipped.fetch_add(record.skipped, Ordering::Relaxed);
+        if let Some(latency) = record.latency {
+            let micros = latency.as_micros().min(u64::MAX as u128) as u64;
+            self.timed.fetch_add(1, Ordering::Relaxed);
+            self.latency_total_us.fetch_add(micros, Ordering::Relaxed);
+            self.latency_max_us.fetch_max(micros, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> AckStats {
+        let timed = self.timed.load(Ordering::Relaxed);
+        let total_us = self.latency_total_us.load(Ordering::Relaxed);
+        AckStats {
+            acks: self.acks.load(Ordering::Relaxed),
+            skipped: self.skipped.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            mean_latency_ms: if timed == 0 { 0.0 } else { total_us as f64 / timed as f64 / 1000.0 },
+            max_latency_ms: self.latency_max_us.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stamp_numbers_json_objects_in_order() {
+        let mut seq = OutboundSequence::new();
+        let first: serde_json::Value = serde_json::from_str(&seq.stamp(r#"{"type":"pong"}"#.to_string())).unwrap();
+        let second: serde_json::Value = serde_json::from_str(&seq.stamp("{}".to_string())).unwrap();
+        assert_eq!(first["seq"], 1);
+        assert_eq!(first["type"], "pong");
+        assert_eq!(second["seq"], 2);
+
+        // Non-objects pass through without consuming a number
+        assert_eq!(seq.stamp("[1]".to_string()), "[1]");
+        assert_eq!(seq.last_sent(), 2);
+    }
+
+    #[test]
+    fn test_ack_tracks_gaps_and_rejects_bad_seqs() {
+        let mut seq = OutboundSequence::new();
+        for _ in 0..5 {
+            seq.stamp(r#"{"type":"pong"}"#.to_string());
+        }
+
+        let record = seq.ack(1).unwrap();
+        assert_eq!(record.skipped, 0);
+        assert!(record.latency.is_some());
+
+        // 2 and 3 were never acked
+        assert_eq!(seq.ack(4).unwrap().skipped, 2);
+        assert_eq!(seq.ack(3), Err(AckRejected::Stale));
+        assert_eq!(seq.ack(6), Err(AckRejected::Unknown));
+
+        let metrics = AckMetrics::default();
+        metrics.record(&AckRecord { latency: Some(Duration::from_millis(4)), skipped: 2 });
+        metrics.record(&AckRecord { latency: None, skipped: 0 });
+        metrics.record_rejected();
+        let stats = metrics.stats();
+        assert_eq!((stats.acks, stats.skipped, stats.rejected), (2, 2, 1));
+        assert_eq!(stats.mean_latency_ms, 4.0);
+        assert_eq!(stats.max_latency_ms, 4.0);
+    }
+}
@@ -0,0 +1,300 @@
+// src/transports/sessions.rs
+// Registry of live WebSocket sessions keyed by user id
+//
+// Each ws_loop registers a bounded command channel here. Other parts of the server
+// (admin routes, broadcasts) push ServerMessages or close requests to a session
+// without owning the socket.
+
+use dashmap::{mapref::entry::Entry, DashMap};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::game::ServerMessage;
+use crate::transports::close::CloseCode;
+use crate::transports::sequence::AckMetrics;
+
+/// Per-session outbound command queue depth
+const SESSION_QUEUE_CAPACITY: usize = 256;
+
+/// Commands delivered to a running ws_loop
+#[derive(Debug)]
+pub enum SessionCommand {
+    /// Serialize and send a message to the client
+    Send(ServerMessage),
+    /// Close the connection with a close frame (preceded by a `Disconnecting` notice)
+    Close { code: CloseCode, reason: String, retry_after_ms: Option<u64> },
+}
+
+/// What to do when a user connects while already having a live session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateSessionPolicy {
+    /// Refuse the new connection, keep the existing one
+    RejectNew,
+    /// Close the existing connection and accept the new one
+    #[default]
+    ReplaceOld,
+}
+
+impl DuplicateSessionPolicy {
+    /// Parse "reject" / "replace" (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "reject" | "reject_new" => Some(Self::RejectNew),
+            "replace" | "replace_old" => Some(Self::ReplaceOld),
+            _ => None,
+        }
+    }
+}
+
+/// Registration refused because the user already has a live session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlreadyConnected;
+
+impl std::fmt::Display for AlreadyConnected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "user already has a live session")
+    }
+}
+
+/// Handle to a registered session
+#[derive(Debug, Clone)]
+struct SessionHandle {
+    session_id: u64,
+    tx: mpsc::Sender<SessionCommand>,
+}
+
+#[derive(Clone, Default)]
+pub struct SessionRegistry {
+    sessions: Arc<DashMap<String, SessionHandle>>,
+    /// Banned user ids -> reason
+    banned: Arc<DashMap<String, String>>,
+    next_session_id: Arc<AtomicU64>,
+    duplicate_policy: DuplicateSessionPolicy,
+    /// Client ack counters across all sessions
+    acks: AckMetrics,
+}
+
+impl SessionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how duplicate connections for the same user are handled
+    pub fn with_duplicate_policy(mut self, policy: DuplicateSessionPolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
+    pub fn duplicate_policy(&self) -> DuplicateSessionPolicy {
+        self.duplicate_policy
+    }
+
+    pub fn ack_metrics(&self) -> &AckMetrics {
+        &self.acks
+    }
+
+    /// Register a session for a user, returning its id and command receiver
+    /// An existing session is either kept (RejectNew -> Err) or told to close (ReplaceOld)
+    pub fn register(&self, user_id: &str) -> Result<(u64, mpsc::Receiver<SessionCommand>), AlreadyConnected> {
+        let (tx, rx) = mpsc::channel(SESSION_QUEUE_CAPACITY);
+        let session_id = self.next_session_id.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let previous = match self.sessions.entry(user_id.to_string()) {
+            Entry::Occupied(_) if self.duplicate_policy == DuplicateSessionPolicy::RejectNew => {
+                warn!(user_id = %user_id, "Duplicate session rejected");
+                return Err(AlreadyConnected);
+            }
+            Entry::Occupied(mut entry) => Some(entry.insert(SessionHandle { session_id, tx })),
+            Entry::Vacant(entry) => {
+                entry.insert(SessionHandle { session_id, tx });
+                None
+            }
+        };
+
+        if let Some(previous) = previous {
+            info!(user_id = %user_id, old_session_id = previous.session_id, session_id, "Replacing existing session");
+            let _ = previous.tx.try_send(SessionCommand::Close {
+                code: CloseCode::SessionReplaced,
+                reason: CloseCode::SessionReplaced.reason().to_string(),
+                retry_after_ms: None,
+            });
+        }
+
+        debug!(user_id = %user_id, session_id, sessions = self.sessions.len(), "Session registered");
+        Ok((session_id, rx))
+    }
+
+    /// Unregister a session (no-op if the user has since registered a newer session)
+    /// Returns true if this was the user's current session, i.e. the caller owns cleanup
+    pub fn unregister(&self, user_id: &str, session_id: u64) -> bool {
+        let removed = self.sessions.remove_if(user_id, |_, handle| handle.session_id == session_id);
+        if removed.is_some() {
+            debug!(user_id = %user_id, session_id, sessions = self.sessions.len(), "Session unregistered");
+        }
+        removed.is_some()
+    }
+
+    /// Id of the user's current session
+    pub fn current_session_id(&self, user_id: &str) -> Option<u64> {
+        self.sessions.get(user_id).map(|h| h.session_id)
+    }
+
+    pub fn is_connected(&self, user_id: &str) -> bool {
+        self.sessions.contains_key(user_id)
+    }
+
+    pub fn session_count(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// User ids with a live session
+    pub fn connected_user_ids(&self) -> Vec<String> {
+        self.sessions.iter().map(|entry| entry.key().clone()).collect()
+    }
+
+    /// Queue a message for a user's session, returns false if not connected or queue is full
+    pub fn send_to(&self, user_id: &str, msg: ServerMessage) -> bool {
+        self.command(user_id, SessionCommand::Send(msg))
+    }
+
+    /// Kick a user: send `Kicked { reason }` then close the socket
+    /// Both commands are queued or neither is, so a full queue can't leave a kicked user
+    /// connected; returns false if the user has no live session or its queue is full
+    pub fn kick(&self, user_id: &str, reason: &str) -> bool {
+        let Some(handle) = self.sessions.get(user_id).map(|h| h.clone()) else {
+            return false;
+        };
+        let permits = match handle.tx.try_reserve_many(2) {
+            Ok(permits) => permits,
+            Err(e) => {
+                warn!(user_id = %user_id, error = %e, "Failed to queue kick");
+                return false;
+            }
+        };
+        let commands = [
+            SessionCommand::Send(ServerMessage::Kicked { reason: reason.to_string() }),
+            SessionCommand::Close { code: CloseCode::Kicked, reason: reason.to_string(), retry_after_ms: None },
+        ];
+        for (permit, command) in permits.zip(commands) {
+            permit.send(command);
+        }
+        info!(user_id = %user_id, reason = %reason, "Session kicked");
+        true
+    }
+
+    /// Close every live session with `code` (e.g. on shutdown); returns how many were told
+    pub fn close_all(&self, code: CloseCode) -> usize {
+        self.close_all_with_reason(code, code.reason())
+    }
+
+    /// Close every live session with `code` and a custom reason (e.g. a maintenance notice)
+    pub fn close_all_with_reason(&self, code: CloseCode, reason: &str) -> usize {
+        let user_ids = self.connected_user_ids();
+        user_ids
+            .iter()
+            .filter(|user_id| {
+                self.command(user_id, SessionCommand::Close { code, reason: reason.to_string(), retry_after_ms: None })
+            })
+            .count()
+    }
+
+    /// Queue a message for every live session; returns how many accepted it
+    pub fn broadcast(&self, msg: &ServerMessage) -> usize {
+        self.connected_user_ids()
+            .iter()
+            .filter(|user_id| self.send_to(user_id, msg.clone()))
+            .count()
+    }
+
+    /// Ban a user from future connections
+    pub fn ban(&self, user_id: &str, reason: &str) {
+        self.banned.insert(user_id.to_string(), reason.to_string());
+        warn!(user_id = %user_id, reason = %reason, "User banned");
+    }
+
+    pub fn unban(&self, user_id: &str) -> bool {
+        self.banned.remove(user_id).is_some()
+    }
+
+    /// Ban reason if the user is banned
+    pub fn ban_reason(&self, user_id: &str) -> Option<String> {
+        self.banned.get(user_id).map(|r| r.clone())
+    }
+
+    fn command(&self, user_id: &str, command: SessionCommand) -> bool {
+        let Some(handle) = self.sessions.get(user_id).map(|h| h.clone()) else {
+            return false;
+        };
+        match handle.tx.try_send(command) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(user_id = %user_id, error = %e, "Failed to queue session command");
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unregister_ignores_stale_session() {
+        let registry = SessionRegistry::new();
+        let (old_id, _old_rx) = registry.register("user-1").unwrap();
+        let (_new_id, _new_rx) = registry.register("user-1").unwrap();
+
+        assert!(!registry.unregister("user-1", old_id));
+        assert!(registry.is_connected("user-1"));
+    }
+
+    #[tokio::test]
+    async fn test_kick_queues_message_then_close() {
+        let registry = SessionRegistry::new();
+        let (_, mut rx) = registry.register("user-1").unwrap();
+
+        assert!(registry.kick("user-1", "spamming"));
+        assert!(matches!(rx.recv().await, Some(SessionCommand::Send(ServerMessage::Kicked { .. }))));
+        assert!(matches!(rx.recv().await, Some(SessionCommand::Close { code: CloseCode::Kicked, .. })));
+        assert!(!registry.kick("user-2", "not connected"));
+    }
+
+    #[tokio::test]
+    async fn test_kick_queues_nothing_without_room_for_the_close() {
+        let registry = SessionRegistry::new();
+        let (_, mut rx) = registry.register("user-1").unwrap();
+        for _ in 0..SESSION_QUEUE_CAPACITY - 1 {
+            assert!(registry.send_to("user-1", ServerMessage::Pong { timestamp: 0 }));
+        }
+
+        assert!(!registry.kick("user-1", "spamming"));
+        rx.close();
+        while let Some(command) = rx.recv().await {
+            assert!(matches!(command, SessionCommand::Send(ServerMessage::Pong { .. })), "got {command:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_old_policy_closes_previous_session() {
+        let registry = SessionRegistry::new().with_duplicate_policy(DuplicateSessionPolicy::ReplaceOld);
+        let (old_id, mut old_rx) = registry.register("user-1").unwrap();
+        let (new_id, _new_rx) = registry.register("user-1").unwrap();
+
+        assert!(matches!(old_rx.recv().await, Some(SessionCommand::Close { code: CloseCode::SessionReplaced, .. })));
+        assert_eq!(registry.current_session_id("user-1"), Some(new_id));
+        assert!(!registry.unregister("user-1", old_id));
+        assert_eq!(registry.session_count(), 1);
+    }
+
+    #[test]
+    fn test_reject_new_policy_keeps_existing_session() {
+        let registry = SessionRegistry::new().with_duplicate_policy(DuplicateSessionPolicy::RejectNew);
+        let (first_id, _rx) = registry.register("user-1").unwrap();
+
+        assert_eq!(registry.register("user-1").unwrap_err(), AlreadyConnected);
+        assert_eq!(registry.current_session_id("user-1"), Some(first_id));
+    }
+}